@@ -0,0 +1,51 @@
+-- Migration 0010: Create approvals table and approval task statuses
+-- Purpose: Persist approval decisions submitted via POST /approvals and let
+--          them drive the referenced task's status (approved/rejected)
+-- Dependencies: 0008_create_tasks_table (tasks table must exist for FK)
+
+-- Allow tasks to end in an approval decision
+ALTER TABLE tasks DROP CONSTRAINT IF EXISTS tasks_status_check;
+ALTER TABLE tasks ADD CONSTRAINT tasks_status_check
+  CHECK (status IN ('pending', 'active', 'completed', 'failed', 'cancelled', 'approved', 'rejected'));
+
+-- Approvals table: one row per decision submitted against a task
+CREATE TABLE IF NOT EXISTS approvals (
+    -- Primary identification
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    -- Task being decided on
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+
+    -- Who decided
+    approver VARCHAR(200) NOT NULL,       -- JWT subject of the caller
+    approver_role VARCHAR(50) NOT NULL,   -- Role the caller decided as (must match tasks.target)
+
+    -- Decision
+    decision VARCHAR(20) NOT NULL,
+    comments TEXT,
+
+    -- Tracing
+    trace_id UUID,
+
+    -- Timestamps
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    -- Constraints
+    CHECK (decision IN ('approved', 'rejected'))
+);
+
+-- Indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_approvals_task_id ON approvals(task_id);
+CREATE INDEX IF NOT EXISTS idx_approvals_approver_role ON approvals(approver_role);
+CREATE INDEX IF NOT EXISTS idx_approvals_created_at ON approvals(created_at DESC);
+
+-- Comments for documentation
+COMMENT ON TABLE approvals IS 'Approval decisions submitted against tasks';
+COMMENT ON COLUMN approvals.id IS 'Unique approval identifier (UUID)';
+COMMENT ON COLUMN approvals.task_id IS 'Task the decision applies to';
+COMMENT ON COLUMN approvals.approver IS 'Subject of the caller who submitted the decision';
+COMMENT ON COLUMN approvals.approver_role IS 'Role the caller decided as';
+COMMENT ON COLUMN approvals.decision IS 'Decision: approved or rejected';
+COMMENT ON COLUMN approvals.comments IS 'Optional free-form comments';
+COMMENT ON COLUMN approvals.trace_id IS 'Distributed tracing identifier';
+COMMENT ON COLUMN approvals.created_at IS 'Decision timestamp';
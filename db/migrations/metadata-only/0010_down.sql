@@ -0,0 +1,14 @@
+-- Rollback migration 0010: Drop approvals table and approval task statuses
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_approvals_task_id;
+DROP INDEX IF EXISTS idx_approvals_approver_role;
+DROP INDEX IF EXISTS idx_approvals_created_at;
+
+-- Drop table
+DROP TABLE IF EXISTS approvals;
+
+-- Restore original tasks status constraint (approved/rejected rows must be migrated first)
+ALTER TABLE tasks DROP CONSTRAINT IF EXISTS tasks_status_check;
+ALTER TABLE tasks ADD CONSTRAINT tasks_status_check
+  CHECK (status IN ('pending', 'active', 'completed', 'failed', 'cancelled'));
@@ -0,0 +1,48 @@
+-- Migration 0011: Create approval_steps table for multi-level approval chains
+-- Purpose: Store the ordered approvers a task needs (derived from profile
+--          approval_rules + org chart) and track each step's decision
+-- Dependencies: 0004_create_org_users, 0010_create_approvals_table
+
+CREATE TABLE IF NOT EXISTS approval_steps (
+    -- Primary identification
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    -- Task this step belongs to
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+
+    -- Position in the chain (1 = first approver)
+    step_order INTEGER NOT NULL,
+
+    -- Who must decide
+    approver_role VARCHAR(50) NOT NULL,
+    approver_user_id INTEGER REFERENCES org_users(user_id) ON DELETE SET NULL,
+
+    -- Rule that produced this step
+    rule_name VARCHAR(100) NOT NULL,
+
+    -- Step state
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    approval_id UUID REFERENCES approvals(id) ON DELETE SET NULL,
+
+    -- Timestamps
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    decided_at TIMESTAMP WITH TIME ZONE,
+
+    -- Constraints
+    UNIQUE (task_id, step_order),
+    CHECK (status IN ('pending', 'approved', 'rejected', 'skipped'))
+);
+
+-- Indexes for common query patterns
+CREATE INDEX IF NOT EXISTS idx_approval_steps_task_id ON approval_steps(task_id, step_order);
+CREATE INDEX IF NOT EXISTS idx_approval_steps_role_status ON approval_steps(approver_role, status);
+
+-- Comments for documentation
+COMMENT ON TABLE approval_steps IS 'Ordered approval chain steps per task (multi-level approvals)';
+COMMENT ON COLUMN approval_steps.step_order IS 'Position in the chain (1 = first approver)';
+COMMENT ON COLUMN approval_steps.approver_role IS 'Role that must decide this step';
+COMMENT ON COLUMN approval_steps.approver_user_id IS 'Org chart user resolved from the requester''s management chain (NULL if none found)';
+COMMENT ON COLUMN approval_steps.rule_name IS 'Profile approval rule that produced this step';
+COMMENT ON COLUMN approval_steps.status IS 'Step status: pending, approved, rejected, skipped';
+COMMENT ON COLUMN approval_steps.approval_id IS 'Approval decision that closed this step';
+COMMENT ON COLUMN approval_steps.decided_at IS 'When the step was decided';
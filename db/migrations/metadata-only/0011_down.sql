@@ -0,0 +1,8 @@
+-- Rollback migration 0011: Drop approval_steps table
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_approval_steps_task_id;
+DROP INDEX IF EXISTS idx_approval_steps_role_status;
+
+-- Drop table
+DROP TABLE IF EXISTS approval_steps;
@@ -0,0 +1,11 @@
+-- Migration 0012: Task lifecycle FSM statuses
+-- Purpose: Let agents acknowledge and work on routed tasks via
+--          PUT /tasks/:id/events (pending → claimed → in_progress → completed/failed/cancelled)
+-- Dependencies: 0010_create_approvals_table (approved/rejected statuses)
+
+ALTER TABLE tasks DROP CONSTRAINT IF EXISTS tasks_status_check;
+ALTER TABLE tasks ADD CONSTRAINT tasks_status_check
+  CHECK (status IN ('pending', 'claimed', 'in_progress', 'active', 'completed', 'failed', 'cancelled', 'approved', 'rejected'));
+
+COMMENT ON COLUMN tasks.status IS 'Current status: pending, claimed, in_progress, completed, failed, cancelled (lifecycle FSM); approved, rejected (approval decisions); active (legacy)';
+COMMENT ON COLUMN tasks.completed_at IS 'Set when the task reaches a terminal status';
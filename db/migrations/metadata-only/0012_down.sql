@@ -0,0 +1,6 @@
+-- Rollback migration 0012: Remove task lifecycle FSM statuses
+
+-- Restore migration 0010 constraint (claimed/in_progress rows must be migrated first)
+ALTER TABLE tasks DROP CONSTRAINT IF EXISTS tasks_status_check;
+ALTER TABLE tasks ADD CONSTRAINT tasks_status_check
+  CHECK (status IN ('pending', 'active', 'completed', 'failed', 'cancelled', 'approved', 'rejected'));
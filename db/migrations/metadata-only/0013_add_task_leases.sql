@@ -0,0 +1,32 @@
+-- Migration 0013: Task leases for atomic claiming
+-- Purpose: Let several agents of one role share a queue via POST /tasks/claim.
+--          A claim leases the task for a visibility timeout; unacknowledged
+--          leases expire and the task returns to the queue.
+-- Dependencies: 0012_add_task_lifecycle_statuses (claimed/in_progress statuses)
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS lease_id UUID;
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS claimed_by VARCHAR(255);
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS lease_expires_at TIMESTAMP WITH TIME ZONE;
+
+-- Claim queue scan: oldest pending tasks for a role
+CREATE INDEX IF NOT EXISTS idx_tasks_claim_queue
+ON tasks (target, created_at)
+WHERE status = 'pending';
+
+-- Lease sweeper: leases that have run out
+CREATE INDEX IF NOT EXISTS idx_tasks_lease_expires
+ON tasks (lease_expires_at)
+WHERE lease_expires_at IS NOT NULL;
+
+COMMENT ON COLUMN tasks.lease_id IS 'Current lease token (NULL when not leased); required to renew the lease';
+COMMENT ON COLUMN tasks.claimed_by IS 'Worker/agent holding the current lease';
+COMMENT ON COLUMN tasks.claimed_at IS 'When the current lease was granted';
+COMMENT ON COLUMN tasks.lease_expires_at IS 'Visibility timeout: after this the task returns to pending unless renewed';
@@ -0,0 +1,11 @@
+-- Rollback migration 0013: Drop task lease columns
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_tasks_claim_queue;
+DROP INDEX IF EXISTS idx_tasks_lease_expires;
+
+-- Drop columns
+ALTER TABLE tasks DROP COLUMN IF EXISTS lease_expires_at;
+ALTER TABLE tasks DROP COLUMN IF EXISTS claimed_at;
+ALTER TABLE tasks DROP COLUMN IF EXISTS claimed_by;
+ALTER TABLE tasks DROP COLUMN IF EXISTS lease_id;
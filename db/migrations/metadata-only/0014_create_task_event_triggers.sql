@@ -0,0 +1,65 @@
+-- Migration 0014: Publish task and approval events via LISTEN/NOTIFY
+-- Purpose: Feed GET /events/stream (Server-Sent Events) on every controller
+--          replica. Triggers publish on channel 'goose_task_events' so every
+--          write path emits events, and notifications are only delivered once
+--          the writing transaction commits.
+-- Dependencies: 0008_create_tasks_table, 0010_create_approvals_table
+
+-- Task created / status or routing changed
+CREATE OR REPLACE FUNCTION notify_task_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Lease renewals and other bookkeeping updates are not interesting to subscribers
+    IF TG_OP = 'UPDATE' AND OLD.status = NEW.status AND OLD.target = NEW.target THEN
+        RETURN NEW;
+    END IF;
+
+    PERFORM pg_notify('goose_task_events', json_build_object(
+        'event', CASE WHEN TG_OP = 'INSERT' THEN 'task_created' ELSE 'task_updated' END,
+        'task_id', NEW.id,
+        'task_type', NEW.task_type,
+        'source', NEW.source,
+        'target', NEW.target,
+        'status', NEW.status,
+        'occurred_at', NOW()
+    )::text);
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER tasks_notify_trigger
+    AFTER INSERT OR UPDATE ON tasks
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_task_event();
+
+-- Approval decided
+CREATE OR REPLACE FUNCTION notify_approval_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    t RECORD;
+BEGIN
+    SELECT task_type, source, target INTO t FROM tasks WHERE id = NEW.task_id;
+
+    PERFORM pg_notify('goose_task_events', json_build_object(
+        'event', 'approval_decided',
+        'task_id', NEW.task_id,
+        'task_type', t.task_type,
+        'source', t.source,
+        'target', t.target,
+        'decision', NEW.decision,
+        'approver_role', NEW.approver_role,
+        'occurred_at', NEW.created_at
+    )::text);
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER approvals_notify_trigger
+    AFTER INSERT ON approvals
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_approval_event();
+
+COMMENT ON FUNCTION notify_task_event() IS 'Publishes task_created/task_updated events on goose_task_events';
+COMMENT ON FUNCTION notify_approval_event() IS 'Publishes approval_decided events on goose_task_events';
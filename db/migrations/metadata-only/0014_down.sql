@@ -0,0 +1,9 @@
+-- Rollback migration 0014: Drop task/approval event triggers
+
+-- Drop triggers first
+DROP TRIGGER IF EXISTS tasks_notify_trigger ON tasks;
+DROP TRIGGER IF EXISTS approvals_notify_trigger ON approvals;
+
+-- Drop functions
+DROP FUNCTION IF EXISTS notify_task_event();
+DROP FUNCTION IF EXISTS notify_approval_event();
@@ -0,0 +1,40 @@
+-- Migration 0015: Policy decision audit log
+-- Purpose: Persist every RBAC/ABAC tool-use decision so compliance reviewers
+--          can answer "why was role X denied tool Y at time T".
+-- Dependencies: 0003_create_policies (policies table)
+
+CREATE TABLE IF NOT EXISTS policy_decisions (
+    id BIGSERIAL PRIMARY KEY,
+    role VARCHAR(50) NOT NULL,
+    tool VARCHAR(200) NOT NULL,
+    context JSONB NOT NULL DEFAULT '{}'::jsonb,
+    matched_policy_id INTEGER,  -- No FK: the audit row must outlive policy edits
+    allowed BOOLEAN NOT NULL,
+    cache_hit BOOLEAN NOT NULL DEFAULT FALSE,
+    reason TEXT,
+    subject VARCHAR(255),
+    decided_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Reviewer queries: decisions for a role/tool over a time window
+CREATE INDEX IF NOT EXISTS idx_policy_decisions_role_tool
+ON policy_decisions (role, tool, decided_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_policy_decisions_decided_at
+ON policy_decisions (decided_at DESC);
+
+-- Denials are the common compliance question
+CREATE INDEX IF NOT EXISTS idx_policy_decisions_denied
+ON policy_decisions (decided_at DESC)
+WHERE allowed = FALSE;
+
+COMMENT ON TABLE policy_decisions IS 'Audit log of RBAC/ABAC tool-use policy decisions (one row per evaluation)';
+COMMENT ON COLUMN policy_decisions.role IS 'Role the policy was evaluated for';
+COMMENT ON COLUMN policy_decisions.tool IS 'Tool name evaluated (e.g., developer__shell)';
+COMMENT ON COLUMN policy_decisions.context IS 'ABAC context the decision was made with';
+COMMENT ON COLUMN policy_decisions.matched_policy_id IS 'policies.id that decided the request (NULL = default deny)';
+COMMENT ON COLUMN policy_decisions.allowed IS 'true = allowed, false = denied';
+COMMENT ON COLUMN policy_decisions.cache_hit IS 'true when the decision was served from the Redis policy cache';
+COMMENT ON COLUMN policy_decisions.reason IS 'Denial reason (NULL when allowed)';
+COMMENT ON COLUMN policy_decisions.subject IS 'JWT subject of the caller, when known';
+COMMENT ON COLUMN policy_decisions.decided_at IS 'When the decision was made';
@@ -0,0 +1,7 @@
+-- Rollback migration 0015: Drop policy decision audit log
+
+DROP INDEX IF EXISTS idx_policy_decisions_denied;
+DROP INDEX IF EXISTS idx_policy_decisions_decided_at;
+DROP INDEX IF EXISTS idx_policy_decisions_role_tool;
+
+DROP TABLE IF EXISTS policy_decisions;
@@ -0,0 +1,6 @@
+-- Rollback migration 0016: Drop policy condition shape check
+
+ALTER TABLE policies
+DROP CONSTRAINT IF EXISTS chk_policies_conditions_object;
+
+COMMENT ON COLUMN policies.conditions IS 'JSONB conditions for attribute-based access (e.g., database patterns)';
@@ -0,0 +1,15 @@
+-- Migration 0016: Policy condition shape check
+-- Purpose: ABAC conditions are a JSON object (see the condition language in
+--          src/controller/src/policy/conditions.rs). The admin API validates
+--          the full grammar on insert/update; this constraint rejects
+--          non-object documents written directly to the table.
+-- Dependencies: 0003_create_policies (policies table)
+
+ALTER TABLE policies
+DROP CONSTRAINT IF EXISTS chk_policies_conditions_object;
+
+ALTER TABLE policies
+ADD CONSTRAINT chk_policies_conditions_object
+CHECK (conditions IS NULL OR jsonb_typeof(conditions) = 'object');
+
+COMMENT ON COLUMN policies.conditions IS 'ABAC conditions: attribute globs, numeric comparisons, time windows, any_of/all_of/not (validated by POST /admin/policies)';
@@ -0,0 +1,46 @@
+-- Migration 0017: Policy version counter
+-- Purpose: Every change to the policies table bumps a global version and
+--          publishes it on channel 'goose_policy_changes'. Controllers put the
+--          version in their policy cache keys, so decisions cached before a
+--          change are never served after it, whichever path made the change
+--          (admin API, seeds, psql).
+-- Dependencies: 0003_create_policies, 0015_create_policy_decisions
+
+-- Single-row counter
+CREATE TABLE IF NOT EXISTS policy_version (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 1,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO policy_version (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
+
+COMMENT ON TABLE policy_version IS 'Global policy version, bumped on every change to policies';
+COMMENT ON COLUMN policy_version.version IS 'Monotonic version; part of the Redis policy cache key';
+
+CREATE OR REPLACE FUNCTION bump_policy_version()
+RETURNS TRIGGER AS $$
+DECLARE
+    new_version BIGINT;
+BEGIN
+    UPDATE policy_version
+    SET version = version + 1, updated_at = NOW()
+    WHERE id
+    RETURNING version INTO new_version;
+
+    PERFORM pg_notify('goose_policy_changes', new_version::text);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER policies_bump_version_trigger
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON policies
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION bump_policy_version();
+
+-- Record which policy version produced each audited decision
+ALTER TABLE policy_decisions
+ADD COLUMN IF NOT EXISTS policy_version BIGINT;
+
+COMMENT ON COLUMN policy_decisions.policy_version IS 'policy_version.version the decision was evaluated (or cached) under';
@@ -0,0 +1,10 @@
+-- Rollback migration 0017: Drop policy version counter
+
+ALTER TABLE policy_decisions DROP COLUMN IF EXISTS policy_version;
+
+-- Drop trigger first
+DROP TRIGGER IF EXISTS policies_bump_version_trigger ON policies;
+
+-- Drop function and table
+DROP FUNCTION IF EXISTS bump_policy_version();
+DROP TABLE IF EXISTS policy_version;
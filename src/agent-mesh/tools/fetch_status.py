@@ -65,7 +65,8 @@ async def fetch_status_handler(params: FetchStatusParams) -> list[TextContent]:
     headers = {
         "Authorization": f"Bearer {jwt_token}",
         "X-Trace-ID": trace_id,
-        "Content-Type": "application/json"
+        "Content-Type": "application/json",
+        "X-Tool-Name": "agent_mesh__fetch_status",  # Controller policy enforcement
     }
     
     try:
@@ -104,6 +104,7 @@ async def notify_handler(params: NotifyParams) -> list[TextContent]:
         "Content-Type": "application/json",
         "idempotency-key": idempotency_key,  # lowercase per Axum requirements
         "X-Trace-Id": trace_id,
+        "X-Tool-Name": "agent_mesh__notify",  # Controller policy enforcement
     }
 
     # Prepare request payload
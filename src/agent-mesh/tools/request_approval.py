@@ -91,6 +91,7 @@ async def request_approval_handler(params: RequestApprovalParams) -> list[TextCo
         "Content-Type": "application/json",
         "idempotency-key": idempotency_key,  # lowercase per Axum requirements
         "X-Trace-Id": trace_id,
+        "X-Tool-Name": "agent_mesh__request_approval",  # Controller policy enforcement
     }
     
     # Construct task payload matching Controller API format
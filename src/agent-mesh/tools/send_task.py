@@ -99,6 +99,7 @@ async def send_task_handler(params: SendTaskParams) -> list[TextContent]:
                     "Content-Type": "application/json",
                     "idempotency-key": idempotency_key,  # lowercase per Axum requirements
                     "X-Trace-Id": trace_id,
+                    "X-Tool-Name": "agent_mesh__send_task",  # Controller policy enforcement
                 },
                 json={
                     "target": params.target,
@@ -15,11 +15,18 @@ use utoipa::openapi::security::{SecurityScheme, HttpAuthScheme, HttpBuilder};
     ),
     paths(
         crate::routes::tasks::route_task,
+        crate::routes::tasks::handle_task_event,
+        crate::routes::tasks::claim_tasks,
+        crate::routes::tasks::renew_task_lease,
+        crate::routes::events::stream_events,
         crate::routes::sessions::list_sessions,
         crate::routes::sessions::create_session,
         crate::routes::approvals::submit_approval,
+        crate::routes::approvals::get_approval_chain,
         crate::routes::profiles::get_profile,
         crate::routes::privacy::submit_audit_log,
+        crate::routes::policy::evaluate_policy,
+        crate::routes::policy::list_policy_decisions,
         crate::status,
         crate::audit_ingest,
     ),
@@ -28,13 +35,30 @@ use utoipa::openapi::security::{SecurityScheme, HttpAuthScheme, HttpBuilder};
             crate::routes::tasks::RouteTaskRequest,
             crate::routes::tasks::RouteTaskResponse,
             crate::routes::tasks::TaskPayload,
+            crate::routes::tasks::TaskEventRequest,
+            crate::routes::tasks::ClaimTasksRequest,
+            crate::routes::tasks::ClaimTasksResponse,
+            crate::routes::tasks::RenewLeaseRequest,
+            crate::models::Task,
+            crate::models::ClaimedTask,
+            crate::events::TaskEvent,
             crate::routes::sessions::CreateSessionRequest,
             crate::routes::sessions::CreateSessionResponse,
             crate::routes::sessions::SessionResponse,
             crate::routes::approvals::SubmitApprovalRequest,
             crate::routes::approvals::SubmitApprovalResponse,
+            crate::routes::approvals::ApprovalChainResponse,
+            crate::models::Approval,
+            crate::models::ApprovalStep,
             crate::routes::privacy::AuditLogEntry,
             crate::routes::privacy::AuditLogResponse,
+            crate::routes::policy::EvaluatePolicyRequest,
+            crate::routes::policy::EvaluatePolicyResponse,
+            crate::routes::policy::ListPolicyDecisionsResponse,
+            crate::policy::PolicyContext,
+            crate::policy::PolicyCandidate,
+            crate::policy::CandidateOutcome,
+            crate::policy::PolicyDecisionRecord,
             // Phase 5: Profile endpoints now return Profile schema directly
             crate::StatusResponse,
             crate::AuditEvent,
@@ -46,6 +70,7 @@ use utoipa::openapi::security::{SecurityScheme, HttpAuthScheme, HttpBuilder};
         (name = "approvals", description = "Approval workflows"),
         (name = "profiles", description = "Agent profile discovery"),
         (name = "privacy", description = "Privacy Guard audit logs"),
+        (name = "policy", description = "RBAC/ABAC tool-use policy evaluation and decision audit log"),
         (name = "system", description = "System health and audit"),
     ),
     modifiers(&SecurityAddon)
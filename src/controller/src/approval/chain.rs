@@ -0,0 +1,329 @@
+// Approval Chain Engine
+//
+// Builds the ordered approval steps a task needs before it can be approved.
+// Rules come from the requester's profile (`approval_rules`); approvers are
+// resolved by walking the requester's management chain upward through
+// org_users.reports_to_id.
+
+use serde_json::Value;
+use sqlx::PgPool;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::models::CreateTaskRequest;
+use crate::profile::ApprovalRule;
+
+/// Upper bound on management chain depth (guards against cycles in org data)
+const MAX_CHAIN_DEPTH: i32 = 32;
+
+/// Org chart user as seen by the chain engine
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OrgUser {
+    pub user_id: i32,
+    pub reports_to_id: Option<i32>,
+    /// Effective profile role (assigned_profile overrides role)
+    pub role: String,
+    pub email: String,
+}
+
+/// Approval step planned for a task (not yet persisted)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStep {
+    pub step_order: i32,
+    pub approver_role: String,
+    pub approver_user_id: Option<i32>,
+    pub rule_name: String,
+}
+
+/// Approval chain errors
+#[derive(Error, Debug)]
+pub enum ChainError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Invalid approval rules for role '{role}': {reason}")]
+    InvalidRules { role: String, reason: String },
+}
+
+/// Approval chain engine
+pub struct ApprovalChainEngine {
+    pool: PgPool,
+}
+
+impl ApprovalChainEngine {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Plan the approval chain for a task about to be created
+    ///
+    /// `requester_email` is the authenticated caller's email (never taken from
+    /// the task itself, so callers cannot pick whose chain applies). Returns an
+    /// empty list when no approval rule applies, in which case the task's
+    /// target role decides alone.
+    pub async fn plan(
+        &self,
+        req: &CreateTaskRequest,
+        requester_email: Option<&str>,
+    ) -> Result<Vec<PlannedStep>, ChainError> {
+        let requester = match requester_email {
+            Some(email) => self.find_user_by_email(email).await?,
+            None => None,
+        };
+
+        // Rules come from the requester's profile, falling back to the source role
+        let role = requester
+            .as_ref()
+            .map(|u| u.role.clone())
+            .unwrap_or_else(|| req.source.clone());
+
+        let rules = self.load_rules(&role).await?;
+        let data = req.data.clone().unwrap_or_else(|| serde_json::json!({}));
+
+        let Some(rule) = select_rule(&rules, &req.task_type, &data) else {
+            return Ok(Vec::new());
+        };
+
+        let chain = match &requester {
+            Some(user) => self.management_chain(user.user_id).await?,
+            None => {
+                warn!(
+                    message = "approval.chain.no_requester",
+                    role = %role,
+                    rule = %rule.name,
+                    "Requester not found in org chart, approvers resolved by role only"
+                );
+                Vec::new()
+            }
+        };
+
+        let steps = plan_steps(rule, &chain);
+
+        info!(
+            message = "approval.chain.planned",
+            role = %role,
+            rule = %rule.name,
+            task_type = %req.task_type,
+            steps = steps.len()
+        );
+
+        Ok(steps)
+    }
+
+    /// Load approval rules from a role's profile (empty if none)
+    async fn load_rules(&self, role: &str) -> Result<Vec<ApprovalRule>, ChainError> {
+        let rules = sqlx::query_scalar::<_, Option<Value>>(
+            "SELECT data->'approval_rules' FROM profiles WHERE role = $1",
+        )
+        .bind(role)
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten();
+
+        match rules {
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(value) => serde_json::from_value(value).map_err(|e| ChainError::InvalidRules {
+                role: role.to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    /// Find an org chart user by email
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<OrgUser>, ChainError> {
+        let user = sqlx::query_as::<_, OrgUser>(
+            r#"
+            SELECT user_id, reports_to_id, COALESCE(assigned_profile, role) AS role, email
+            FROM org_users
+            WHERE email = $1
+            "#,
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Walk reports_to_id upward from a user (nearest manager first)
+    async fn management_chain(&self, user_id: i32) -> Result<Vec<OrgUser>, ChainError> {
+        let chain = sqlx::query_as::<_, OrgUser>(
+            r#"
+            WITH RECURSIVE chain AS (
+                SELECT m.user_id, m.reports_to_id,
+                       COALESCE(m.assigned_profile, m.role) AS role, m.email, 1 AS depth
+                FROM org_users u
+                JOIN org_users m ON m.user_id = u.reports_to_id
+                WHERE u.user_id = $1
+                UNION ALL
+                SELECT m.user_id, m.reports_to_id,
+                       COALESCE(m.assigned_profile, m.role), m.email, c.depth + 1
+                FROM chain c
+                JOIN org_users m ON m.user_id = c.reports_to_id
+                WHERE c.depth < $2
+            )
+            SELECT user_id, reports_to_id, role, email
+            FROM chain
+            ORDER BY depth
+            "#,
+        )
+        .bind(user_id)
+        .bind(MAX_CHAIN_DEPTH)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(chain)
+    }
+}
+
+/// Read a numeric attribute from task data
+///
+/// Accepts JSON numbers and numeric strings such as "12000" or "$12,000.50".
+fn attribute_value(data: &Value, attribute: &str) -> Option<f64> {
+    match data.get(attribute)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => {
+            let cleaned: String = s
+                .chars()
+                .filter(|c| !matches!(c, '$' | ',' | ' '))
+                .collect();
+            cleaned.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Select the applicable rule for a task
+///
+/// A rule applies when its task_type (if set) matches and the task's attribute
+/// exceeds its threshold. When several apply, the highest threshold wins.
+pub fn select_rule<'a>(
+    rules: &'a [ApprovalRule],
+    task_type: &str,
+    data: &Value,
+) -> Option<&'a ApprovalRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.task_type.as_deref().is_none_or(|t| t == task_type))
+        .filter(|rule| {
+            attribute_value(data, &rule.attribute).is_some_and(|v| v > rule.greater_than)
+        })
+        .max_by(|a, b| a.greater_than.total_cmp(&b.greater_than))
+}
+
+/// Resolve a rule's approver roles against a management chain
+///
+/// Each approver role is assigned to the nearest manager holding that role.
+/// If nobody in the chain holds it, the step is left to any user of the role.
+pub fn plan_steps(rule: &ApprovalRule, chain: &[OrgUser]) -> Vec<PlannedStep> {
+    rule.approvers
+        .iter()
+        .enumerate()
+        .map(|(i, role)| PlannedStep {
+            step_order: i as i32 + 1,
+            approver_role: role.clone(),
+            approver_user_id: chain.iter().find(|u| &u.role == role).map(|u| u.user_id),
+            rule_name: rule.name.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn finance_rules() -> Vec<ApprovalRule> {
+        vec![
+            ApprovalRule {
+                name: "spend-over-10k".to_string(),
+                task_type: None,
+                attribute: "amount".to_string(),
+                greater_than: 10000.0,
+                approvers: vec!["manager".to_string()],
+            },
+            ApprovalRule {
+                name: "spend-over-50k".to_string(),
+                task_type: None,
+                attribute: "amount".to_string(),
+                greater_than: 50000.0,
+                approvers: vec!["finance".to_string(), "manager".to_string()],
+            },
+        ]
+    }
+
+    fn user(user_id: i32, reports_to_id: Option<i32>, role: &str) -> OrgUser {
+        OrgUser {
+            user_id,
+            reports_to_id,
+            role: role.to_string(),
+            email: format!("user{}@example.com", user_id),
+        }
+    }
+
+    #[test]
+    fn test_select_rule_below_threshold() {
+        let rules = finance_rules();
+        assert!(select_rule(&rules, "budget_approval", &json!({"amount": 5000})).is_none());
+        assert!(select_rule(&rules, "budget_approval", &json!({"amount": 10000})).is_none());
+    }
+
+    #[test]
+    fn test_select_rule_highest_threshold_wins() {
+        let rules = finance_rules();
+
+        let rule = select_rule(&rules, "budget_approval", &json!({"amount": 20000})).unwrap();
+        assert_eq!(rule.name, "spend-over-10k");
+
+        let rule = select_rule(&rules, "budget_approval", &json!({"amount": 75000})).unwrap();
+        assert_eq!(rule.name, "spend-over-50k");
+    }
+
+    #[test]
+    fn test_select_rule_task_type_filter() {
+        let mut rules = finance_rules();
+        rules[0].task_type = Some("budget_approval".to_string());
+
+        assert!(select_rule(&rules[..1], "notification", &json!({"amount": 20000})).is_none());
+        assert!(select_rule(&rules[..1], "budget_approval", &json!({"amount": 20000})).is_some());
+    }
+
+    #[test]
+    fn test_attribute_value_formats() {
+        assert_eq!(attribute_value(&json!({"amount": 12000}), "amount"), Some(12000.0));
+        assert_eq!(attribute_value(&json!({"amount": "12000"}), "amount"), Some(12000.0));
+        assert_eq!(attribute_value(&json!({"amount": "$12,000.50"}), "amount"), Some(12000.5));
+        assert_eq!(attribute_value(&json!({"amount": "lots"}), "amount"), None);
+        assert_eq!(attribute_value(&json!({}), "amount"), None);
+    }
+
+    #[test]
+    fn test_plan_steps_resolves_nearest_manager() {
+        let rules = finance_rules();
+        // Requester → 2 (manager) → 3 (finance) → 4 (manager)
+        let chain = vec![
+            user(2, Some(3), "manager"),
+            user(3, Some(4), "finance"),
+            user(4, None, "manager"),
+        ];
+
+        let steps = plan_steps(&rules[1], &chain);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].step_order, 1);
+        assert_eq!(steps[0].approver_role, "finance");
+        assert_eq!(steps[0].approver_user_id, Some(3));
+        assert_eq!(steps[1].step_order, 2);
+        assert_eq!(steps[1].approver_role, "manager");
+        assert_eq!(steps[1].approver_user_id, Some(2));
+        assert_eq!(steps[1].rule_name, "spend-over-50k");
+    }
+
+    #[test]
+    fn test_plan_steps_role_missing_from_chain() {
+        let rules = finance_rules();
+        let steps = plan_steps(&rules[0], &[]);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].approver_role, "manager");
+        assert_eq!(steps[0].approver_user_id, None);
+    }
+}
@@ -0,0 +1,6 @@
+// Approval chain module for multi-level approvals
+// Derives ordered approvers from profile approval_rules + org chart
+
+pub mod chain;
+
+pub use chain::{ApprovalChainEngine, ChainError, PlannedStep};
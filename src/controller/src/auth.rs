@@ -1,7 +1,8 @@
 use axum::{
+    async_trait,
     body::Body,
-    extract::{Request, State},
-    http::{StatusCode, header::AUTHORIZATION},
+    extract::{FromRequestParts, Request, State},
+    http::{StatusCode, header::AUTHORIZATION, request::Parts},
     middleware::Next,
     response::{IntoResponse, Response},
 };
@@ -10,7 +11,13 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{error, warn, debug};
 
-/// JWT Claims structure (minimal; extend as needed)
+/// Identity recorded when a request carries no verified JWT (dev mode)
+pub const ANONYMOUS_PRINCIPAL: &str = "anonymous";
+
+/// Keycloak default realm roles that never identify an org role
+const DEFAULT_REALM_ROLES: [&str; 2] = ["offline_access", "uma_authorization"];
+
+/// JWT Claims structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
@@ -19,6 +26,129 @@ pub struct Claims {
     pub nbf: Option<usize>,
     pub iss: Option<String>,
     pub aud: Option<serde_json::Value>, // Can be string or array
+    /// User email (OIDC standard claim)
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Login name (OIDC standard claim)
+    #[serde(default)]
+    pub preferred_username: Option<String>,
+    /// Org role (custom claim mapped from the user's profile, e.g. "finance")
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Department (custom claim mapped from the user's profile, e.g. "finance")
+    #[serde(default)]
+    pub department: Option<String>,
+    /// Group memberships (Keycloak group mapper, e.g. "/finance")
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Keycloak realm roles
+    #[serde(default)]
+    pub realm_access: Option<RealmAccess>,
+}
+
+/// Keycloak `realm_access` claim
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RealmAccess {
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// Authenticated caller, derived from verified JWT claims
+///
+/// Extract `Principal` in handlers that require authentication (401 when the
+/// request has no verified JWT), or `Option<Principal>` for handlers that also
+/// serve dev mode without OIDC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    /// JWT subject
+    pub subject: String,
+    pub email: Option<String>,
+    pub username: Option<String>,
+    /// Org role the caller acts as (see `Principal::from_claims`)
+    pub role: Option<String>,
+    /// Realm roles, excluding Keycloak defaults
+    pub roles: Vec<String>,
+    /// Group names without the leading '/'
+    pub groups: Vec<String>,
+}
+
+impl Principal {
+    /// Build a principal from verified claims
+    ///
+    /// The org role is the explicit `role` claim if present, otherwise the
+    /// first non-default realm role, otherwise the first group.
+    pub fn from_claims(claims: &Claims) -> Self {
+        let roles: Vec<String> = claims
+            .realm_access
+            .as_ref()
+            .map(|ra| {
+                ra.roles
+                    .iter()
+                    .filter(|r| !DEFAULT_REALM_ROLES.contains(&r.as_str()) && !r.starts_with("default-roles-"))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let groups: Vec<String> = claims
+            .groups
+            .iter()
+            .map(|g| g.trim_start_matches('/').to_string())
+            .filter(|g| !g.is_empty())
+            .collect();
+
+        let role = claims
+            .role
+            .clone()
+            .filter(|r| !r.is_empty())
+            .or_else(|| roles.first().cloned())
+            .or_else(|| groups.first().cloned());
+
+        Self {
+            subject: claims.sub.clone(),
+            email: claims.email.clone(),
+            username: claims.preferred_username.clone(),
+            role,
+            roles,
+            groups,
+        }
+    }
+
+    /// Identity to record in audit fields (email, else username, else subject)
+    pub fn audit_identity(&self) -> &str {
+        self.email
+            .as_deref()
+            .or(self.username.as_deref())
+            .unwrap_or(&self.subject)
+    }
+
+    /// Check whether the caller holds a role (org role, realm role or group)
+    pub fn has_role(&self, role: &str) -> bool {
+        self.role.as_deref() == Some(role)
+            || self.roles.iter().any(|r| r == role)
+            || self.groups.iter().any(|g| g == role)
+    }
+
+    /// Check whether the caller holds any of the given roles
+    pub fn has_any_role(&self, roles: &[&str]) -> bool {
+        roles.iter().any(|role| self.has_role(role))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Principal
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Claims>()
+            .map(Principal::from_claims)
+            .ok_or((StatusCode::UNAUTHORIZED, "Authentication required".to_string()))
+    }
 }
 
 /// JWKS response structure
@@ -197,3 +327,64 @@ pub async fn jwt_middleware(
 
     Ok(next.run(req).await)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims() -> Claims {
+        Claims {
+            sub: "3f1c2a9e".to_string(),
+            exp: 0,
+            iat: None,
+            nbf: None,
+            iss: None,
+            aud: None,
+            email: None,
+            preferred_username: None,
+            role: None,
+            department: None,
+            groups: Vec::new(),
+            realm_access: None,
+        }
+    }
+
+    #[test]
+    fn test_principal_from_keycloak_claims() {
+        let json = serde_json::json!({
+            "sub": "3f1c2a9e",
+            "exp": 0,
+            "email": "alice@example.com",
+            "preferred_username": "alice",
+            "groups": ["/finance"],
+            "realm_access": {"roles": ["default-roles-dev", "offline_access", "finance"]}
+        });
+        let claims: Claims = serde_json::from_value(json).unwrap();
+        let principal = Principal::from_claims(&claims);
+
+        assert_eq!(principal.role.as_deref(), Some("finance"));
+        assert_eq!(principal.roles, vec!["finance".to_string()]);
+        assert_eq!(principal.groups, vec!["finance".to_string()]);
+        assert_eq!(principal.audit_identity(), "alice@example.com");
+        assert!(principal.has_role("finance"));
+        assert!(!principal.has_role("offline_access"));
+    }
+
+    #[test]
+    fn test_explicit_role_claim_wins() {
+        let mut c = claims();
+        c.role = Some("manager".to_string());
+        c.groups = vec!["/finance".to_string()];
+
+        let principal = Principal::from_claims(&c);
+        assert_eq!(principal.role.as_deref(), Some("manager"));
+        assert!(principal.has_role("finance"));
+    }
+
+    #[test]
+    fn test_audit_identity_falls_back_to_subject() {
+        let principal = Principal::from_claims(&claims());
+        assert_eq!(principal.role, None);
+        assert_eq!(principal.audit_identity(), "3f1c2a9e");
+    }
+}
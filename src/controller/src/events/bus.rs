@@ -0,0 +1,176 @@
+// Event Bus
+//
+// Task and approval changes are published by database triggers with
+// pg_notify (see migration 0014), so every write path emits events and
+// delivery happens only after the writing transaction commits. Each
+// controller replica runs one LISTEN connection and fans notifications out
+// to its local subscribers through a broadcast channel.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Postgres NOTIFY channel carrying task and approval events
+pub const TASK_EVENTS_CHANNEL: &str = "goose_task_events";
+
+/// Delay before re-establishing a dropped LISTEN connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Task or approval event delivered to subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TaskEvent {
+    /// Event kind ("task_created", "task_updated", "approval_decided")
+    #[schema(example = "task_created")]
+    pub event: String,
+
+    /// Task the event refers to
+    pub task_id: Uuid,
+
+    /// Task type
+    #[schema(example = "budget_approval")]
+    pub task_type: String,
+
+    /// Role that created the task
+    #[schema(example = "finance")]
+    pub source: String,
+
+    /// Role the task is routed to
+    #[schema(example = "manager")]
+    pub target: String,
+
+    /// Task status after the change (task events only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    /// Decision (approval events only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+
+    /// Role that decided (approval events only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approver_role: Option<String>,
+
+    /// When the change happened
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl TaskEvent {
+    /// Check whether a role should receive this event
+    ///
+    /// Roles see events for tasks routed to them and for tasks they created.
+    pub fn is_visible_to(&self, role: &str) -> bool {
+        self.target == role || self.source == role
+    }
+}
+
+/// Event bus fanning out database notifications to local subscribers
+pub struct EventBus {
+    sender: broadcast::Sender<TaskEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Subscribe to all events received by this replica
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Deliver an event to local subscribers
+    ///
+    /// Returns the number of subscribers that received it.
+    pub fn dispatch(&self, event: TaskEvent) -> usize {
+        // Err only means nobody is subscribed right now
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Start the LISTEN loop in the background
+    ///
+    /// Reconnects after connection failures; events emitted while
+    /// disconnected are lost, so subscribers should resync via GET /tasks.
+    pub fn spawn_listener(self: std::sync::Arc<Self>, pool: PgPool) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = self.listen(&pool).await {
+                    warn!(message = "events.listener.error", error = %e);
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+    }
+
+    async fn listen(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(TASK_EVENTS_CHANNEL).await?;
+
+        info!(message = "events.listener.started", channel = TASK_EVENTS_CHANNEL);
+
+        loop {
+            let notification = listener.recv().await?;
+            match serde_json::from_str::<TaskEvent>(notification.payload()) {
+                Ok(event) => {
+                    self.dispatch(event);
+                }
+                Err(e) => {
+                    warn!(message = "events.payload.invalid", error = %e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> &'static str {
+        r#"{
+            "event": "task_updated",
+            "task_id": "550e8400-e29b-41d4-a716-446655440000",
+            "task_type": "budget_approval",
+            "source": "finance",
+            "target": "manager",
+            "status": "claimed",
+            "occurred_at": "2025-11-11T10:00:00.123456+00:00"
+        }"#
+    }
+
+    #[test]
+    fn test_parse_trigger_payload() {
+        let event: TaskEvent = serde_json::from_str(payload()).unwrap();
+        assert_eq!(event.event, "task_updated");
+        assert_eq!(event.status.as_deref(), Some("claimed"));
+        assert!(event.decision.is_none());
+    }
+
+    #[test]
+    fn test_visibility_by_role() {
+        let event: TaskEvent = serde_json::from_str(payload()).unwrap();
+        assert!(event.is_visible_to("manager"));
+        assert!(event.is_visible_to("finance"));
+        assert!(!event.is_visible_to("legal"));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_subscribers() {
+        let bus = EventBus::new(16);
+        let event: TaskEvent = serde_json::from_str(payload()).unwrap();
+
+        // No subscribers yet
+        assert_eq!(bus.dispatch(event.clone()), 0);
+
+        let mut rx = bus.subscribe();
+        assert_eq!(bus.dispatch(event.clone()), 1);
+        assert_eq!(rx.recv().await.unwrap(), event);
+    }
+}
@@ -0,0 +1,6 @@
+// Real-time task and approval events
+// Postgres LISTEN/NOTIFY fan-out to Server-Sent Events subscribers
+
+pub mod bus;
+
+pub use bus::{EventBus, TaskEvent, TASK_EVENTS_CHANNEL};
@@ -7,6 +7,8 @@ pub mod models;
 pub mod repository;
 pub mod middleware;
 pub mod policy; // Phase 5 Workstream C: RBAC/ABAC policy engine
+pub mod approval; // Multi-level approval chains (profile rules + org chart)
+pub mod events; // Real-time task/approval events (LISTEN/NOTIFY → SSE)
 
 // Phase 4: Lifecycle management (lives outside controller for reusability)
 #[path = "../../lifecycle/mod.rs"]
@@ -47,6 +49,10 @@ pub struct AppState {
     pub vault_client: Option<Arc<vault::VaultClient>>,
     /// Phase 6 A1: Session lifecycle manager for FSM state transitions
     pub session_lifecycle: Option<Arc<lifecycle::SessionLifecycle>>,
+    /// Real-time task/approval event fan-out for SSE subscribers
+    pub event_bus: Option<Arc<events::EventBus>>,
+    /// Latest policies table version, part of the policy cache key
+    pub policy_version: Option<Arc<policy::PolicyVersion>>,
 }
 
 impl AppState {
@@ -58,6 +64,8 @@ impl AppState {
             redis_client: None,
             vault_client: None,
             session_lifecycle: None,
+            event_bus: None,
+            policy_version: None,
         }
     }
 
@@ -82,6 +90,18 @@ impl AppState {
         self.session_lifecycle = Some(Arc::new(lifecycle));
         self
     }
+
+    /// Add event bus to state (enables GET /events/stream)
+    pub fn with_event_bus(mut self, bus: Arc<events::EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Add policy version tracking to state (versioned policy cache keys)
+    pub fn with_policy_version(mut self, version: Arc<policy::PolicyVersion>) -> Self {
+        self.policy_version = Some(version);
+        self
+    }
 }
 
 // Re-export types needed by OpenAPI
@@ -2,7 +2,7 @@ use goose_controller::{AppState, auth, guard_client, api, routes, status, health
 use goose_controller::middleware as goose_middleware;
 
 use axum::{
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
     Json,
     middleware,
@@ -168,6 +168,36 @@ async fn main() {
             message = "session lifecycle initialized",
             retention_days = retention_days
         );
+
+        // Return tasks with expired leases to the pending queue
+        let sweep_secs = std::env::var("TASK_LEASE_SWEEP_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(30);
+        let task_repo = goose_controller::repository::TaskRepository::new(pool.clone());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(sweep_secs));
+            loop {
+                interval.tick().await;
+                match task_repo.release_expired_leases().await {
+                    Ok(0) => {}
+                    Ok(count) => info!(message = "task.lease.expired", count = count),
+                    Err(e) => warn!(message = "task.lease.sweep.error", error = %e),
+                }
+            }
+        });
+        info!(message = "task lease sweeper started", interval_secs = sweep_secs);
+
+        // Real-time events: one LISTEN connection per replica feeds SSE subscribers
+        let event_bus = Arc::new(goose_controller::events::EventBus::new(1024));
+        event_bus.clone().spawn_listener(pool.clone());
+        app_state = app_state.with_event_bus(event_bus);
+        info!(message = "event bus initialized");
+
+        // Policy cache keys follow the policies table version across replicas
+        let policy_version = Arc::new(goose_controller::policy::PolicyVersion::new());
+        policy_version.clone().spawn_listener(pool.clone());
+        app_state = app_state.with_policy_version(policy_version);
     }
     if let Some(redis) = redis_client {
         app_state = app_state.with_redis_client(redis);
@@ -188,6 +218,18 @@ async fn main() {
         info!(message = "idempotency deduplication disabled");
     }
 
+    // Policy enforcement needs verified JWT claims, so it only applies with JWT enabled
+    let policy_enforcement_enabled = std::env::var("POLICY_ENFORCEMENT_ENABLED")
+        .ok()
+        .and_then(|s| s.parse::<bool>().ok())
+        .unwrap_or(true);
+
+    if policy_enforcement_enabled && jwt_config.is_some() {
+        info!(message = "policy enforcement enabled");
+    } else {
+        info!(message = "policy enforcement disabled");
+    }
+
     // Build router with conditional JWT and idempotency middleware
     let app = if let Some(config) = jwt_config {
         // Phase 3: Protected routes require JWT - build as nested router
@@ -195,13 +237,18 @@ async fn main() {
             .route("/audit/ingest", post(audit_ingest))
             .route("/tasks/route", post(routes::tasks::route_task))
             .route("/tasks/:id", get(routes::tasks::get_task))
+            .route("/tasks/:id/events", put(routes::tasks::handle_task_event))
+            .route("/tasks/:id/lease", put(routes::tasks::renew_task_lease))
+            .route("/tasks/claim", post(routes::tasks::claim_tasks))
             .route("/tasks", get(routes::tasks::list_tasks))
+            .route("/events/stream", get(routes::events::stream_events))
             .route("/sessions", get(routes::sessions::list_sessions))
             .route("/sessions", post(routes::sessions::create_session))
             .route("/sessions/:id", get(routes::sessions::get_session))
             .route("/sessions/:id", put(routes::sessions::update_session))
             .route("/sessions/:id/events", put(routes::sessions::handle_session_event))
             .route("/approvals", post(routes::approvals::submit_approval))
+            .route("/approvals/:task_id", get(routes::approvals::get_approval_chain))
             .route("/profiles/:role", get(routes::profiles::get_profile))
             .route("/profiles/:role/config", get(routes::profiles::get_config))
             .route("/profiles/:role/goosehints", get(routes::profiles::get_goosehints))
@@ -209,10 +256,16 @@ async fn main() {
             .route("/profiles/:role/local-hints", get(routes::profiles::get_local_hints))
             .route("/profiles/:role/recipes", get(routes::profiles::get_recipes))
             .route("/privacy/audit", post(routes::privacy::submit_audit_log))
+            .route("/policy/evaluate", post(routes::policy::evaluate_policy))
+            .route("/policy/decisions", get(routes::policy::list_policy_decisions))
             // Phase 5 Workstream D: Admin routes (D7-D9 - Profile Management - protected)
             .route("/admin/profiles", post(routes::admin::profiles::create_profile))
             .route("/admin/profiles/:role", put(routes::admin::profiles::update_profile))
             .route("/admin/profiles/:role/publish", post(routes::admin::profiles::publish_profile))
+            .route("/admin/policies", get(routes::admin::policies::list_policies))
+            .route("/admin/policies", post(routes::admin::policies::create_policy))
+            .route("/admin/policies/:id", put(routes::admin::policies::update_policy))
+            .route("/admin/policies/:id", delete(routes::admin::policies::delete_policy))
             .with_state(app_state.clone());
         
         // Phase 4: Apply idempotency middleware if enabled (before JWT middleware)
@@ -223,6 +276,14 @@ async fn main() {
             ));
         }
         
+        // RBAC/ABAC policy enforcement on tool invocations (runs after JWT middleware)
+        if policy_enforcement_enabled {
+            protected = protected.layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                goose_middleware::enforce_policy
+            ));
+        }
+
         protected = protected.layer(middleware::from_fn_with_state(config, jwt_middleware));
 
         // Public routes (status + health + OpenAPI spec + admin UI - NO JWT)
@@ -257,13 +318,18 @@ async fn main() {
             .route("/audit/ingest", post(audit_ingest))
             .route("/tasks/route", post(routes::tasks::route_task))
             .route("/tasks/:id", get(routes::tasks::get_task))
+            .route("/tasks/:id/events", put(routes::tasks::handle_task_event))
+            .route("/tasks/:id/lease", put(routes::tasks::renew_task_lease))
+            .route("/tasks/claim", post(routes::tasks::claim_tasks))
             .route("/tasks", get(routes::tasks::list_tasks))
+            .route("/events/stream", get(routes::events::stream_events))
             .route("/sessions", get(routes::sessions::list_sessions))
             .route("/sessions", post(routes::sessions::create_session))
             .route("/sessions/:id", get(routes::sessions::get_session))
             .route("/sessions/:id", put(routes::sessions::update_session))
             .route("/sessions/:id/events", put(routes::sessions::handle_session_event))
             .route("/approvals", post(routes::approvals::submit_approval))
+            .route("/approvals/:task_id", get(routes::approvals::get_approval_chain))
             .route("/profiles/:role", get(routes::profiles::get_profile))
             .route("/profiles/:role/config", get(routes::profiles::get_config))
             .route("/profiles/:role/goosehints", get(routes::profiles::get_goosehints))
@@ -271,10 +337,16 @@ async fn main() {
             .route("/profiles/:role/local-hints", get(routes::profiles::get_local_hints))
             .route("/profiles/:role/recipes", get(routes::profiles::get_recipes))
             .route("/privacy/audit", post(routes::privacy::submit_audit_log))
+            .route("/policy/evaluate", post(routes::policy::evaluate_policy))
+            .route("/policy/decisions", get(routes::policy::list_policy_decisions))
             // Phase 5 Workstream D: Admin routes (D7-D12)
             .route("/admin/profiles", post(routes::admin::profiles::create_profile))
             .route("/admin/profiles/:role", put(routes::admin::profiles::update_profile))
             .route("/admin/profiles/:role/publish", post(routes::admin::profiles::publish_profile))
+            .route("/admin/policies", get(routes::admin::policies::list_policies))
+            .route("/admin/policies", post(routes::admin::policies::create_policy))
+            .route("/admin/policies/:id", put(routes::admin::policies::update_policy))
+            .route("/admin/policies/:id", delete(routes::admin::policies::delete_policy))
             .route("/admin/org/import", post(routes::admin::org::import_csv))
             .route("/admin/org/imports", get(routes::admin::org::get_import_history))
             .route("/admin/org/tree", get(routes::admin::org::get_org_tree))
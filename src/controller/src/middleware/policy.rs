@@ -2,24 +2,39 @@
 // Phase 5 Workstream C: Task C4
 //
 // Enforces RBAC/ABAC policies before allowing requests to routes.
-// Extracts role from JWT, tool name from request, evaluates policy.
+// Extracts role and identity attributes from JWT claims, tool name and
+// request attributes from the request, evaluates policy.
 // Returns 403 Forbidden if policy denies access.
 
 use axum::{
     body::Body,
     extract::{Request, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
-use std::sync::Arc;
+use serde_json::{json, Map, Value};
 use tracing::{debug, warn};
 
-use crate::policy::{PolicyContext, PolicyEngine, PolicyError};
+use crate::auth::{Claims, Principal};
+use crate::policy::{PolicyContext, PolicyEngine};
 use crate::AppState;
 
+/// ABAC attributes describing the caller, only ever taken from verified claims
+const IDENTITY_ATTRIBUTES: [&str; 2] = ["department", "groups"];
+
+/// Tool implied by task routing requests that don't name one
+const DEFAULT_TASK_ROUTE_TOOL: &str = "agent_mesh__send_task";
+
+/// Agent-mesh tools that route through POST /tasks/route (and may name
+/// themselves there via X-Tool-Name)
+const TASK_ROUTE_TOOLS: [&str; 3] = [
+    DEFAULT_TASK_ROUTE_TOOL,
+    "agent_mesh__notify",
+    "agent_mesh__request_approval",
+];
+
 /// Policy enforcement middleware
 ///
 /// Extracts role from JWT claims and tool name from request.
@@ -30,59 +45,80 @@ use crate::AppState;
 /// so that JWT claims are already validated and available.
 pub async fn enforce_policy(
     State(state): State<AppState>,
-    mut req: Request,
+    req: Request,
     next: Next,
 ) -> Result<Response, PolicyDeniedResponse> {
-    // 1. Extract role from JWT claims (set by JWT middleware)
-    let role = match req.extensions().get::<String>() {
-        Some(role_claim) => role_claim.clone(),
-        None => {
-            // No role claim found - might be unauthenticated route
-            // Skip policy enforcement for unauthenticated routes
-            debug!("No role claim found, skipping policy enforcement");
+    // 1. Extract tool name from request (only tool invocations are policed)
+    let tool_name = match extract_tool_name(&req) {
+        Ok(Some(tool_name)) => tool_name,
+        Ok(None) => {
+            debug!("No tool name found, skipping policy enforcement");
             return Ok(next.run(req).await);
         }
+        Err(conflict) => {
+            warn!(route_tool = %conflict.route, header_tool = %conflict.header, "X-Tool-Name disagrees with the route, denying");
+            return Err(PolicyDeniedResponse {
+                role: "unknown".to_string(),
+                reason: format!(
+                    "X-Tool-Name '{}' does not match the requested tool '{}'",
+                    conflict.header, conflict.route
+                ),
+                tool: conflict.route,
+            });
+        }
     };
 
-    // 2. Extract tool name from request
-    // For now, we'll extract from the request path or body
-    // This is a placeholder - actual extraction depends on API design
-    let tool_name = extract_tool_name(&req).await;
-    
-    let Some(tool_name) = tool_name else {
-        // No tool name in request - skip policy enforcement
-        debug!(role = %role, "No tool name found, skipping policy enforcement");
+    // 2. Extract role from JWT claims (set by JWT middleware)
+    let Some(claims) = req.extensions().get::<Claims>() else {
+        // Unauthenticated route (JWT middleware not applied)
+        debug!(tool = %tool_name, "No JWT claims found, skipping policy enforcement");
         return Ok(next.run(req).await);
     };
 
-    // 3. Build policy context (for ABAC conditions)
-    let context = extract_policy_context(&req).await;
+    let Some(role) = Principal::from_claims(claims).role else {
+        // Authenticated but no org role: fail closed for tool invocations
+        warn!(tool = %tool_name, subject = %claims.sub, "No role in JWT claims, denying tool invocation");
+        return Err(PolicyDeniedResponse {
+            role: "unknown".to_string(),
+            tool: tool_name,
+            reason: "No role claim in token".to_string(),
+        });
+    };
+
+    let claims = claims.clone();
+
+    // 3. Build policy context (for ABAC conditions); the body is buffered so
+    //    the route still receives it
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.map_err(|e| {
+        warn!(role = %role, tool = %tool_name, error = %e, "Failed to read request body, denying");
+        PolicyDeniedResponse {
+            role: role.clone(),
+            tool: tool_name.clone(),
+            reason: "Request body could not be read".to_string(),
+        }
+    })?;
+    let context = extract_policy_context(&parts.headers, parts.uri.path(), &claims, &body);
+    let req = Request::from_parts(parts, Body::from(body));
+
+    // 4. Check policy using PolicyEngine (the decision is audited)
+    let policy_engine = create_policy_engine(&state)?.with_subject(Some(claims.sub.clone()));
 
-    // 4. Check policy using PolicyEngine
-    let policy_engine = create_policy_engine(&state)?;
-    
-    match policy_engine.can_use_tool(&role, &tool_name, &context).await {
-        Ok(true) => {
+    match policy_engine.evaluate(&role, &tool_name, &context, false).await {
+        Ok(decision) if decision.allowed => {
             // Policy allows - proceed to route
-            debug!(role = %role, tool = %tool_name, "Policy allows access");
+            debug!(role = %role, tool = %tool_name, policy_id = ?decision.matched_policy_id, "Policy allows access");
             Ok(next.run(req).await)
         }
-        Ok(false) => {
-            // Policy explicitly denies
-            warn!(role = %role, tool = %tool_name, "Policy denies access");
+        Ok(decision) => {
+            // Policy explicitly denies, or no policy found (default deny)
+            warn!(role = %role, tool = %tool_name, policy_id = ?decision.matched_policy_id, "Policy denies access");
             Err(PolicyDeniedResponse {
                 role: role.clone(),
                 tool: tool_name.clone(),
-                reason: format!("Policy denies {} access to {}", role, tool_name),
-            })
-        }
-        Err(PolicyError::Denied(reason)) => {
-            // No policy found - default deny
-            warn!(role = %role, tool = %tool_name, "No policy found, default deny");
-            Err(PolicyDeniedResponse {
-                role: role.clone(),
-                tool: tool_name.clone(),
-                reason,
+                reason: decision
+                    .reason
+                    .unwrap_or_else(|| format!("Policy denies {} access to {}", role, tool_name)),
             })
         }
         Err(e) => {
@@ -97,61 +133,119 @@ pub async fn enforce_policy(
     }
 }
 
+/// X-Tool-Name header naming a different tool than the route
+#[derive(Debug, PartialEq)]
+struct ToolNameConflict {
+    route: String,
+    header: String,
+}
+
 /// Extract tool name from request
 ///
-/// Checks request path and body for tool references.
-/// Returns None if no tool name found.
-async fn extract_tool_name(req: &Request) -> Option<String> {
+/// The route decides: `/tools/{name}` names its tool, and `/tasks/route`
+/// is one of the agent-mesh task routing tools (`agent_mesh__send_task`
+/// unless X-Tool-Name names another of them). Elsewhere the client-controlled
+/// X-Tool-Name header names the tool. A header that disagrees with the route
+/// is an error, so it can't swap in a more permissive tool. Returns None if
+/// no tool name found.
+fn extract_tool_name(req: &Request) -> Result<Option<String>, ToolNameConflict> {
+    let header = req
+        .headers()
+        .get("X-Tool-Name")
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string);
+
     // Strategy 1: Check path for tool routes
     // Example: POST /tools/developer__shell → "developer__shell"
     let path = req.uri().path();
     if let Some(tool_segment) = path.strip_prefix("/tools/") {
-        return Some(tool_segment.to_string());
+        return match header {
+            Some(header) if header != tool_segment => Err(ToolNameConflict {
+                route: tool_segment.to_string(),
+                header,
+            }),
+            _ => Ok(Some(tool_segment.to_string())),
+        };
     }
 
-    // Strategy 2: Check for task routing requests
-    // Example: POST /tasks/route with body containing tool name
+    // Strategy 2: Task routing, shared by the agent-mesh routing tools
+    // Example: POST /tasks/route → "agent_mesh__send_task"
     if path == "/tasks/route" {
-        // TODO: Parse request body for tool name
-        // For now, return None (body parsing requires more complex logic)
-        // This will be implemented when task routing is fully designed
-        return None;
+        return match header {
+            None => Ok(Some(DEFAULT_TASK_ROUTE_TOOL.to_string())),
+            Some(header) if TASK_ROUTE_TOOLS.contains(&header.as_str()) => Ok(Some(header)),
+            Some(header) => Err(ToolNameConflict {
+                route: DEFAULT_TASK_ROUTE_TOOL.to_string(),
+                header,
+            }),
+        };
     }
 
-    // Strategy 3: Check custom header
+    // Strategy 3: Custom header (sent by agent-mesh tools) on other routes
     // Example: X-Tool-Name: developer__shell
-    if let Some(tool_header) = req.headers().get("X-Tool-Name") {
-        if let Ok(tool_str) = tool_header.to_str() {
-            return Some(tool_str.to_string());
-        }
-    }
-
-    None
+    Ok(header)
 }
 
 /// Extract policy context from request
 ///
-/// Builds PolicyContext with ABAC attributes extracted from request.
-async fn extract_policy_context(req: &Request) -> PolicyContext {
+/// Builds PolicyContext with ABAC attributes. Identity attributes
+/// (`department`, `groups`) come from the verified JWT claims only; request
+/// attributes (`amount`, ...) come from the scalar fields of the JSON body,
+/// and for task routing also from `task.data`. A body field can never set an
+/// identity attribute, whether or not the token carries it.
+fn extract_policy_context(headers: &HeaderMap, path: &str, claims: &Claims, body: &[u8]) -> PolicyContext {
     let mut context = PolicyContext::empty();
 
     // Extract database name from query params or headers
-    if let Some(db_header) = req.headers().get("X-Database-Name") {
+    if let Some(db_header) = headers.get("X-Database-Name") {
         if let Ok(db_str) = db_header.to_str() {
             context.database = Some(db_str.to_string());
         }
     }
 
     // Extract file path from query params or headers
-    if let Some(path_header) = req.headers().get("X-File-Path") {
+    if let Some(path_header) = headers.get("X-File-Path") {
         if let Ok(path_str) = path_header.to_str() {
             context.file_path = Some(path_str.to_string());
         }
     }
 
+    if let Ok(Value::Object(fields)) = serde_json::from_slice::<Value>(body) {
+        insert_request_attributes(&mut context, &fields);
+        if path == "/tasks/route" {
+            if let Some(Value::Object(data)) = fields.get("task").and_then(|task| task.get("data")) {
+                insert_request_attributes(&mut context, data);
+            }
+        }
+    }
+
+    if let Some(department) = claims.department.as_deref().filter(|d| !d.is_empty()) {
+        context.extra.insert("department".to_string(), department.to_string());
+    }
+    let groups = Principal::from_claims(claims).groups;
+    if !groups.is_empty() {
+        context.extra.insert("groups".to_string(), groups.join(","));
+    }
+
     context
 }
 
+/// Copy the scalar fields of a request body object into the context
+fn insert_request_attributes(context: &mut PolicyContext, fields: &Map<String, Value>) {
+    for (name, value) in fields {
+        if IDENTITY_ATTRIBUTES.contains(&name.as_str()) {
+            continue;
+        }
+        let value = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => continue,
+        };
+        context.extra.insert(name.clone(), value);
+    }
+}
+
 /// Create PolicyEngine from AppState
 fn create_policy_engine(state: &AppState) -> Result<PolicyEngine, PolicyDeniedResponse> {
     let db_pool = state.db_pool.clone().ok_or_else(|| PolicyDeniedResponse {
@@ -160,12 +254,14 @@ fn create_policy_engine(state: &AppState) -> Result<PolicyEngine, PolicyDeniedRe
         reason: "Database not configured".to_string(),
     })?;
 
-    Ok(PolicyEngine::new(db_pool, state.redis_client.clone()))
+    let version = state.policy_version.as_ref().map_or(0, |v| v.get());
+
+    Ok(PolicyEngine::new(db_pool, state.redis_client.clone()).with_version(version))
 }
 
 /// Policy denied response
 #[derive(Debug)]
-struct PolicyDeniedResponse {
+pub struct PolicyDeniedResponse {
     role: String,
     tool: String,
     reason: String,
@@ -188,53 +284,166 @@ impl IntoResponse for PolicyDeniedResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::body::Body;
     use axum::http::Request;
 
-    #[tokio::test]
-    async fn test_extract_tool_name_from_path() {
+    #[test]
+    fn test_extract_tool_name_from_path() {
         let req = Request::builder()
             .uri("/tools/developer__shell")
             .body(Body::empty())
             .unwrap();
 
-        let tool = extract_tool_name(&req).await;
-        assert_eq!(tool, Some("developer__shell".to_string()));
+        let tool = extract_tool_name(&req);
+        assert_eq!(tool, Ok(Some("developer__shell".to_string())));
     }
 
-    #[tokio::test]
-    async fn test_extract_tool_name_from_header() {
+    #[test]
+    fn test_extract_tool_name_path_wins_over_header() {
+        // A matching header is fine
+        let req = Request::builder()
+            .uri("/tools/developer__shell")
+            .header("X-Tool-Name", "developer__shell")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(extract_tool_name(&req), Ok(Some("developer__shell".to_string())));
+
+        // A header naming another tool cannot override the route
+        let req = Request::builder()
+            .uri("/tools/developer__shell")
+            .header("X-Tool-Name", "github__list_issues")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            extract_tool_name(&req),
+            Err(ToolNameConflict {
+                route: "developer__shell".to_string(),
+                header: "github__list_issues".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_extract_tool_name_from_header() {
         let req = Request::builder()
             .uri("/some/path")
             .header("X-Tool-Name", "github__list_issues")
             .body(Body::empty())
             .unwrap();
 
-        let tool = extract_tool_name(&req).await;
-        assert_eq!(tool, Some("github__list_issues".to_string()));
+        let tool = extract_tool_name(&req);
+        assert_eq!(tool, Ok(Some("github__list_issues".to_string())));
     }
 
-    #[tokio::test]
-    async fn test_extract_tool_name_not_found() {
+    #[test]
+    fn test_extract_tool_name_task_route() {
         let req = Request::builder()
-            .uri("/status")
+            .method("POST")
+            .uri("/tasks/route")
             .body(Body::empty())
             .unwrap();
 
-        let tool = extract_tool_name(&req).await;
-        assert_eq!(tool, None);
+        let tool = extract_tool_name(&req);
+        assert_eq!(tool, Ok(Some("agent_mesh__send_task".to_string())));
+
+        // Other agent-mesh routing tools may name themselves
+        let req = Request::builder()
+            .method("POST")
+            .uri("/tasks/route")
+            .header("X-Tool-Name", "agent_mesh__notify")
+            .body(Body::empty())
+            .unwrap();
+
+        let tool = extract_tool_name(&req);
+        assert_eq!(tool, Ok(Some("agent_mesh__notify".to_string())));
+
+        // But the header cannot swap in an unrelated tool
+        let req = Request::builder()
+            .method("POST")
+            .uri("/tasks/route")
+            .header("X-Tool-Name", "github__list_issues")
+            .body(Body::empty())
+            .unwrap();
+
+        let tool = extract_tool_name(&req);
+        assert!(tool.is_err());
     }
 
-    #[tokio::test]
-    async fn test_extract_policy_context() {
+    #[test]
+    fn test_extract_tool_name_not_found() {
         let req = Request::builder()
-            .uri("/some/path")
-            .header("X-Database-Name", "analytics_prod")
-            .header("X-File-Path", "/data/reports/q4.csv")
+            .uri("/status")
             .body(Body::empty())
             .unwrap();
 
-        let context = extract_policy_context(&req).await;
+        let tool = extract_tool_name(&req);
+        assert_eq!(tool, Ok(None));
+    }
+
+    fn claims(department: Option<&str>, groups: &[&str]) -> Claims {
+        serde_json::from_value(json!({
+            "sub": "user-1",
+            "exp": 0,
+            "role": "finance",
+            "department": department,
+            "groups": groups,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_extract_policy_context() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Database-Name", "analytics_prod".parse().unwrap());
+        headers.insert("X-File-Path", "/data/reports/q4.csv".parse().unwrap());
+        let body = json!({
+            "target": "manager",
+            "task": {"task_type": "budget_approval", "data": {"amount": 2500, "cost_center": "ops"}}
+        });
+
+        let context = extract_policy_context(
+            &headers,
+            "/tasks/route",
+            &claims(Some("finance"), &["/finance", "/emea"]),
+            body.to_string().as_bytes(),
+        );
         assert_eq!(context.database, Some("analytics_prod".to_string()));
         assert_eq!(context.file_path, Some("/data/reports/q4.csv".to_string()));
+        assert_eq!(context.extra.get("target").map(String::as_str), Some("manager"));
+        assert_eq!(context.extra.get("amount").map(String::as_str), Some("2500"));
+        assert_eq!(context.extra.get("cost_center").map(String::as_str), Some("ops"));
+        assert_eq!(context.extra.get("department").map(String::as_str), Some("finance"));
+        assert_eq!(context.extra.get("groups").map(String::as_str), Some("finance,emea"));
+        assert_eq!(context.extra.len(), 5);
+    }
+
+    #[test]
+    fn test_extract_policy_context_ignores_forged_attributes() {
+        // Clients used to set attributes through X-Policy-Context-* headers
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Policy-Context-Amount", "1".parse().unwrap());
+        headers.insert("X-Policy-Context-Department", "finance".parse().unwrap());
+        let body = json!({"amount": 50000, "department": "finance", "groups": "finance"});
+
+        // Identity attributes in the body never override the token...
+        let context = extract_policy_context(
+            &headers,
+            "/tools/sql-mcp__query",
+            &claims(Some("legal"), &[]),
+            body.to_string().as_bytes(),
+        );
+        assert_eq!(context.extra.get("amount").map(String::as_str), Some("50000"));
+        assert_eq!(context.extra.get("department").map(String::as_str), Some("legal"));
+        assert_eq!(context.extra.get("groups"), None);
+
+        // ...and aren't filled in when the token doesn't carry them
+        let context = extract_policy_context(
+            &headers,
+            "/tools/sql-mcp__query",
+            &claims(None, &[]),
+            body.to_string().as_bytes(),
+        );
+        assert_eq!(context.extra.get("department"), None);
+        assert_eq!(context.extra.len(), 1);
     }
 }
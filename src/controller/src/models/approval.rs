@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+/// Approval model - a decision submitted against a task
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Approval {
+    /// Unique approval identifier
+    pub id: Uuid,
+
+    /// Task the decision applies to
+    pub task_id: Uuid,
+
+    /// Subject of the caller who decided
+    pub approver: String,
+
+    /// Role the caller decided as
+    pub approver_role: String,
+
+    /// Decision ("approved" or "rejected")
+    pub decision: String,
+
+    /// Optional comments
+    pub comments: Option<String>,
+
+    /// Distributed tracing identifier
+    pub trace_id: Option<Uuid>,
+
+    /// Decision timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to record a new approval decision
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateApprovalRequest {
+    pub task_id: Uuid,
+    pub approver: String,
+    pub decision: String,
+    pub comments: Option<String>,
+    pub trace_id: Option<Uuid>,
+}
+
+/// Approval step - one approver in a task's multi-level approval chain
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ApprovalStep {
+    /// Unique step identifier
+    pub id: Uuid,
+
+    /// Task the step belongs to
+    pub task_id: Uuid,
+
+    /// Position in the chain (1 = first approver)
+    pub step_order: i32,
+
+    /// Role that must decide this step
+    pub approver_role: String,
+
+    /// Org chart user resolved from the requester's management chain
+    pub approver_user_id: Option<i32>,
+
+    /// Profile approval rule that produced this step
+    pub rule_name: String,
+
+    /// Step status ("pending", "approved", "rejected", "skipped")
+    pub status: String,
+
+    /// Approval decision that closed this step
+    pub approval_id: Option<Uuid>,
+
+    /// Step creation timestamp
+    pub created_at: DateTime<Utc>,
+
+    /// When the step was decided
+    pub decided_at: Option<DateTime<Utc>>,
+}
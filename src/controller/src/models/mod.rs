@@ -1,10 +1,15 @@
 pub mod session;
 pub mod task;
+pub mod approval;
 
 pub use session::{
     CreateSessionRequest, Session, SessionListResponse, SessionStatus, UpdateSessionRequest,
 };
 
 pub use task::{
-    Task, CreateTaskRequest, CreateTaskResponse,
+    Task, TaskStatus, ClaimedTask, CreateTaskRequest, CreateTaskResponse,
+};
+
+pub use approval::{
+    Approval, ApprovalStep, CreateApprovalRequest,
 };
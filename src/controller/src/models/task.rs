@@ -4,6 +4,78 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use utoipa::ToSchema;
 
+/// Task status enum
+///
+/// `Task.status` is stored as a plain string; this enum is what the task
+/// lifecycle FSM reasons about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    Claimed,
+    InProgress,
+    /// Legacy status, treated like in_progress
+    Active,
+    Completed,
+    Failed,
+    Cancelled,
+    Approved,
+    Rejected,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::Claimed => "claimed",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Active => "active",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Cancelled => "cancelled",
+            TaskStatus::Approved => "approved",
+            TaskStatus::Rejected => "rejected",
+        }
+    }
+
+    /// Terminal statuses can no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Completed
+                | TaskStatus::Failed
+                | TaskStatus::Cancelled
+                | TaskStatus::Approved
+                | TaskStatus::Rejected
+        )
+    }
+}
+
+impl std::str::FromStr for TaskStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TaskStatus::Pending),
+            "claimed" => Ok(TaskStatus::Claimed),
+            "in_progress" => Ok(TaskStatus::InProgress),
+            "active" => Ok(TaskStatus::Active),
+            "completed" => Ok(TaskStatus::Completed),
+            "failed" => Ok(TaskStatus::Failed),
+            "cancelled" => Ok(TaskStatus::Cancelled),
+            "approved" => Ok(TaskStatus::Approved),
+            "rejected" => Ok(TaskStatus::Rejected),
+            other => Err(format!("Unknown task status: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Task model - represents a task routed between agents
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Task {
@@ -47,6 +119,24 @@ pub struct Task {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// Task leased to a worker via atomic claiming
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ClaimedTask {
+    /// The claimed task
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub task: Task,
+
+    /// Lease token (required to renew the lease)
+    pub lease_id: Uuid,
+
+    /// Worker holding the lease
+    pub claimed_by: String,
+
+    /// When the lease expires unless renewed
+    pub lease_expires_at: DateTime<Utc>,
+}
+
 /// Request to create a new task
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateTaskRequest {
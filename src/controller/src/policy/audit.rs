@@ -0,0 +1,164 @@
+// Policy Decision Audit Log
+//
+// Every tool-use decision made by the PolicyEngine is persisted to the
+// policy_decisions table (migration 0015) so compliance reviewers can answer
+// "why was role X denied tool Y at time T". Writes are best-effort and happen
+// off the request path: a failed audit insert is logged, never surfaced to
+// the caller.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::engine::PolicyContext;
+
+/// Decision to be written to the audit log
+#[derive(Debug, Clone)]
+pub struct NewPolicyDecision {
+    pub role: String,
+    pub tool: String,
+    pub context: PolicyContext,
+    pub matched_policy_id: Option<i32>,
+    pub allowed: bool,
+    pub cache_hit: bool,
+    pub policy_version: i64,
+    pub reason: Option<String>,
+    pub subject: Option<String>,
+}
+
+/// Persisted policy decision
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct PolicyDecisionRecord {
+    /// Audit row identifier
+    pub id: i64,
+
+    /// Role the policy was evaluated for
+    #[schema(example = "finance")]
+    pub role: String,
+
+    /// Tool evaluated
+    #[schema(example = "developer__shell")]
+    pub tool: String,
+
+    /// ABAC context the decision was made with
+    #[schema(value_type = Object)]
+    pub context: serde_json::Value,
+
+    /// Policy that decided the request (null = default deny)
+    pub matched_policy_id: Option<i32>,
+
+    /// Whether access was allowed
+    pub allowed: bool,
+
+    /// Whether the decision was served from the policy cache
+    pub cache_hit: bool,
+
+    /// Policy version the decision was made under
+    pub policy_version: Option<i64>,
+
+    /// Denial reason
+    pub reason: Option<String>,
+
+    /// JWT subject of the caller
+    pub subject: Option<String>,
+
+    /// When the decision was made
+    pub decided_at: DateTime<Utc>,
+}
+
+/// Filters for querying the audit log
+#[derive(Debug, Clone, Default)]
+pub struct PolicyDecisionFilter {
+    pub role: Option<String>,
+    pub tool: Option<String>,
+    pub allowed: Option<bool>,
+    pub subject: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: i64,
+}
+
+/// Policy decision audit log
+pub struct PolicyAuditLog {
+    pool: PgPool,
+}
+
+impl PolicyAuditLog {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert a decision
+    pub async fn record(&self, decision: &NewPolicyDecision) -> Result<(), sqlx::Error> {
+        let context = serde_json::to_value(&decision.context).unwrap_or_default();
+
+        sqlx::query(
+            r#"
+            INSERT INTO policy_decisions
+                (role, tool, context, matched_policy_id, allowed, cache_hit, policy_version,
+                 reason, subject)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&decision.role)
+        .bind(&decision.tool)
+        .bind(context)
+        .bind(decision.matched_policy_id)
+        .bind(decision.allowed)
+        .bind(decision.cache_hit)
+        .bind(decision.policy_version)
+        .bind(&decision.reason)
+        .bind(&decision.subject)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Insert a decision without blocking the caller
+    pub fn record_in_background(self, decision: NewPolicyDecision) {
+        tokio::spawn(async move {
+            if let Err(e) = self.record(&decision).await {
+                warn!(
+                    message = "policy.audit.write_failed",
+                    role = %decision.role,
+                    tool = %decision.tool,
+                    error = %e
+                );
+            }
+        });
+    }
+
+    /// Query decisions, newest first
+    pub async fn list(
+        &self,
+        filter: &PolicyDecisionFilter,
+    ) -> Result<Vec<PolicyDecisionRecord>, sqlx::Error> {
+        sqlx::query_as::<_, PolicyDecisionRecord>(
+            r#"
+            SELECT id, role, tool, context, matched_policy_id, allowed, cache_hit,
+                   policy_version, reason, subject, decided_at
+            FROM policy_decisions
+            WHERE ($1::text IS NULL OR role = $1)
+              AND ($2::text IS NULL OR tool = $2)
+              AND ($3::boolean IS NULL OR allowed = $3)
+              AND ($4::text IS NULL OR subject = $4)
+              AND ($5::timestamptz IS NULL OR decided_at >= $5)
+              AND ($6::timestamptz IS NULL OR decided_at < $6)
+            ORDER BY decided_at DESC, id DESC
+            LIMIT $7
+            "#,
+        )
+        .bind(&filter.role)
+        .bind(&filter.tool)
+        .bind(filter.allowed)
+        .bind(&filter.subject)
+        .bind(filter.since)
+        .bind(filter.until)
+        .bind(filter.limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
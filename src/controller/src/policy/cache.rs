@@ -0,0 +1,239 @@
+// Policy Decision Cache
+//
+// Decisions are cached in Redis under
+//   policy:v{version}:{role}:{tool}:{context}
+// where `version` is the global policy version (migration 0017) and
+// `context` is the canonical JSON of the PolicyContext, so a decision made
+// for one database or file path is never served for another. Any change to
+// the policies table bumps the version and notifies every replica, which
+// orphans all earlier keys; admin edits additionally purge the role's keys
+// right away. Orphaned keys expire with the cache TTL.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use redis::AsyncCommands;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use super::engine::PolicyContext;
+
+/// Postgres NOTIFY channel carrying policy version bumps
+pub const POLICY_CHANGES_CHANNEL: &str = "goose_policy_changes";
+
+/// Prefix of every policy cache key
+const CACHE_PREFIX: &str = "policy:";
+
+/// Delay before re-establishing a dropped LISTEN connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Keys deleted per DEL when purging
+const PURGE_BATCH: usize = 500;
+
+/// Build the cache key for a decision
+pub fn cache_key(version: i64, role: &str, tool_name: &str, context: &PolicyContext) -> String {
+    format!(
+        "{}v{}:{}:{}:{}",
+        CACHE_PREFIX,
+        version,
+        role,
+        tool_name,
+        canonical_context(context)
+    )
+}
+
+/// Canonical JSON of a context (stable key order, empty fields omitted)
+fn canonical_context(context: &PolicyContext) -> String {
+    #[derive(serde::Serialize)]
+    struct Canonical<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        database: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_path: Option<&'a str>,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        extra: BTreeMap<&'a str, &'a str>,
+    }
+
+    serde_json::to_string(&Canonical {
+        database: context.database.as_deref(),
+        file_path: context.file_path.as_deref(),
+        extra: context
+            .extra
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect(),
+    })
+    .unwrap_or_default()
+}
+
+/// Delete cached decisions for a role, or for every role
+///
+/// Returns the number of keys removed. Also removes keys in the old
+/// `policy:{role}:{tool}` format.
+pub async fn purge(
+    redis: &redis::aio::ConnectionManager,
+    role: Option<&str>,
+) -> Result<usize, redis::RedisError> {
+    let patterns = match role {
+        Some(role) => vec![
+            format!("{}v*:{}:*", CACHE_PREFIX, role),
+            format!("{}{}:*", CACHE_PREFIX, role),
+        ],
+        None => vec![format!("{}*", CACHE_PREFIX)],
+    };
+
+    let mut keys: Vec<String> = Vec::new();
+    for pattern in patterns {
+        let mut conn = redis.clone();
+        let mut iter = conn.scan_match::<_, String>(pattern).await?;
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+    }
+
+    let mut conn = redis.clone();
+    for batch in keys.chunks(PURGE_BATCH) {
+        conn.del::<_, ()>(batch).await?;
+    }
+
+    Ok(keys.len())
+}
+
+/// Purge cached decisions after an admin edit, logging instead of failing
+pub async fn purge_after_edit(redis: Option<&redis::aio::ConnectionManager>, role: Option<&str>) {
+    let Some(redis) = redis else {
+        return;
+    };
+    match purge(redis, role).await {
+        Ok(count) => info!(message = "policy.cache.purged", role = role.unwrap_or("*"), keys = count),
+        Err(e) => warn!(message = "policy.cache.purge_failed", role = role.unwrap_or("*"), error = %e),
+    }
+}
+
+/// Latest policy version seen by this replica
+#[derive(Debug, Default)]
+pub struct PolicyVersion {
+    current: AtomicI64,
+}
+
+impl PolicyVersion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current version (0 until loaded)
+    pub fn get(&self) -> i64 {
+        self.current.load(Ordering::Acquire)
+    }
+
+    /// Record a version; older versions are ignored
+    pub fn observe(&self, version: i64) {
+        self.current.fetch_max(version, Ordering::AcqRel);
+    }
+
+    /// Read the version from the database
+    pub async fn load(&self, pool: &PgPool) -> Result<i64, sqlx::Error> {
+        let (version,): (i64,) = sqlx::query_as("SELECT version FROM policy_version WHERE id")
+            .fetch_one(pool)
+            .await?;
+        self.observe(version);
+        Ok(version)
+    }
+
+    /// Follow version bumps in the background
+    ///
+    /// Reloads the version after every (re)connect so bumps missed while
+    /// disconnected are picked up.
+    pub fn spawn_listener(self: Arc<Self>, pool: PgPool) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = self.listen(&pool).await {
+                    warn!(message = "policy.version.listener.error", error = %e);
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+    }
+
+    async fn listen(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(POLICY_CHANGES_CHANNEL).await?;
+
+        let version = self.load(pool).await?;
+        info!(message = "policy.version.listener.started", version = version);
+
+        loop {
+            let notification = listener.recv().await?;
+            match notification.payload().parse::<i64>() {
+                Ok(version) => {
+                    self.observe(version);
+                    info!(message = "policy.version.changed", version = version);
+                }
+                Err(e) => {
+                    warn!(message = "policy.version.payload.invalid", error = %e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_includes_context() {
+        let analytics = PolicyContext::with_database("analytics_prod");
+        let finance = PolicyContext::with_database("finance_db");
+
+        let a = cache_key(3, "analyst", "sql-mcp__query", &analytics);
+        let b = cache_key(3, "analyst", "sql-mcp__query", &finance);
+        assert_ne!(a, b);
+        assert_eq!(a, r#"policy:v3:analyst:sql-mcp__query:{"database":"analytics_prod"}"#);
+    }
+
+    #[test]
+    fn test_cache_key_is_canonical() {
+        let mut first = PolicyContext::empty();
+        first.extra.insert("region".to_string(), "eu".to_string());
+        first.extra.insert("amount".to_string(), "10".to_string());
+
+        let mut second = PolicyContext::empty();
+        second.extra.insert("amount".to_string(), "10".to_string());
+        second.extra.insert("region".to_string(), "eu".to_string());
+
+        assert_eq!(
+            cache_key(1, "finance", "excel-mcp__read", &first),
+            cache_key(1, "finance", "excel-mcp__read", &second)
+        );
+
+        // An extra attribute named like a built-in one is a different context
+        let mut shadow = PolicyContext::empty();
+        shadow.extra.insert("database".to_string(), "analytics_prod".to_string());
+        assert_ne!(
+            cache_key(1, "analyst", "sql-mcp__query", &shadow),
+            cache_key(1, "analyst", "sql-mcp__query", &PolicyContext::with_database("analytics_prod"))
+        );
+    }
+
+    #[test]
+    fn test_cache_key_changes_with_version() {
+        let ctx = PolicyContext::empty();
+        assert_ne!(
+            cache_key(1, "finance", "developer__shell", &ctx),
+            cache_key(2, "finance", "developer__shell", &ctx)
+        );
+    }
+
+    #[test]
+    fn test_version_only_moves_forward() {
+        let version = PolicyVersion::new();
+        assert_eq!(version.get(), 0);
+        version.observe(5);
+        version.observe(3);
+        assert_eq!(version.get(), 5);
+    }
+}
@@ -0,0 +1,639 @@
+// ABAC Condition Language
+// Phase 5 Workstream C
+//
+// Conditions are stored as JSONB on the policies table. An object holds one
+// or more keys that must all be satisfied:
+//
+//   {"database": "analytics_*"}                    glob on an attribute
+//   {"file_path": "/finance/**"}                   `*` stops at '/', `**` does not
+//   {"department": ["finance", "ops"]}             any of the listed globs
+//   {"amount": {"lte": 10000}}                     numeric comparison
+//   {"region": {"ne": "eu", "exists": true}}       several operators, all must hold
+//   {"time_of_day": {"after": "09:00", "before": "17:00"}}
+//   {"day_of_week": ["mon", "tue", "wed", "thu", "fri"]}
+//   {"any_of": [{...}, {...}]}  {"all_of": [...]}  {"not": {...}}
+//
+// Attributes resolve against PolicyContext: `database`, `file_path`, then
+// `extra`. A missing attribute fails every test except `exists: false`, and
+// a `not` over a missing attribute fails too (it never fails open).
+// Time windows are evaluated in UTC unless `utc_offset` ("+02:00") is given.
+// Conditions are parsed (and so validated) before they are stored; see
+// `Condition::parse`.
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveTime, Timelike, Utc, Weekday};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::engine::PolicyContext;
+
+/// A condition that failed validation
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("Invalid condition at '{path}': {message}")]
+pub struct ConditionError {
+    /// JSON path of the offending element (e.g. "any_of[1].amount")
+    pub path: String,
+    pub message: String,
+}
+
+impl ConditionError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: if path.is_empty() { "$".to_string() } else { path.to_string() },
+            message: message.into(),
+        }
+    }
+}
+
+/// Parsed ABAC condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Every condition must hold
+    All(Vec<Condition>),
+    /// At least one condition must hold
+    Any(Vec<Condition>),
+    /// The condition must not hold
+    Not(Box<Condition>),
+    /// Tests on a context attribute
+    Attribute { name: String, tests: Vec<ValueTest> },
+    /// Time window; wraps midnight when `after` > `before`
+    TimeOfDay {
+        after: NaiveTime,
+        before: NaiveTime,
+        offset: FixedOffset,
+    },
+    /// Allowed days of the week
+    DayOfWeek { days: Vec<Weekday>, offset: FixedOffset },
+}
+
+/// Single test applied to an attribute value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueTest {
+    Exists(bool),
+    Eq(String),
+    Ne(String),
+    Glob(String),
+    In(Vec<String>),
+    NotIn(Vec<String>),
+    Lt(f64),
+    Lte(f64),
+    Gt(f64),
+    Gte(f64),
+}
+
+impl Condition {
+    /// Parse and validate a stored condition document
+    pub fn parse(value: &Value) -> Result<Self, ConditionError> {
+        parse_object(value, "")
+    }
+
+    /// Whether evaluation depends on the current time
+    pub fn is_time_dependent(&self) -> bool {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().any(Condition::is_time_dependent)
+            }
+            Condition::Not(inner) => inner.is_time_dependent(),
+            Condition::Attribute { .. } => false,
+            Condition::TimeOfDay { .. } | Condition::DayOfWeek { .. } => true,
+        }
+    }
+
+    /// Check the condition against a request context at `now`
+    ///
+    /// Returns a description of the first unmet part on failure.
+    pub fn evaluate(&self, context: &PolicyContext, now: DateTime<Utc>) -> Result<(), String> {
+        match self {
+            Condition::All(conditions) => conditions
+                .iter()
+                .try_for_each(|c| c.evaluate(context, now)),
+            Condition::Any(conditions) => {
+                let mut failures = Vec::new();
+                for condition in conditions {
+                    match condition.evaluate(context, now) {
+                        Ok(()) => return Ok(()),
+                        Err(e) => failures.push(e),
+                    }
+                }
+                Err(format!("none of any_of matched ({})", failures.join("; ")))
+            }
+            Condition::Not(inner) => {
+                if let Some(name) = inner.missing_attribute(context) {
+                    return Err(format!("context has no '{}' attribute", name));
+                }
+                match inner.evaluate(context, now) {
+                    Ok(()) => Err("negated condition matched".to_string()),
+                    Err(_) => Ok(()),
+                }
+            }
+            Condition::Attribute { name, tests } => {
+                let value = attribute(context, name);
+                tests.iter().try_for_each(|test| test.check(name, value))
+            }
+            Condition::TimeOfDay { after, before, offset } => {
+                let time = now.with_timezone(offset).time();
+                let inside = if after <= before {
+                    time >= *after && time < *before
+                } else {
+                    time >= *after || time < *before
+                };
+                if inside {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "time {} is outside {}-{} ({})",
+                        time.format("%H:%M"),
+                        after.format("%H:%M"),
+                        before.format("%H:%M"),
+                        offset
+                    ))
+                }
+            }
+            Condition::DayOfWeek { days, offset } => {
+                let day = now.with_timezone(offset).weekday();
+                if days.contains(&day) {
+                    Ok(())
+                } else {
+                    Err(format!("day {} is not an allowed day ({})", day, offset))
+                }
+            }
+        }
+    }
+}
+
+impl Condition {
+    /// First attribute the condition tests that the context lacks, ignoring
+    /// attributes tested with `exists` (for which absence is meaningful)
+    fn missing_attribute(&self, context: &PolicyContext) -> Option<&str> {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().find_map(|c| c.missing_attribute(context))
+            }
+            Condition::Not(inner) => inner.missing_attribute(context),
+            Condition::Attribute { name, tests } => {
+                let checks_presence = tests.iter().any(|t| matches!(t, ValueTest::Exists(_)));
+                (!checks_presence && attribute(context, name).is_none()).then_some(name.as_str())
+            }
+            Condition::TimeOfDay { .. } | Condition::DayOfWeek { .. } => None,
+        }
+    }
+}
+
+impl ValueTest {
+    fn check(&self, name: &str, value: Option<&str>) -> Result<(), String> {
+        let Some(actual) = value else {
+            return match self {
+                ValueTest::Exists(false) => Ok(()),
+                _ => Err(format!("context has no '{}' attribute", name)),
+            };
+        };
+
+        let ok = match self {
+            ValueTest::Exists(expected) => *expected,
+            ValueTest::Eq(expected) => scalar_eq(actual, expected),
+            ValueTest::Ne(expected) => !scalar_eq(actual, expected),
+            ValueTest::Glob(pattern) => glob_match(pattern, actual),
+            ValueTest::In(patterns) => patterns.iter().any(|p| glob_match(p, actual)),
+            ValueTest::NotIn(patterns) => !patterns.iter().any(|p| glob_match(p, actual)),
+            ValueTest::Lt(n) | ValueTest::Lte(n) | ValueTest::Gt(n) | ValueTest::Gte(n) => {
+                let Ok(number) = actual.trim().parse::<f64>() else {
+                    return Err(format!("{} '{}' is not a number", name, actual));
+                };
+                match self {
+                    ValueTest::Lt(_) => number < *n,
+                    ValueTest::Lte(_) => number <= *n,
+                    ValueTest::Gt(_) => number > *n,
+                    _ => number >= *n,
+                }
+            }
+        };
+
+        if ok {
+            Ok(())
+        } else {
+            Err(self.describe_failure(name, actual))
+        }
+    }
+
+    fn describe_failure(&self, name: &str, actual: &str) -> String {
+        match self {
+            ValueTest::Exists(_) => format!("context has '{}' but it must be absent", name),
+            ValueTest::Eq(expected) => format!("{} '{}' is not '{}'", name, actual, expected),
+            ValueTest::Ne(expected) => format!("{} must not be '{}'", name, expected),
+            ValueTest::Glob(pattern) => format!("{} '{}' does not match '{}'", name, actual, pattern),
+            ValueTest::In(patterns) => {
+                format!("{} '{}' is not one of [{}]", name, actual, patterns.join(", "))
+            }
+            ValueTest::NotIn(patterns) => {
+                format!("{} '{}' is one of [{}]", name, actual, patterns.join(", "))
+            }
+            ValueTest::Lt(n) => format!("{} {} is not < {}", name, actual, n),
+            ValueTest::Lte(n) => format!("{} {} is not <= {}", name, actual, n),
+            ValueTest::Gt(n) => format!("{} {} is not > {}", name, actual, n),
+            ValueTest::Gte(n) => format!("{} {} is not >= {}", name, actual, n),
+        }
+    }
+}
+
+/// Compare scalars exactly, or numerically when both sides are numbers
+fn scalar_eq(actual: &str, expected: &str) -> bool {
+    if actual == expected {
+        return true;
+    }
+    match (actual.trim().parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Resolve an attribute from the request context
+fn attribute<'a>(context: &'a PolicyContext, name: &str) -> Option<&'a str> {
+    match name {
+        "database" => context.database.as_deref(),
+        "file_path" => context.file_path.as_deref(),
+        _ => context.extra.get(name).map(String::as_str),
+    }
+}
+
+/// Glob match: `*` matches within one path segment, `**` across segments,
+/// `?` matches one character
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    fn matches(p: &[char], v: &[char]) -> bool {
+        match p.first() {
+            None => v.is_empty(),
+            Some('*') if p.get(1) == Some(&'*') => {
+                let rest = &p[2..];
+                (0..=v.len()).any(|i| matches(rest, &v[i..]))
+            }
+            Some('*') => {
+                let rest = &p[1..];
+                for i in 0..=v.len() {
+                    if matches(rest, &v[i..]) {
+                        return true;
+                    }
+                    if v.get(i) == Some(&'/') {
+                        break;
+                    }
+                }
+                false
+            }
+            Some('?') => !v.is_empty() && v[0] != '/' && matches(&p[1..], &v[1..]),
+            Some(c) => v.first() == Some(c) && matches(&p[1..], &v[1..]),
+        }
+    }
+
+    let p: Vec<char> = pattern.chars().collect();
+    let v: Vec<char> = value.chars().collect();
+    matches(&p, &v)
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn parse_object(value: &Value, path: &str) -> Result<Condition, ConditionError> {
+    let Value::Object(map) = value else {
+        return Err(ConditionError::new(path, "condition must be an object"));
+    };
+    if map.is_empty() {
+        return Err(ConditionError::new(path, "condition must not be empty"));
+    }
+
+    let mut conditions = map
+        .iter()
+        .map(|(key, value)| parse_key(key, value, &child(path, key)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(if conditions.len() == 1 {
+        conditions.remove(0)
+    } else {
+        Condition::All(conditions)
+    })
+}
+
+fn parse_list(value: &Value, path: &str) -> Result<Vec<Condition>, ConditionError> {
+    let Value::Array(items) = value else {
+        return Err(ConditionError::new(path, "expected a list of conditions"));
+    };
+    if items.is_empty() {
+        return Err(ConditionError::new(path, "list must not be empty"));
+    }
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| parse_object(item, &format!("{}[{}]", path, i)))
+        .collect()
+}
+
+fn parse_key(key: &str, value: &Value, path: &str) -> Result<Condition, ConditionError> {
+    match key {
+        "all_of" => Ok(Condition::All(parse_list(value, path)?)),
+        "any_of" => Ok(Condition::Any(parse_list(value, path)?)),
+        "not" => Ok(Condition::Not(Box::new(parse_object(value, path)?))),
+        "time_of_day" => parse_time_of_day(value, path),
+        "day_of_week" => parse_day_of_week(value, path),
+        _ if key.trim().is_empty() => Err(ConditionError::new(path, "attribute name must not be empty")),
+        _ => Ok(Condition::Attribute {
+            name: key.to_string(),
+            tests: parse_tests(value, path)?,
+        }),
+    }
+}
+
+fn parse_tests(value: &Value, path: &str) -> Result<Vec<ValueTest>, ConditionError> {
+    match value {
+        // Bare string is a glob (backwards compatible with {"database": "analytics_*"})
+        Value::String(pattern) => Ok(vec![ValueTest::Glob(pattern.clone())]),
+        Value::Number(_) | Value::Bool(_) => Ok(vec![ValueTest::Eq(scalar(value, path)?)]),
+        Value::Array(_) => Ok(vec![ValueTest::In(string_list(value, path)?)]),
+        Value::Object(ops) => {
+            if ops.is_empty() {
+                return Err(ConditionError::new(path, "operator object must not be empty"));
+            }
+            ops.iter()
+                .map(|(op, operand)| parse_op(op, operand, &child(path, op)))
+                .collect()
+        }
+        Value::Null => Err(ConditionError::new(path, "null is not a valid attribute test")),
+    }
+}
+
+fn parse_op(op: &str, operand: &Value, path: &str) -> Result<ValueTest, ConditionError> {
+    match op {
+        "exists" => operand
+            .as_bool()
+            .map(ValueTest::Exists)
+            .ok_or_else(|| ConditionError::new(path, "expected true or false")),
+        "eq" => Ok(ValueTest::Eq(scalar(operand, path)?)),
+        "ne" => Ok(ValueTest::Ne(scalar(operand, path)?)),
+        "glob" => operand
+            .as_str()
+            .map(|p| ValueTest::Glob(p.to_string()))
+            .ok_or_else(|| ConditionError::new(path, "expected a glob string")),
+        "in" => Ok(ValueTest::In(string_list(operand, path)?)),
+        "not_in" => Ok(ValueTest::NotIn(string_list(operand, path)?)),
+        "lt" => Ok(ValueTest::Lt(number(operand, path)?)),
+        "lte" => Ok(ValueTest::Lte(number(operand, path)?)),
+        "gt" => Ok(ValueTest::Gt(number(operand, path)?)),
+        "gte" => Ok(ValueTest::Gte(number(operand, path)?)),
+        _ => Err(ConditionError::new(
+            path,
+            format!(
+                "unknown operator '{}' (expected exists, eq, ne, glob, in, not_in, lt, lte, gt, gte)",
+                op
+            ),
+        )),
+    }
+}
+
+fn scalar(value: &Value, path: &str) -> Result<String, ConditionError> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(ConditionError::new(path, "expected a string, number or boolean")),
+    }
+}
+
+fn number(value: &Value, path: &str) -> Result<f64, ConditionError> {
+    value
+        .as_f64()
+        .ok_or_else(|| ConditionError::new(path, "expected a number"))
+}
+
+fn string_list(value: &Value, path: &str) -> Result<Vec<String>, ConditionError> {
+    let Value::Array(items) = value else {
+        return Err(ConditionError::new(path, "expected a list"));
+    };
+    if items.is_empty() {
+        return Err(ConditionError::new(path, "list must not be empty"));
+    }
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| scalar(item, &format!("{}[{}]", path, i)))
+        .collect()
+}
+
+fn parse_offset(map: &Map<String, Value>, path: &str) -> Result<FixedOffset, ConditionError> {
+    let Some(value) = map.get("utc_offset") else {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset is valid"));
+    };
+    let path = child(path, "utc_offset");
+    let raw = value
+        .as_str()
+        .ok_or_else(|| ConditionError::new(&path, "expected \"+HH:MM\" or \"-HH:MM\""))?;
+
+    let (sign, rest) = if let Some(rest) = raw.strip_prefix('+') {
+        (1, rest)
+    } else if let Some(rest) = raw.strip_prefix('-') {
+        (-1, rest)
+    } else {
+        return Err(ConditionError::new(&path, "expected \"+HH:MM\" or \"-HH:MM\""));
+    };
+    let time = NaiveTime::parse_from_str(rest, "%H:%M")
+        .map_err(|_| ConditionError::new(&path, "expected \"+HH:MM\" or \"-HH:MM\""))?;
+    let seconds = sign * (time.hour() * 3600 + time.minute() * 60) as i32;
+
+    FixedOffset::east_opt(seconds).ok_or_else(|| ConditionError::new(&path, "offset out of range"))
+}
+
+fn parse_time_of_day(value: &Value, path: &str) -> Result<Condition, ConditionError> {
+    let Value::Object(map) = value else {
+        return Err(ConditionError::new(path, "expected {\"after\": \"HH:MM\", \"before\": \"HH:MM\"}"));
+    };
+
+    let time = |key: &str| -> Result<NaiveTime, ConditionError> {
+        let path = child(path, key);
+        map.get(key)
+            .and_then(Value::as_str)
+            .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M").ok())
+            .ok_or_else(|| ConditionError::new(&path, "expected a time as \"HH:MM\""))
+    };
+
+    if let Some(key) = map.keys().find(|k| !matches!(k.as_str(), "after" | "before" | "utc_offset")) {
+        return Err(ConditionError::new(&child(path, key), "unknown time_of_day field"));
+    }
+
+    let after = time("after")?;
+    let before = time("before")?;
+    if after == before {
+        return Err(ConditionError::new(path, "after and before must differ"));
+    }
+
+    Ok(Condition::TimeOfDay {
+        after,
+        before,
+        offset: parse_offset(map, path)?,
+    })
+}
+
+fn parse_day_of_week(value: &Value, path: &str) -> Result<Condition, ConditionError> {
+    let (days, offset) = match value {
+        Value::Array(_) => (value, FixedOffset::east_opt(0).expect("zero offset is valid")),
+        Value::Object(map) => {
+            if let Some(key) = map.keys().find(|k| !matches!(k.as_str(), "in" | "utc_offset")) {
+                return Err(ConditionError::new(&child(path, key), "unknown day_of_week field"));
+            }
+            let days = map
+                .get("in")
+                .ok_or_else(|| ConditionError::new(path, "expected an \"in\" list of days"))?;
+            (days, parse_offset(map, path)?)
+        }
+        _ => return Err(ConditionError::new(path, "expected a list of days (\"mon\".. \"sun\")")),
+    };
+
+    let days = string_list(days, path)?
+        .iter()
+        .map(|d| {
+            d.parse::<Weekday>()
+                .map_err(|_| ConditionError::new(path, format!("unknown day '{}'", d)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Condition::DayOfWeek { days, offset })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn ctx(extra: &[(&str, &str)]) -> PolicyContext {
+        PolicyContext {
+            database: Some("analytics_prod".to_string()),
+            file_path: Some("/finance/reports/q3.xlsx".to_string()),
+            extra: extra
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    // Tuesday 2025-11-11 10:30 UTC
+    fn tuesday_morning() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 11, 11, 10, 30, 0).unwrap()
+    }
+
+    fn check(condition: Value, context: &PolicyContext) -> Result<(), String> {
+        Condition::parse(&condition).unwrap().evaluate(context, tuesday_morning())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("analytics_*", "analytics_prod"));
+        assert!(!glob_match("analytics_*", "finance_db"));
+        assert!(glob_match("/finance/*", "/finance/budget.xlsx"));
+        assert!(!glob_match("/finance/*", "/finance/reports/q3.xlsx"));
+        assert!(glob_match("/finance/**", "/finance/reports/q3.xlsx"));
+        assert!(glob_match("/finance/**/*.xlsx", "/finance/reports/q3.xlsx"));
+        assert!(glob_match("q?.csv", "q3.csv"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn test_database_condition_backwards_compatible() {
+        let context = ctx(&[]);
+        assert!(check(json!({"database": "analytics_*"}), &context).is_ok());
+        assert_eq!(
+            check(json!({"database": "finance_*"}), &context),
+            Err("database 'analytics_prod' does not match 'finance_*'".to_string())
+        );
+        assert!(check(json!({"database": "analytics_*"}), &PolicyContext::empty()).is_err());
+    }
+
+    #[test]
+    fn test_file_path_and_extra_attributes() {
+        let context = ctx(&[("department", "finance"), ("amount", "2500.50")]);
+        assert!(check(json!({"file_path": "/finance/**"}), &context).is_ok());
+        assert!(check(json!({"file_path": "/hr/**"}), &context).is_err());
+        assert!(check(json!({"department": ["finance", "ops"]}), &context).is_ok());
+        assert!(check(json!({"department": {"not_in": ["legal"]}}), &context).is_ok());
+        assert!(check(json!({"region": {"exists": false}}), &context).is_ok());
+        assert!(check(json!({"region": "eu-*"}), &context).is_err());
+    }
+
+    #[test]
+    fn test_numeric_comparisons() {
+        let context = ctx(&[("amount", "2500.50")]);
+        assert!(check(json!({"amount": {"lte": 10000}}), &context).is_ok());
+        assert!(check(json!({"amount": {"gt": 1000, "lt": 5000}}), &context).is_ok());
+        assert!(check(json!({"amount": 2500.5}), &context).is_ok());
+        assert_eq!(
+            check(json!({"amount": {"lte": 1000}}), &context),
+            Err("amount 2500.50 is not <= 1000".to_string())
+        );
+
+        let context = ctx(&[("amount", "lots")]);
+        assert!(check(json!({"amount": {"lte": 10000}}), &context).is_err());
+    }
+
+    #[test]
+    fn test_time_windows() {
+        let context = ctx(&[]);
+        assert!(check(json!({"time_of_day": {"after": "09:00", "before": "17:00"}}), &context).is_ok());
+        assert!(check(json!({"time_of_day": {"after": "22:00", "before": "06:00"}}), &context).is_err());
+        // 10:30 UTC is 05:30 at -05:00
+        assert!(check(
+            json!({"time_of_day": {"after": "09:00", "before": "17:00", "utc_offset": "-05:00"}}),
+            &context
+        )
+        .is_err());
+
+        assert!(check(json!({"day_of_week": ["mon", "tue", "wed", "thu", "fri"]}), &context).is_ok());
+        assert!(check(json!({"day_of_week": ["sat", "sun"]}), &context).is_err());
+        // 10:30 Tuesday UTC is already Wednesday at +14:00
+        assert!(check(json!({"day_of_week": {"in": ["wed"], "utc_offset": "+14:00"}}), &context).is_ok());
+    }
+
+    #[test]
+    fn test_composition() {
+        let context = ctx(&[("amount", "50000")]);
+
+        let condition = json!({
+            "any_of": [
+                {"amount": {"lte": 10000}},
+                {"all_of": [{"database": "analytics_*"}, {"not": {"file_path": "/hr/**"}}]}
+            ]
+        });
+        assert!(check(condition, &context).is_ok());
+
+        let condition = json!({"not": {"database": "analytics_*"}});
+        assert_eq!(check(condition, &context), Err("negated condition matched".to_string()));
+
+        // `not` fails closed when the attribute is absent
+        let condition = json!({"not": {"department": "hr"}});
+        assert_eq!(check(condition.clone(), &context), Err("context has no 'department' attribute".to_string()));
+        assert!(check(condition, &ctx(&[("department", "finance")])).is_ok());
+        assert!(check(json!({"not": {"department": {"exists": true}}}), &context).is_ok());
+
+        // Sibling keys are all required
+        let condition = json!({"database": "analytics_*", "amount": {"lte": 10000}});
+        assert!(check(condition, &context).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_conditions() {
+        let error = |value: Value| Condition::parse(&value).unwrap_err();
+
+        assert_eq!(error(json!("analytics_*")).path, "$");
+        assert_eq!(error(json!({})).message, "condition must not be empty");
+        assert_eq!(error(json!({"amount": {"lte": "ten"}})).path, "amount.lte");
+        assert_eq!(error(json!({"amount": {"between": [1, 2]}})).path, "amount.between");
+        assert_eq!(error(json!({"any_of": []})).path, "any_of");
+        assert_eq!(error(json!({"any_of": [{"x": "y"}, 5]})).path, "any_of[1]");
+        assert_eq!(error(json!({"time_of_day": {"after": "9am", "before": "17:00"}})).path, "time_of_day.after");
+        assert_eq!(error(json!({"day_of_week": ["funday"]})).message, "unknown day 'funday'");
+        assert_eq!(
+            error(json!({"time_of_day": {"after": "09:00", "before": "17:00", "utc_offset": "EST"}})).path,
+            "time_of_day.utc_offset"
+        );
+        assert_eq!(error(json!({"region": null})).path, "region");
+    }
+}
@@ -10,16 +10,25 @@ use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::collections::HashMap;
+use chrono::{DateTime, Utc};
 use thiserror::Error;
+use tracing::warn;
+
+use super::audit::{NewPolicyDecision, PolicyAuditLog};
+use super::cache;
+use super::conditions::Condition;
 
 /// Policy evaluation context (for ABAC conditions)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct PolicyContext {
     /// Database name (for sql-mcp__query conditions)
+    #[serde(default)]
     pub database: Option<String>,
     /// File path (for developer__* conditions)
+    #[serde(default)]
     pub file_path: Option<String>,
     /// Additional context fields
+    #[serde(default)]
     pub extra: HashMap<String, String>,
 }
 
@@ -62,10 +71,11 @@ pub enum PolicyError {
 /// Policy record from database
 #[derive(Debug, Clone, sqlx::FromRow)]
 struct Policy {
+    id: i32,
     role: String,
     tool_pattern: String,
     allow: bool,
-    conditions: Option<sqlx::types::Json<HashMap<String, String>>>,
+    conditions: Option<sqlx::types::Json<serde_json::Value>>,
     reason: Option<String>,
 }
 
@@ -81,32 +91,189 @@ impl Policy {
         }
     }
 
-    /// Check if ABAC conditions are met
-    fn conditions_met(&self, context: &PolicyContext) -> bool {
+    /// Describe the first ABAC condition the context fails, if any
+    ///
+    /// Conditions that fail to parse (stored before validation existed) fail
+    /// closed: an allow policy never matches and a deny policy always does.
+    fn unmet_condition(&self, context: &PolicyContext, now: DateTime<Utc>) -> Option<String> {
         let Some(conditions) = &self.conditions else {
             // No conditions = always match
-            return true;
+            return None;
         };
 
-        // Check database condition (for sql-mcp__query)
-        if let Some(db_pattern) = conditions.get("database") {
-            let Some(db_name) = &context.database else {
-                return false; // Required condition missing
+        match Condition::parse(conditions) {
+            Ok(condition) => condition.evaluate(context, now).err(),
+            Err(e) => {
+                warn!(policy_id = self.id, error = %e, "Invalid policy conditions, failing closed");
+                self.allow.then(|| format!("invalid conditions: {}", e))
+            }
+        }
+    }
+
+    /// Whether the conditions depend on the clock
+    fn is_time_dependent(&self) -> bool {
+        self.conditions
+            .as_ref()
+            .and_then(|c| Condition::parse(c).ok())
+            .is_some_and(|c| c.is_time_dependent())
+    }
+
+    /// Reason reported when this policy denies a request
+    fn deny_reason(&self, role: &str, tool_name: &str) -> String {
+        self.reason
+            .clone()
+            .unwrap_or_else(|| format!("Policy denies {} access to {}", role, tool_name))
+    }
+}
+
+/// How a candidate policy related to the request being evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateOutcome {
+    /// First matching policy; it decided the request
+    Matched,
+    /// Tool pattern does not cover the tool
+    ToolMismatch,
+    /// Tool pattern matches but an ABAC condition failed
+    ConditionsNotMet,
+    /// Would have matched, but an earlier policy already decided
+    Shadowed,
+}
+
+/// One policy considered during an explained evaluation
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PolicyCandidate {
+    /// policies.id
+    pub policy_id: i32,
+    /// Tool pattern of the policy
+    #[schema(example = "developer__*")]
+    pub tool_pattern: String,
+    /// Whether the policy allows or denies
+    pub allow: bool,
+    /// ABAC conditions of the policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub conditions: Option<serde_json::Value>,
+    /// How the policy related to the request
+    pub outcome: CandidateOutcome,
+    /// Human-readable explanation of the outcome
+    pub explanation: String,
+}
+
+/// Result of evaluating a tool-use policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    /// Whether the tool may be used
+    pub allowed: bool,
+    /// Policy that decided the request (None = default deny)
+    pub matched_policy_id: Option<i32>,
+    /// Whether the decision came from the Redis cache
+    pub cache_hit: bool,
+    /// Policy version the decision was made under
+    pub policy_version: i64,
+    /// Why access was denied (None when allowed)
+    pub reason: Option<String>,
+    /// Candidate policies in evaluation order (explain mode only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<PolicyCandidate>,
+    /// A time-of-day/day-of-week condition was consulted (not cacheable)
+    #[serde(skip)]
+    pub time_dependent: bool,
+}
+
+impl PolicyDecision {
+    /// Decide from policies in evaluation order: first match wins
+    fn from_policies(
+        policies: &[Policy],
+        role: &str,
+        tool_name: &str,
+        context: &PolicyContext,
+        now: DateTime<Utc>,
+        explain: bool,
+    ) -> Self {
+        let mut decided: Option<&Policy> = None;
+        let mut candidates = Vec::new();
+        let mut time_dependent = false;
+
+        for policy in policies {
+            if decided.is_some() && !explain {
+                break;
+            }
+            if decided.is_none() && policy.matches_tool(tool_name) {
+                time_dependent |= policy.is_time_dependent();
+            }
+
+            let (outcome, explanation) = if !policy.matches_tool(tool_name) {
+                (
+                    CandidateOutcome::ToolMismatch,
+                    format!("tool '{}' does not match pattern '{}'", tool_name, policy.tool_pattern),
+                )
+            } else if let Some(unmet) = policy.unmet_condition(context, now) {
+                (CandidateOutcome::ConditionsNotMet, unmet)
+            } else if let Some(winner) = decided {
+                (
+                    CandidateOutcome::Shadowed,
+                    format!("matches, but policy {} was evaluated first", winner.id),
+                )
+            } else {
+                decided = Some(policy);
+                let verb = if policy.allow { "allows" } else { "denies" };
+                (CandidateOutcome::Matched, format!("first match; {} the request", verb))
             };
 
-            // Support glob patterns: "analytics_*"
-            if db_pattern.ends_with('*') {
-                let prefix = db_pattern.trim_end_matches('*');
-                if !db_name.starts_with(prefix) {
-                    return false;
-                }
-            } else if db_name != db_pattern {
-                return false;
+            if explain {
+                candidates.push(PolicyCandidate {
+                    policy_id: policy.id,
+                    tool_pattern: policy.tool_pattern.clone(),
+                    allow: policy.allow,
+                    conditions: policy.conditions.as_ref().map(|c| c.0.clone()),
+                    outcome,
+                    explanation,
+                });
             }
         }
 
-        // All conditions met
-        true
+        match decided {
+            Some(policy) => Self {
+                allowed: policy.allow,
+                matched_policy_id: Some(policy.id),
+                cache_hit: false,
+                policy_version: 0,
+                reason: (!policy.allow).then(|| policy.deny_reason(role, tool_name)),
+                candidates,
+                time_dependent,
+            },
+            None => Self {
+                allowed: false,
+                matched_policy_id: None,
+                cache_hit: false,
+                policy_version: 0,
+                reason: Some(default_deny_reason(role, tool_name)),
+                candidates,
+                time_dependent,
+            },
+        }
+    }
+}
+
+fn default_deny_reason(role: &str, tool_name: &str) -> String {
+    format!(
+        "No policy found for role '{}' and tool '{}' (default deny)",
+        role, tool_name
+    )
+}
+
+/// Decision as stored in the Redis cache
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedDecision {
+    allow: bool,
+    policy_id: Option<i32>,
+    reason: Option<String>,
+}
+
+impl CachedDecision {
+    fn parse(value: &str) -> Option<Self> {
+        serde_json::from_str(value).ok()
     }
 }
 
@@ -114,6 +281,9 @@ impl Policy {
 pub struct PolicyEngine {
     postgres_pool: PgPool,
     redis_client: Option<redis::aio::ConnectionManager>,
+    subject: Option<String>,
+    version: i64,
+    audit: bool,
 }
 
 impl PolicyEngine {
@@ -125,13 +295,36 @@ impl PolicyEngine {
         Self {
             postgres_pool,
             redis_client,
+            subject: None,
+            version: 0,
+            audit: true,
         }
     }
 
+    /// Record the caller's subject on audited decisions
+    pub fn with_subject(mut self, subject: Option<String>) -> Self {
+        self.subject = subject;
+        self
+    }
+
+    /// Policy version to key cached decisions by (see `cache::PolicyVersion`)
+    pub fn with_version(mut self, version: i64) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Whether decisions are written to the audit log (off for what-if
+    /// evaluations, which must not mix with enforced decisions)
+    pub fn with_audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+
     /// Evaluate if role can use tool
     ///
     /// Returns true if allowed, false if denied.
-    /// Uses Redis cache with 300s TTL for performance.
+    /// Uses Redis cache with 300s TTL for performance, keyed by policy
+    /// version, role, tool and context.
     /// Defaults to deny if no policy found (security-first).
     pub async fn can_use_tool(
         &self,
@@ -139,62 +332,107 @@ impl PolicyEngine {
         tool_name: &str,
         context: &PolicyContext,
     ) -> Result<bool, PolicyError> {
-        let cache_key = format!("policy:{}:{}", role, tool_name);
+        let decision = self.evaluate(role, tool_name, context, false).await?;
 
-        // 1. Check cache (if Redis available)
-        if let Some(redis) = &self.redis_client {
-            let mut conn = redis.clone();
-            if let Ok(cached) = conn.get::<_, Option<String>>(&cache_key).await {
-                if let Some(value) = cached {
-                    // Cache hit
-                    return Ok(value == "allow");
-                }
-            }
+        if decision.matched_policy_id.is_none() && !decision.allowed {
+            // No policy found - deny by default
+            return Err(PolicyError::Denied(
+                decision.reason.unwrap_or_else(|| default_deny_reason(role, tool_name)),
+            ));
         }
 
-        // 2. Load policies from database
-        let policies = sqlx::query_as::<_, Policy>(
-            "SELECT role, tool_pattern, allow, conditions, reason 
-             FROM policies 
-             WHERE role = $1 
-             ORDER BY tool_pattern DESC", // Most specific first
-        )
-        .bind(role)
-        .fetch_all(&self.postgres_pool)
-        .await
-        .context("Failed to load policies from database")?;
-
-        // 3. Evaluate policies
-        let mut result = false; // Deny by default
-        let mut matched = false;
+        Ok(decision.allowed)
+    }
 
-        for policy in policies {
-            if policy.matches_tool(tool_name) && policy.conditions_met(context) {
-                result = policy.allow;
-                matched = true;
-                break; // First match wins
+    /// Evaluate a tool-use request and record the decision
+    ///
+    /// In explain mode the cache is bypassed and every policy for the role is
+    /// returned in evaluation order with the reason it did or didn't match.
+    /// Every decision is written to the policy_decisions audit log unless
+    /// auditing is disabled (see `with_audit`).
+    pub async fn evaluate(
+        &self,
+        role: &str,
+        tool_name: &str,
+        context: &PolicyContext,
+        explain: bool,
+    ) -> Result<PolicyDecision, PolicyError> {
+        let cache_key = cache::cache_key(self.version, role, tool_name, context);
+
+        // 1. Check cache (if Redis available)
+        let cached = match (&self.redis_client, explain) {
+            (Some(redis), false) => {
+                let mut conn = redis.clone();
+                conn.get::<_, Option<String>>(&cache_key)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|value| CachedDecision::parse(&value))
             }
-        }
+            _ => None,
+        };
 
-        // 4. Cache result (if Redis available)
-        if let Some(redis) = &self.redis_client {
-            let mut conn = redis.clone();
-            let cache_value = if result { "allow" } else { "deny" };
-            let _ = conn
-                .set_ex::<_, _, ()>(&cache_key, cache_value, 300) // 5 min TTL
-                .await;
-        }
+        let decision = if let Some(cached) = cached {
+            PolicyDecision {
+                allowed: cached.allow,
+                matched_policy_id: cached.policy_id,
+                cache_hit: true,
+                policy_version: self.version,
+                reason: cached.reason,
+                candidates: Vec::new(),
+                time_dependent: false,
+            }
+        } else {
+            // 2. Load policies from database
+            let policies = sqlx::query_as::<_, Policy>(
+                "SELECT id, role, tool_pattern, allow, conditions, reason 
+                 FROM policies 
+                 WHERE role = $1 
+                 ORDER BY tool_pattern DESC, id ASC", // Most specific first
+            )
+            .bind(role)
+            .fetch_all(&self.postgres_pool)
+            .await
+            .context("Failed to load policies from database")?;
+
+            // 3. Evaluate policies (first match wins, deny by default)
+            let mut decision =
+                PolicyDecision::from_policies(&policies, role, tool_name, context, Utc::now(), explain);
+            decision.policy_version = self.version;
+
+            // 4. Cache result (if Redis available; time windows are re-evaluated every call)
+            if let (Some(redis), false) = (&self.redis_client, decision.time_dependent) {
+                let mut conn = redis.clone();
+                let cache_value = serde_json::to_string(&CachedDecision {
+                    allow: decision.allowed,
+                    policy_id: decision.matched_policy_id,
+                    reason: decision.reason.clone(),
+                })
+                .context("Failed to serialize policy decision")?;
+                let _ = conn
+                    .set_ex::<_, _, ()>(&cache_key, cache_value, 300) // 5 min TTL
+                    .await;
+            }
 
-        // 5. Return result (deny by default if no match)
-        if !matched {
-            // No policy found - deny by default
-            return Err(PolicyError::Denied(format!(
-                "No policy found for role '{}' and tool '{}' (default deny)",
-                role, tool_name
-            )));
+            decision
+        };
+
+        // 5. Audit the decision
+        if self.audit {
+            PolicyAuditLog::new(self.postgres_pool.clone()).record_in_background(NewPolicyDecision {
+                role: role.to_string(),
+                tool: tool_name.to_string(),
+                context: context.clone(),
+                matched_policy_id: decision.matched_policy_id,
+                allowed: decision.allowed,
+                cache_hit: decision.cache_hit,
+                policy_version: decision.policy_version,
+                reason: decision.reason.clone(),
+                subject: self.subject.clone(),
+            });
         }
 
-        Ok(result)
+        Ok(decision)
     }
 
     /// Evaluate if role can access data
@@ -221,6 +459,7 @@ mod tests {
     #[test]
     fn test_policy_matches_tool() {
         let policy = Policy {
+            id: 1,
             role: "finance".to_string(),
             tool_pattern: "github__*".to_string(),
             allow: true,
@@ -236,6 +475,7 @@ mod tests {
     #[test]
     fn test_policy_exact_match() {
         let policy = Policy {
+            id: 2,
             role: "finance".to_string(),
             tool_pattern: "developer__shell".to_string(),
             allow: false,
@@ -249,10 +489,10 @@ mod tests {
 
     #[test]
     fn test_policy_conditions_database() {
-        let mut conditions = HashMap::new();
-        conditions.insert("database".to_string(), "analytics_*".to_string());
+        let conditions = serde_json::json!({"database": "analytics_*"});
 
         let policy = Policy {
+            id: 3,
             role: "analyst".to_string(),
             tool_pattern: "sql-mcp__query".to_string(),
             allow: true,
@@ -262,20 +502,21 @@ mod tests {
 
         // Match
         let ctx = PolicyContext::with_database("analytics_prod");
-        assert!(policy.conditions_met(&ctx));
+        assert!(policy.unmet_condition(&ctx, Utc::now()).is_none());
 
         // No match
         let ctx = PolicyContext::with_database("finance_db");
-        assert!(!policy.conditions_met(&ctx));
+        assert!(policy.unmet_condition(&ctx, Utc::now()).is_some());
 
         // Missing context
         let ctx = PolicyContext::empty();
-        assert!(!policy.conditions_met(&ctx));
+        assert!(policy.unmet_condition(&ctx, Utc::now()).is_some());
     }
 
     #[test]
     fn test_policy_no_conditions() {
         let policy = Policy {
+            id: 4,
             role: "manager".to_string(),
             tool_pattern: "agent_mesh__*".to_string(),
             allow: true,
@@ -285,6 +526,124 @@ mod tests {
 
         // Always match when no conditions
         let ctx = PolicyContext::empty();
-        assert!(policy.conditions_met(&ctx));
+        assert!(policy.unmet_condition(&ctx, Utc::now()).is_none());
+    }
+
+    fn policy(id: i32, tool_pattern: &str, allow: bool, database: Option<&str>) -> Policy {
+        Policy {
+            id,
+            role: "finance".to_string(),
+            tool_pattern: tool_pattern.to_string(),
+            allow,
+            conditions: database.map(|db| {
+                sqlx::types::Json(serde_json::json!({"database": db}))
+            }),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_decision_first_match_wins() {
+        let policies = vec![
+            policy(1, "sql-mcp__query", true, Some("analytics_*")),
+            policy(2, "developer__shell", false, None),
+            policy(3, "developer__*", true, None),
+        ];
+
+        let decision = PolicyDecision::from_policies(
+            &policies, "finance", "developer__shell", &PolicyContext::empty(), Utc::now(), false,
+        );
+        assert!(!decision.allowed);
+        assert_eq!(decision.matched_policy_id, Some(2));
+        assert_eq!(
+            decision.reason.as_deref(),
+            Some("Policy denies finance access to developer__shell")
+        );
+        assert!(decision.candidates.is_empty());
+    }
+
+    #[test]
+    fn test_decision_default_deny() {
+        let policies = vec![policy(1, "github__*", true, None)];
+
+        let decision = PolicyDecision::from_policies(
+            &policies, "finance", "developer__shell", &PolicyContext::empty(), Utc::now(), false,
+        );
+        assert!(!decision.allowed);
+        assert_eq!(decision.matched_policy_id, None);
+        assert!(decision.reason.unwrap().contains("default deny"));
+    }
+
+    #[test]
+    fn test_decision_explain_lists_all_candidates() {
+        let policies = vec![
+            policy(1, "sql-mcp__query", true, Some("analytics_*")),
+            policy(2, "sql-mcp__*", false, None),
+            policy(3, "sql-mcp__*", true, None),
+            policy(4, "github__*", true, None),
+        ];
+
+        let ctx = PolicyContext::with_database("finance_db");
+        let decision =
+            PolicyDecision::from_policies(&policies, "finance", "sql-mcp__query", &ctx, Utc::now(), true);
+
+        assert!(!decision.allowed);
+        assert_eq!(decision.matched_policy_id, Some(2));
+
+        let outcomes: Vec<_> = decision.candidates.iter().map(|c| (c.policy_id, c.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (1, CandidateOutcome::ConditionsNotMet),
+                (2, CandidateOutcome::Matched),
+                (3, CandidateOutcome::Shadowed),
+                (4, CandidateOutcome::ToolMismatch),
+            ]
+        );
+        assert_eq!(
+            decision.candidates[0].explanation,
+            "database 'finance_db' does not match 'analytics_*'"
+        );
+    }
+
+    #[test]
+    fn test_cached_decision_parse() {
+        let cached = CachedDecision::parse(r#"{"allow":false,"policy_id":7,"reason":"No code execution"}"#).unwrap();
+        assert!(!cached.allow);
+        assert_eq!(cached.policy_id, Some(7));
+        assert_eq!(cached.reason.as_deref(), Some("No code execution"));
+
+        assert!(CachedDecision::parse("allow").is_none());
+    }
+
+    #[test]
+    fn test_time_conditions_mark_decision_uncacheable() {
+        let mut business_hours = policy(1, "developer__*", true, None);
+        business_hours.conditions = Some(sqlx::types::Json(
+            serde_json::json!({"time_of_day": {"after": "00:00", "before": "23:59"}}),
+        ));
+        let policies = vec![policy(2, "github__*", true, None), business_hours];
+
+        let decision = PolicyDecision::from_policies(
+            &policies, "finance", "developer__shell", &PolicyContext::empty(), Utc::now(), false,
+        );
+        assert!(decision.time_dependent);
+
+        let decision = PolicyDecision::from_policies(
+            &policies, "finance", "github__list_issues", &PolicyContext::empty(), Utc::now(), false,
+        );
+        assert!(!decision.time_dependent);
+    }
+
+    #[test]
+    fn test_invalid_conditions_fail_closed() {
+        let mut allow = policy(1, "sql-mcp__query", true, None);
+        allow.conditions = Some(sqlx::types::Json(serde_json::json!({"amount": {"between": [1, 2]}})));
+        let mut deny = allow.clone();
+        deny.allow = false;
+
+        let ctx = PolicyContext::with_database("analytics_prod");
+        assert!(allow.unmet_condition(&ctx, Utc::now()).unwrap().starts_with("invalid conditions"));
+        assert!(deny.unmet_condition(&ctx, Utc::now()).is_none());
     }
 }
@@ -1,6 +1,14 @@
 // Policy engine module for RBAC/ABAC enforcement
 // Phase 5 Workstream C
 
+pub mod audit;
+pub mod cache;
+pub mod conditions;
 pub mod engine;
 
-pub use engine::{PolicyEngine, PolicyContext, PolicyError};
+pub use audit::{PolicyAuditLog, PolicyDecisionFilter, PolicyDecisionRecord};
+pub use cache::PolicyVersion;
+pub use conditions::{Condition, ConditionError};
+pub use engine::{
+    CandidateOutcome, PolicyCandidate, PolicyContext, PolicyDecision, PolicyEngine, PolicyError,
+};
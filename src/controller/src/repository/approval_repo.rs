@@ -0,0 +1,429 @@
+use sqlx::PgPool;
+use sqlx::types::Uuid;
+use crate::approval::PlannedStep;
+use crate::auth::Principal;
+use crate::models::{Approval, ApprovalStep, CreateApprovalRequest, CreateTaskRequest, Task};
+use crate::repository::task_repo::insert_task;
+
+/// Approval repository for database operations
+///
+/// Recording a decision is transactional: the approval row is inserted, the
+/// current approval step (if the task has a chain) is closed, and the task
+/// moves to `approved`/`rejected` together, or not at all.
+pub struct ApprovalRepository {
+    pool: PgPool,
+}
+
+impl ApprovalRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record an approval decision and apply it to the referenced task
+    ///
+    /// When the current approval step names an org chart user, only that user
+    /// may decide; otherwise the caller must hold the step's role (or the
+    /// task's target role). Each caller decides at most once per task, so one
+    /// person holding several roles cannot clear a whole chain. The decision
+    /// is recorded under the required role. The task row is locked for the
+    /// duration of the transaction so two concurrent decisions on the same
+    /// task cannot both succeed.
+    pub async fn record_decision(
+        &self,
+        req: &CreateApprovalRequest,
+        principal: &Principal,
+    ) -> Result<(Approval, Task), ApprovalError> {
+        let mut tx = self.pool.begin().await?;
+
+        let task = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, task_type, description, data, source, target, status,
+                   context, trace_id, idempotency_key, created_at, updated_at, completed_at
+            FROM tasks
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(req.task_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApprovalError::TaskNotFound(req.task_id))?;
+
+        // Multi-level chains: the first pending step decides who may act next
+        let steps = sqlx::query_as::<_, ApprovalStep>(
+            r#"
+            SELECT id, task_id, step_order, approver_role, approver_user_id, rule_name,
+                   status, approval_id, created_at, decided_at
+            FROM approval_steps
+            WHERE task_id = $1
+            ORDER BY step_order ASC
+            FOR UPDATE
+            "#,
+        )
+        .bind(req.task_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut pending = steps.iter().filter(|s| s.status == "pending");
+        let current_step = pending.next();
+        let next_step = pending.next();
+
+        let required_role = current_step
+            .map(|s| s.approver_role.as_str())
+            .unwrap_or(&task.target);
+
+        match current_step.and_then(|s| s.approver_user_id) {
+            Some(user_id) => {
+                let approver_email = sqlx::query_scalar::<_, String>(
+                    "SELECT email FROM org_users WHERE user_id = $1",
+                )
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if !approver_email.is_some_and(|email| is_assigned_approver(&email, principal)) {
+                    return Err(ApprovalError::NotAssignedApprover {
+                        task_id: task.id,
+                        user_id,
+                    });
+                }
+            }
+            None => {
+                if !can_decide(required_role, principal) {
+                    return Err(ApprovalError::NotAuthorized {
+                        task_id: task.id,
+                        role: required_role.to_string(),
+                    });
+                }
+            }
+        }
+
+        let already_decided = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM approvals WHERE task_id = $1 AND approver = $2)",
+        )
+        .bind(req.task_id)
+        .bind(&req.approver)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if already_decided {
+            return Err(ApprovalError::DuplicateApprover {
+                task_id: task.id,
+                approver: req.approver.clone(),
+            });
+        }
+
+        if !is_open_status(&task.status) {
+            return Err(ApprovalError::AlreadyDecided {
+                task_id: task.id,
+                status: task.status,
+            });
+        }
+
+        if decision_to_task_status(&req.decision).is_none() {
+            return Err(ApprovalError::InvalidDecision(req.decision.clone()));
+        }
+
+        let approval = sqlx::query_as::<_, Approval>(
+            r#"
+            INSERT INTO approvals (
+                task_id, approver, approver_role, decision, comments, trace_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, task_id, approver, approver_role, decision, comments,
+                      trace_id, created_at
+            "#,
+        )
+        .bind(req.task_id)
+        .bind(&req.approver)
+        .bind(required_role)
+        .bind(&req.decision)
+        .bind(&req.comments)
+        .bind(req.trace_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(step) = current_step {
+            sqlx::query(
+                "UPDATE approval_steps SET status = $2, approval_id = $3, decided_at = NOW() WHERE id = $1",
+            )
+            .bind(step.id)
+            .bind(&req.decision)
+            .bind(approval.id)
+            .execute(&mut *tx)
+            .await?;
+
+            // A rejection ends the chain; later approvers are not consulted
+            if req.decision == "rejected" {
+                sqlx::query(
+                    "UPDATE approval_steps SET status = 'skipped' WHERE task_id = $1 AND status = 'pending'",
+                )
+                .bind(req.task_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let task = match task_status_after(&req.decision, next_step.is_some()) {
+            Some(task_status) => {
+                sqlx::query_as::<_, Task>(
+                    r#"
+                    UPDATE tasks
+                    SET status = $2,
+                        completed_at = NOW(),
+                        lease_id = NULL,
+                        claimed_by = NULL,
+                        lease_expires_at = NULL
+                    WHERE id = $1
+                    RETURNING id, task_type, description, data, source, target, status,
+                              context, trace_id, idempotency_key, created_at, updated_at, completed_at
+                    "#,
+                )
+                .bind(req.task_id)
+                .bind(task_status)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+            None => {
+                // More approvers remain: hand the task to the next role in the
+                // chain, back in the queue and free of the previous worker's lease
+                let next_role = next_step.map(|s| s.approver_role.as_str()).unwrap_or(&task.target);
+                sqlx::query_as::<_, Task>(
+                    r#"
+                    UPDATE tasks
+                    SET target = $2,
+                        status = 'pending',
+                        lease_id = NULL,
+                        claimed_by = NULL,
+                        lease_expires_at = NULL
+                    WHERE id = $1
+                    RETURNING id, task_type, description, data, source, target, status,
+                              context, trace_id, idempotency_key, created_at, updated_at, completed_at
+                    "#,
+                )
+                .bind(req.task_id)
+                .bind(next_role)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok((approval, task))
+    }
+
+    /// Create a task together with its planned approval chain
+    ///
+    /// The task and its steps are inserted in one transaction, so a task never
+    /// exists without the chain it was planned with. With a chain, the task is
+    /// routed to the first approver's role so it shows up in that role's task
+    /// list.
+    pub async fn create_task_with_steps(
+        &self,
+        req: &CreateTaskRequest,
+        steps: &[PlannedStep],
+    ) -> sqlx::Result<Task> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut task = insert_task(&mut *tx, req).await?;
+
+        for step in steps {
+            sqlx::query(
+                r#"
+                INSERT INTO approval_steps (
+                    task_id, step_order, approver_role, approver_user_id, rule_name
+                )
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(task.id)
+            .bind(step.step_order)
+            .bind(&step.approver_role)
+            .bind(step.approver_user_id)
+            .bind(&step.rule_name)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(first) = steps.first() {
+            sqlx::query("UPDATE tasks SET target = $2 WHERE id = $1")
+                .bind(task.id)
+                .bind(&first.approver_role)
+                .execute(&mut *tx)
+                .await?;
+            task.target = first.approver_role.clone();
+        }
+
+        tx.commit().await?;
+
+        Ok(task)
+    }
+
+    /// List the approval chain for a task (in order)
+    pub async fn list_steps(&self, task_id: Uuid) -> sqlx::Result<Vec<ApprovalStep>> {
+        let steps = sqlx::query_as::<_, ApprovalStep>(
+            r#"
+            SELECT id, task_id, step_order, approver_role, approver_user_id, rule_name,
+                   status, approval_id, created_at, decided_at
+            FROM approval_steps
+            WHERE task_id = $1
+            ORDER BY step_order ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(steps)
+    }
+
+    /// List all approvals recorded for a task (oldest first)
+    pub async fn list_by_task(&self, task_id: Uuid) -> sqlx::Result<Vec<Approval>> {
+        let approvals = sqlx::query_as::<_, Approval>(
+            r#"
+            SELECT id, task_id, approver, approver_role, decision, comments,
+                   trace_id, created_at
+            FROM approvals
+            WHERE task_id = $1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(approvals)
+    }
+}
+
+/// Check whether a caller may decide as `required_role`
+///
+/// Only callers holding the role the task is routed to (or the current
+/// approval step's role) can approve or reject it.
+pub fn can_decide(required_role: &str, principal: &Principal) -> bool {
+    !required_role.is_empty() && principal.has_role(required_role)
+}
+
+/// Check whether the caller is the org chart user assigned to a step
+pub fn is_assigned_approver(approver_email: &str, principal: &Principal) -> bool {
+    principal
+        .email
+        .as_deref()
+        .is_some_and(|email| email.eq_ignore_ascii_case(approver_email))
+}
+
+/// Check whether a task is still awaiting a decision
+pub fn is_open_status(status: &str) -> bool {
+    matches!(status, "pending" | "claimed" | "in_progress" | "active")
+}
+
+/// Map an approval decision to the resulting task status
+pub fn decision_to_task_status(decision: &str) -> Option<&'static str> {
+    match decision {
+        "approved" => Some("approved"),
+        "rejected" => Some("rejected"),
+        _ => None,
+    }
+}
+
+/// Task status to apply after a decision, or None if the task stays open
+///
+/// An approval only finalizes the task once no further chain steps remain;
+/// a rejection always finalizes it.
+pub fn task_status_after(decision: &str, has_next_step: bool) -> Option<&'static str> {
+    match decision {
+        "approved" if has_next_step => None,
+        _ => decision_to_task_status(decision),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("Task not found: {0}")]
+    TaskNotFound(Uuid),
+
+    #[error("Caller does not hold role '{role}' required to decide on task {task_id}")]
+    NotAuthorized { task_id: Uuid, role: String },
+
+    #[error("Task {task_id} must be decided by its assigned approver (org user {user_id})")]
+    NotAssignedApprover { task_id: Uuid, user_id: i32 },
+
+    #[error("'{approver}' has already decided on task {task_id}")]
+    DuplicateApprover { task_id: Uuid, approver: String },
+
+    #[error("Task {task_id} is already {status}")]
+    AlreadyDecided { task_id: Uuid, status: String },
+
+    #[error("Invalid decision: {0}")]
+    InvalidDecision(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(role: Option<&str>, groups: &[&str]) -> Principal {
+        Principal {
+            subject: "user-1".to_string(),
+            email: Some("user@example.com".to_string()),
+            username: None,
+            role: role.map(str::to_string),
+            roles: vec![],
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_can_decide() {
+        assert!(can_decide("manager", &principal(Some("manager"), &[])));
+        assert!(can_decide("manager", &principal(Some("finance"), &["manager"])));
+        assert!(!can_decide("manager", &principal(Some("finance"), &[])));
+        assert!(!can_decide("manager", &principal(None, &[])));
+        assert!(!can_decide("", &principal(None, &[""])));
+    }
+
+    #[test]
+    fn test_is_assigned_approver() {
+        let caller = principal(Some("manager"), &[]);
+        assert!(is_assigned_approver("user@example.com", &caller));
+        assert!(is_assigned_approver("User@Example.com", &caller));
+        assert!(!is_assigned_approver("boss@example.com", &caller));
+
+        let no_email = Principal { email: None, ..caller };
+        assert!(!is_assigned_approver("user@example.com", &no_email));
+    }
+
+    #[test]
+    fn test_is_open_status() {
+        assert!(is_open_status("pending"));
+        assert!(is_open_status("active"));
+        assert!(is_open_status("claimed"));
+        assert!(is_open_status("in_progress"));
+        assert!(!is_open_status("approved"));
+        assert!(!is_open_status("rejected"));
+        assert!(!is_open_status("completed"));
+        assert!(!is_open_status("cancelled"));
+    }
+
+    #[test]
+    fn test_decision_to_task_status() {
+        assert_eq!(decision_to_task_status("approved"), Some("approved"));
+        assert_eq!(decision_to_task_status("rejected"), Some("rejected"));
+        assert_eq!(decision_to_task_status("pending"), None);
+    }
+
+    #[test]
+    fn test_task_status_after_chain_steps() {
+        // Intermediate approval keeps the task open
+        assert_eq!(task_status_after("approved", true), None);
+        // Final approval closes it
+        assert_eq!(task_status_after("approved", false), Some("approved"));
+        // Rejection closes it regardless of remaining steps
+        assert_eq!(task_status_after("rejected", true), Some("rejected"));
+        assert_eq!(task_status_after("rejected", false), Some("rejected"));
+    }
+}
@@ -1,5 +1,7 @@
 pub mod session_repo;
 pub mod task_repo;
+pub mod approval_repo;
 
 pub use session_repo::SessionRepository;
 pub use task_repo::TaskRepository;
+pub use approval_repo::{ApprovalRepository, ApprovalError};
@@ -1,6 +1,7 @@
-use sqlx::{PgPool, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool, Result};
 use sqlx::types::Uuid;
-use crate::models::{Task, CreateTaskRequest};
+use crate::models::{Task, ClaimedTask, CreateTaskRequest};
 
 /// Task repository for database operations
 pub struct TaskRepository {
@@ -14,30 +15,7 @@ impl TaskRepository {
 
     /// Create a new task
     pub async fn create(&self, req: &CreateTaskRequest) -> Result<Task> {
-        let task = sqlx::query_as::<_, Task>(
-            r#"
-            INSERT INTO tasks (
-                task_type, description, data, source, target,
-                status, context, trace_id, idempotency_key
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING id, task_type, description, data, source, target, status,
-                      context, trace_id, idempotency_key, created_at, updated_at, completed_at
-            "#,
-        )
-        .bind(&req.task_type)
-        .bind(&req.description)
-        .bind(req.data.as_ref().unwrap_or(&serde_json::json!({})))
-        .bind(&req.source)
-        .bind(&req.target)
-        .bind("pending")  // Default status
-        .bind(req.context.as_ref().unwrap_or(&serde_json::json!({})))
-        .bind(req.trace_id)
-        .bind(req.idempotency_key)
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(task)
+        insert_task(&self.pool, req).await
     }
 
     /// Get a task by ID
@@ -117,6 +95,170 @@ impl TaskRepository {
         Ok(task)
     }
 
+    /// Move a task from one status to another
+    ///
+    /// The update only applies if the task is still in `from`, so concurrent
+    /// transitions cannot both succeed. With `lease_id`, it also only applies
+    /// while that lease is held and unexpired. Returns None if the status or
+    /// lease changed underneath the caller (or the task does not exist).
+    pub async fn transition_status(
+        &self,
+        id: Uuid,
+        from: &str,
+        to: &str,
+        lease_id: Option<Uuid>,
+    ) -> Result<Option<Task>> {
+        let task = sqlx::query_as::<_, Task>(
+            r#"
+            UPDATE tasks
+            SET status = $3,
+                completed_at = CASE WHEN $3 IN ('completed', 'failed', 'cancelled') THEN NOW() ELSE NULL END,
+                lease_id = CASE WHEN $3 IN ('claimed', 'in_progress') THEN lease_id ELSE NULL END,
+                claimed_by = CASE WHEN $3 IN ('claimed', 'in_progress') THEN claimed_by ELSE NULL END,
+                claimed_at = CASE WHEN $3 IN ('claimed', 'in_progress') THEN claimed_at ELSE NULL END,
+                lease_expires_at = CASE WHEN $3 IN ('claimed', 'in_progress') THEN lease_expires_at ELSE NULL END
+            WHERE id = $1 AND status = $2
+              AND ($4::uuid IS NULL OR (lease_id = $4 AND lease_expires_at > NOW()))
+            RETURNING id, task_type, description, data, source, target, status,
+                      context, trace_id, idempotency_key, created_at, updated_at, completed_at
+            "#,
+        )
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .bind(lease_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Get a task's lease token and expiry (both None if it was never leased)
+    pub async fn lease(&self, id: Uuid) -> Result<Option<(Option<Uuid>, Option<DateTime<Utc>>)>> {
+        let lease = sqlx::query_as::<_, (Option<Uuid>, Option<DateTime<Utc>>)>(
+            "SELECT lease_id, lease_expires_at FROM tasks WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(lease)
+    }
+
+    /// Check whether a task still has undecided approval steps
+    pub async fn has_pending_approval_steps(&self, id: Uuid) -> Result<bool> {
+        let pending = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM approval_steps WHERE task_id = $1 AND status = 'pending')",
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(pending)
+    }
+
+    /// Atomically lease the oldest pending tasks for a role
+    ///
+    /// Rows locked by a concurrent claim are skipped (`FOR UPDATE SKIP LOCKED`),
+    /// so several workers polling the same role never receive the same task.
+    /// Tasks whose lease expired are eligible again even before the sweeper
+    /// has returned them to pending.
+    pub async fn claim_next(
+        &self,
+        target: &str,
+        claimed_by: &str,
+        limit: i64,
+        visibility_timeout_secs: i64,
+    ) -> Result<Vec<ClaimedTask>> {
+        let tasks = sqlx::query_as::<_, ClaimedTask>(
+            r#"
+            WITH next AS (
+                SELECT id
+                FROM tasks
+                WHERE target = $1
+                  AND (status = 'pending'
+                       OR (status IN ('claimed', 'in_progress') AND lease_expires_at < NOW()))
+                ORDER BY created_at ASC
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE tasks t
+            SET status = 'claimed',
+                lease_id = gen_random_uuid(),
+                claimed_by = $2,
+                claimed_at = NOW(),
+                lease_expires_at = NOW() + make_interval(secs => $4)
+            FROM next
+            WHERE t.id = next.id
+            RETURNING t.id, t.task_type, t.description, t.data, t.source, t.target, t.status,
+                      t.context, t.trace_id, t.idempotency_key, t.created_at, t.updated_at, t.completed_at,
+                      t.lease_id, t.claimed_by, t.lease_expires_at
+            "#,
+        )
+        .bind(target)
+        .bind(claimed_by)
+        .bind(limit)
+        .bind(visibility_timeout_secs as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Extend an active lease
+    ///
+    /// Returns None if the lease token doesn't match or the lease already
+    /// expired (the task may have been handed to another worker).
+    pub async fn renew_lease(
+        &self,
+        id: Uuid,
+        lease_id: Uuid,
+        visibility_timeout_secs: i64,
+    ) -> Result<Option<ClaimedTask>> {
+        let task = sqlx::query_as::<_, ClaimedTask>(
+            r#"
+            UPDATE tasks
+            SET lease_expires_at = NOW() + make_interval(secs => $3)
+            WHERE id = $1
+              AND lease_id = $2
+              AND status IN ('claimed', 'in_progress')
+              AND lease_expires_at > NOW()
+            RETURNING id, task_type, description, data, source, target, status,
+                      context, trace_id, idempotency_key, created_at, updated_at, completed_at,
+                      lease_id, claimed_by, lease_expires_at
+            "#,
+        )
+        .bind(id)
+        .bind(lease_id)
+        .bind(visibility_timeout_secs as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Return tasks whose lease has expired to the pending queue
+    ///
+    /// Returns the number of tasks released.
+    pub async fn release_expired_leases(&self) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET status = 'pending',
+                lease_id = NULL,
+                claimed_by = NULL,
+                claimed_at = NULL,
+                lease_expires_at = NULL
+            WHERE status IN ('claimed', 'in_progress')
+              AND lease_expires_at < NOW()
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Check if a task with the given idempotency key already exists
     pub async fn find_by_idempotency_key(&self, key: Uuid) -> Result<Option<Task>> {
         let task = sqlx::query_as::<_, Task>(
@@ -134,3 +276,37 @@ impl TaskRepository {
         Ok(task)
     }
 }
+
+/// Insert a task row
+///
+/// Shared with `ApprovalRepository::create_task_with_steps`, which inserts the
+/// task and its approval chain in one transaction.
+pub(crate) async fn insert_task<'e, E>(executor: E, req: &CreateTaskRequest) -> Result<Task>
+where
+    E: PgExecutor<'e>,
+{
+    let task = sqlx::query_as::<_, Task>(
+        r#"
+        INSERT INTO tasks (
+            task_type, description, data, source, target,
+            status, context, trace_id, idempotency_key
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, task_type, description, data, source, target, status,
+                  context, trace_id, idempotency_key, created_at, updated_at, completed_at
+        "#,
+    )
+    .bind(&req.task_type)
+    .bind(&req.description)
+    .bind(req.data.as_ref().unwrap_or(&serde_json::json!({})))
+    .bind(&req.source)
+    .bind(&req.target)
+    .bind("pending")  // Default status
+    .bind(req.context.as_ref().unwrap_or(&serde_json::json!({})))
+    .bind(req.trace_id)
+    .bind(req.idempotency_key)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(task)
+}
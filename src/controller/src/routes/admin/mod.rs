@@ -2,6 +2,7 @@
 // Phase 6: Added dashboard UI and API routes
 
 pub mod profiles;
+pub mod policies;
 pub mod org;
 
 // Dashboard UI and API functions
@@ -333,6 +334,7 @@ pub async fn save_profile_from_editor(
     {
         Ok(_) => {
             info!(profile = %profile_name, "Profile saved to database");
+            crate::policy::cache::purge_after_edit(state.redis_client.as_ref(), Some(&profile_name)).await;
             Ok(Json(serde_json::json!({
                 "success": true,
                 "message": format!("Profile '{}' saved successfully", profile_name)
@@ -11,6 +11,7 @@ use utoipa::ToSchema;
 use tracing::{info, error};
 use chrono::Utc;
 use crate::AppState;
+use crate::auth::{Principal, ANONYMOUS_PRINCIPAL};
 use crate::org::csv_parser::{CsvParser, CsvError};
 
 // ============================================================================
@@ -140,6 +141,7 @@ impl From<CsvError> for OrgError {
 )]
 pub async fn import_csv(
     State(state): State<AppState>,
+    principal: Option<Principal>,
     mut multipart: Multipart,
 ) -> Result<(StatusCode, Json<ImportResponse>), OrgError> {
     info!(message = "admin.org.import.start");
@@ -176,7 +178,7 @@ pub async fn import_csv(
         "INSERT INTO org_imports (filename, uploaded_by, uploaded_at, status) VALUES ($1, $2, NOW(), 'pending') RETURNING id"
     )
     .bind(&filename)
-    .bind("admin@example.com") // TODO: Extract from JWT claims
+    .bind(principal.as_ref().map(|p| p.audit_identity()).unwrap_or(ANONYMOUS_PRINCIPAL))
     .fetch_one(pool)
     .await
     .map_err(|e| {
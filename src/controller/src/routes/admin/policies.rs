@@ -0,0 +1,388 @@
+// Phase 5 Workstream C: Admin Policy Endpoints
+// Create, update, and delete RBAC/ABAC policies. ABAC conditions are parsed
+// before they are stored so the engine never sees an invalid condition, and
+// cached decisions for the affected role are purged after every edit.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::AppState;
+use crate::auth::Principal;
+use crate::policy::Condition;
+use crate::policy::cache::purge_after_edit;
+
+/// Policy row as stored in the policies table
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct PolicyRecord {
+    pub id: i32,
+    #[schema(example = "analyst")]
+    pub role: String,
+    #[schema(example = "sql-mcp__query")]
+    pub tool_pattern: String,
+    pub allow: bool,
+    #[schema(value_type = Option<Object>)]
+    pub conditions: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// Create or replace a policy
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PolicyRequest {
+    /// Role the policy applies to
+    #[schema(example = "analyst")]
+    pub role: String,
+
+    /// Tool name or glob (e.g., "github__*")
+    #[schema(example = "sql-mcp__query")]
+    pub tool_pattern: String,
+
+    /// true = allow, false = deny
+    pub allow: bool,
+
+    /// ABAC conditions (see policy::conditions for the language)
+    #[serde(default)]
+    #[schema(value_type = Option<Object>, example = json!({"database": "analytics_*", "amount": {"lte": 10000}}))]
+    pub conditions: Option<serde_json::Value>,
+
+    /// Human-readable reason
+    pub reason: Option<String>,
+}
+
+impl PolicyRequest {
+    /// Validate the request, including the ABAC condition document
+    pub fn validate(&self) -> Result<(), AdminPolicyError> {
+        if self.role.trim().is_empty() {
+            return Err(AdminPolicyError::ValidationError("role is required".to_string()));
+        }
+        if self.tool_pattern.trim().is_empty() {
+            return Err(AdminPolicyError::ValidationError("tool_pattern is required".to_string()));
+        }
+        if let Some(conditions) = &self.conditions {
+            Condition::parse(conditions)
+                .map_err(|e| AdminPolicyError::ValidationError(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Query parameters for listing policies
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListPoliciesQuery {
+    /// Filter by role
+    pub role: Option<String>,
+}
+
+/// Custom error type for admin policy endpoints
+#[derive(Debug)]
+pub enum AdminPolicyError {
+    NotFound(String),
+    Forbidden(String),
+    ValidationError(String),
+    DatabaseError(String),
+    InternalError(String),
+}
+
+impl IntoResponse for AdminPolicyError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            AdminPolicyError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AdminPolicyError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AdminPolicyError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
+            AdminPolicyError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AdminPolicyError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+
+        let body = Json(serde_json::json!({
+            "error": message,
+            "status": status.as_u16()
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+/// Policies decide who may use which tool, so only admins may read or edit them
+fn require_admin(principal: &Principal) -> Result<(), AdminPolicyError> {
+    if principal.has_role("admin") {
+        Ok(())
+    } else {
+        Err(AdminPolicyError::Forbidden("Admin role required".to_string()))
+    }
+}
+
+/// List policies (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/policies",
+    tag = "admin",
+    params(ListPoliciesQuery),
+    responses(
+        (status = 200, description = "Policies in evaluation order", body = Vec<PolicyRecord>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden - not admin"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_policies(
+    State(state): State<AppState>,
+    principal: Principal,
+    Query(params): Query<ListPoliciesQuery>,
+) -> Result<Json<Vec<PolicyRecord>>, AdminPolicyError> {
+    require_admin(&principal)?;
+
+    let pool = state.db_pool.as_ref()
+        .ok_or_else(|| AdminPolicyError::InternalError("Database not configured".to_string()))?;
+
+    let policies = sqlx::query_as::<_, PolicyRecord>(
+        "SELECT id, role, tool_pattern, allow, conditions, reason, created_at, updated_at
+         FROM policies
+         WHERE ($1::text IS NULL OR role = $1)
+         ORDER BY role, tool_pattern DESC, id ASC",
+    )
+    .bind(&params.role)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AdminPolicyError::DatabaseError(format!("Database query failed: {}", e)))?;
+
+    Ok(Json(policies))
+}
+
+/// Create policy (admin only)
+///
+/// Rejects conditions that don't parse with 400 and the offending JSON path.
+#[utoipa::path(
+    post,
+    path = "/admin/policies",
+    tag = "admin",
+    request_body = PolicyRequest,
+    responses(
+        (status = 201, description = "Policy created", body = PolicyRecord),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden - not admin"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_policy(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(payload): Json<PolicyRequest>,
+) -> Result<(StatusCode, Json<PolicyRecord>), AdminPolicyError> {
+    info!(message = "admin.policy.create", role = %payload.role, tool_pattern = %payload.tool_pattern);
+
+    require_admin(&principal)?;
+
+    payload.validate()?;
+
+    let pool = state.db_pool.as_ref()
+        .ok_or_else(|| AdminPolicyError::InternalError("Database not configured".to_string()))?;
+
+    let policy = sqlx::query_as::<_, PolicyRecord>(
+        "INSERT INTO policies (role, tool_pattern, allow, conditions, reason)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, role, tool_pattern, allow, conditions, reason, created_at, updated_at",
+    )
+    .bind(&payload.role)
+    .bind(&payload.tool_pattern)
+    .bind(payload.allow)
+    .bind(&payload.conditions)
+    .bind(&payload.reason)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!(message = "policy.insert.error", role = %payload.role, error = %e);
+        AdminPolicyError::DatabaseError(format!("Failed to insert policy: {}", e))
+    })?;
+
+    info!(message = "admin.policy.created", id = policy.id, role = %policy.role);
+
+    purge_after_edit(state.redis_client.as_ref(), Some(&policy.role)).await;
+
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+/// Replace policy (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/policies/{id}",
+    tag = "admin",
+    params(
+        ("id" = i32, Path, description = "Policy ID")
+    ),
+    request_body = PolicyRequest,
+    responses(
+        (status = 200, description = "Policy updated", body = PolicyRecord),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden - not admin"),
+        (status = 404, description = "Policy not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_policy(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i32>,
+    Json(payload): Json<PolicyRequest>,
+) -> Result<Json<PolicyRecord>, AdminPolicyError> {
+    info!(message = "admin.policy.update", id = id);
+
+    require_admin(&principal)?;
+
+    payload.validate()?;
+
+    let pool = state.db_pool.as_ref()
+        .ok_or_else(|| AdminPolicyError::InternalError("Database not configured".to_string()))?;
+
+    let policy = sqlx::query_as::<_, PolicyRecord>(
+        "UPDATE policies
+         SET role = $1, tool_pattern = $2, allow = $3, conditions = $4, reason = $5
+         WHERE id = $6
+         RETURNING id, role, tool_pattern, allow, conditions, reason, created_at, updated_at",
+    )
+    .bind(&payload.role)
+    .bind(&payload.tool_pattern)
+    .bind(payload.allow)
+    .bind(&payload.conditions)
+    .bind(&payload.reason)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AdminPolicyError::DatabaseError(format!("Failed to update policy: {}", e)))?
+    .ok_or_else(|| AdminPolicyError::NotFound(format!("Policy not found: {}", id)))?;
+
+    info!(message = "admin.policy.updated", id = id, role = %policy.role);
+
+    // The update may have moved the policy to another role
+    purge_after_edit(state.redis_client.as_ref(), None).await;
+
+    Ok(Json(policy))
+}
+
+/// Delete policy (admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/policies/{id}",
+    tag = "admin",
+    params(
+        ("id" = i32, Path, description = "Policy ID")
+    ),
+    responses(
+        (status = 204, description = "Policy deleted"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden - not admin"),
+        (status = 404, description = "Policy not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_policy(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AdminPolicyError> {
+    info!(message = "admin.policy.delete", id = id);
+
+    require_admin(&principal)?;
+
+    let pool = state.db_pool.as_ref()
+        .ok_or_else(|| AdminPolicyError::InternalError("Database not configured".to_string()))?;
+
+    let role: String = sqlx::query_scalar("DELETE FROM policies WHERE id = $1 RETURNING role")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AdminPolicyError::DatabaseError(format!("Failed to delete policy: {}", e)))?
+        .ok_or_else(|| AdminPolicyError::NotFound(format!("Policy not found: {}", id)))?;
+
+    info!(message = "admin.policy.deleted", id = id, role = %role);
+
+    purge_after_edit(state.redis_client.as_ref(), Some(&role)).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(conditions: Option<serde_json::Value>) -> PolicyRequest {
+        PolicyRequest {
+            role: "analyst".to_string(),
+            tool_pattern: "sql-mcp__query".to_string(),
+            allow: true,
+            conditions,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_conditions() {
+        assert!(request(None).validate().is_ok());
+        assert!(request(Some(json!({
+            "database": "analytics_*",
+            "any_of": [{"amount": {"lte": 10000}}, {"approved": true}],
+            "day_of_week": ["mon", "tue", "wed", "thu", "fri"]
+        })))
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_conditions() {
+        let err = request(Some(json!({"amount": {"lte": "lots"}}))).validate().unwrap_err();
+        match err {
+            AdminPolicyError::ValidationError(msg) => assert!(msg.contains("amount.lte"), "{}", msg),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        assert!(matches!(
+            request(Some(json!(["database"]))).validate(),
+            Err(AdminPolicyError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_require_admin() {
+        let principal = |role: &str| {
+            let claims: crate::auth::Claims =
+                serde_json::from_value(json!({"sub": "user-1", "exp": 0, "role": role})).unwrap();
+            Principal::from_claims(&claims)
+        };
+        assert!(require_admin(&principal("admin")).is_ok());
+        assert!(matches!(require_admin(&principal("finance")), Err(AdminPolicyError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_validate_requires_role_and_pattern() {
+        let mut req = request(None);
+        req.tool_pattern = " ".to_string();
+        assert!(req.validate().is_err());
+
+        let mut req = request(None);
+        req.role = String::new();
+        assert!(req.validate().is_err());
+    }
+}
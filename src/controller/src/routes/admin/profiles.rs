@@ -14,6 +14,8 @@ use tracing::{info, error};
 use chrono::Utc;
 
 use crate::AppState;
+use crate::auth::{Principal, ANONYMOUS_PRINCIPAL};
+use crate::policy::cache::purge_after_edit;
 use crate::profile::schema::Profile;
 use crate::profile::validator::ProfileValidator;
 use crate::vault::transit::TransitOps;
@@ -162,6 +164,8 @@ pub async fn create_profile(
     })?;
 
     info!(message = "admin.profile.created", role = %profile.role);
+
+    purge_after_edit(state.redis_client.as_ref(), Some(&profile.role)).await;
     
     Ok((
         StatusCode::CREATED,
@@ -249,6 +253,9 @@ pub async fn update_profile(
         .map_err(|e| AdminProfileError::DatabaseError(format!("Failed to update profile: {}", e)))?;
 
     info!(message = "admin.profile.updated", role = %role);
+
+    // Cached policy decisions for the role may depend on the old profile
+    purge_after_edit(state.redis_client.as_ref(), Some(&role)).await;
     
     Ok(Json(UpdateProfileResponse {
         role: role.clone(),
@@ -279,12 +286,12 @@ pub async fn update_profile(
 )]
 pub async fn publish_profile(
     State(state): State<AppState>,
+    principal: Option<Principal>,
     Path(role): Path<String>,
 ) -> Result<Json<PublishProfileResponse>, AdminProfileError> {
     info!(message = "admin.profile.publish", role = %role);
 
     // TODO: Check admin role from JWT claims
-    // TODO: Extract email from JWT for signed_by field
 
     // Get database pool
     let pool = state.db_pool.as_ref()
@@ -358,7 +365,12 @@ pub async fn publish_profile(
         algorithm: "sha2-256".to_string(),
         vault_key: "transit/keys/profile-signing".to_string(),
         signed_at: Some(now.to_rfc3339()),
-        signed_by: Some("admin@example.com".to_string()), // TODO: Extract from JWT claims
+        signed_by: Some(
+            principal
+                .as_ref()
+                .map(|p| p.audit_identity().to_string())
+                .unwrap_or_else(|| ANONYMOUS_PRINCIPAL.to_string()),
+        ),
         signature: Some(signature.clone()),
     });
 
@@ -375,6 +387,8 @@ pub async fn publish_profile(
         .map_err(|e| AdminProfileError::DatabaseError(format!("Failed to update profile: {}", e)))?;
 
     info!(message = "admin.profile.published", role = %role, signature = %signature);
+
+    purge_after_edit(state.redis_client.as_ref(), Some(&role)).await;
     
     Ok(Json(PublishProfileResponse {
         role: role.clone(),
@@ -1,13 +1,17 @@
 use axum::{
-    extract::{State, Json},
-    http::StatusCode,
+    extract::{State, Json, Path},
+    http::{StatusCode, HeaderMap},
 };
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
-use tracing::info;
+use tracing::{info, warn, error};
 
 pub use crate::AppState;
+use crate::auth::Principal;
+use crate::models::{Approval, ApprovalStep, CreateApprovalRequest};
+use crate::repository::{ApprovalRepository, ApprovalError};
+use crate::repository::approval_repo::decision_to_task_status;
 
 /// Request to submit an approval decision
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -16,7 +20,7 @@ pub struct SubmitApprovalRequest {
     #[schema(example = "task-550e8400-e29b-41d4-a716-446655440000")]
     pub task_id: String,
     
-    /// Approval decision
+    /// Approval decision ("approved" or "rejected")
     #[schema(example = "approved")]
     pub decision: String,
     
@@ -35,48 +39,193 @@ pub struct SubmitApprovalResponse {
     /// Approval status
     #[schema(example = "accepted")]
     pub status: String,
+
+    /// Task the decision was applied to
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub task_id: String,
+
+    /// Task status after the decision
+    #[schema(example = "approved")]
+    pub task_status: String,
+}
+
+/// Approval chain and decisions recorded for a task
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApprovalChainResponse {
+    /// Task the chain belongs to
+    pub task_id: String,
+
+    /// Ordered approval steps (empty if the task needs a single decision)
+    pub steps: Vec<ApprovalStep>,
+
+    /// Decisions recorded so far (oldest first)
+    pub approvals: Vec<Approval>,
 }
 
 /// Submit an approval decision
 ///
-/// Submits an approval or rejection decision for a task. In Phase 3, approvals
-/// are ephemeral and not persisted (deferred to Phase 4).
+/// Records an approval or rejection decision for a task. For tasks with a
+/// multi-level approval chain, the decision closes the current step; an
+/// approval hands the task to the next approver and only the last approval
+/// moves the task to `approved`. A rejection at any step rejects the task.
+/// Only the org chart user assigned to the current step, or otherwise a
+/// caller holding the step's role (or the task's target role) in their JWT,
+/// may decide. Each caller decides at most once per task, and only while the
+/// task is still pending or active.
 #[utoipa::path(
     post,
     path = "/approvals",
     tag = "approvals",
     request_body = SubmitApprovalRequest,
     responses(
-        (status = 202, description = "Approval accepted", body = SubmitApprovalResponse),
-        (status = 400, description = "Bad request"),
+        (status = 202, description = "Approval recorded", body = SubmitApprovalResponse),
+        (status = 400, description = "Bad request - invalid task ID or decision"),
         (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 403, description = "Forbidden - caller may not decide on this task"),
+        (status = 404, description = "Task not found"),
+        (status = 409, description = "Task already decided"),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "Database not available"),
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
 pub async fn submit_approval(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
     Json(payload): Json<SubmitApprovalRequest>,
-) -> (StatusCode, Json<SubmitApprovalResponse>) {
-    // Generate approval ID
-    let approval_id = format!("approval-{}", Uuid::new_v4());
+) -> Result<(StatusCode, Json<SubmitApprovalResponse>), (StatusCode, String)> {
+    // Accept both bare UUIDs and the "task-<uuid>" form used in examples
+    let task_id = payload.task_id.strip_prefix("task-").unwrap_or(&payload.task_id);
+    let task_id = Uuid::parse_str(task_id).map_err(|_| {
+        warn!(message = "approval.invalid_task_id", task_id = %payload.task_id);
+        (StatusCode::BAD_REQUEST, format!("Invalid task_id: {}", payload.task_id))
+    })?;
+
+    if decision_to_task_status(&payload.decision).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid decision: {}. Supported decisions: approved, rejected", payload.decision),
+        ));
+    }
+
+    let trace_id = headers
+        .get("X-Trace-Id")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| Uuid::parse_str(s).ok());
+
+    let approver = principal.audit_identity().to_string();
+
+    // Check if database is available
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let repo = ApprovalRepository::new(pool.clone());
+
+    let create_req = CreateApprovalRequest {
+        task_id,
+        approver,
+        decision: payload.decision.clone(),
+        comments: payload.comments.clone(),
+        trace_id,
+    };
+
+    let (approval, task) = repo.record_decision(&create_req, &principal).await.map_err(|e| match e {
+        ApprovalError::TaskNotFound(_) => {
+            (StatusCode::NOT_FOUND, "Task not found".to_string())
+        }
+        ApprovalError::NotAuthorized { .. }
+        | ApprovalError::NotAssignedApprover { .. }
+        | ApprovalError::DuplicateApprover { .. } => {
+            warn!(message = "approval.forbidden", task_id = %task_id, approver = %principal.audit_identity(), reason = %e);
+            (StatusCode::FORBIDDEN, e.to_string())
+        }
+        ApprovalError::AlreadyDecided { .. } => {
+            (StatusCode::CONFLICT, e.to_string())
+        }
+        ApprovalError::InvalidDecision(_) => {
+            (StatusCode::BAD_REQUEST, e.to_string())
+        }
+        ApprovalError::DatabaseError(err) => {
+            error!(message = "approval.record.error", error = %err, task_id = %task_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", err))
+        }
+    })?;
 
     // Emit audit event
     info!(
         message = "approval.submitted",
-        approval_id = %approval_id,
-        task_id = %payload.task_id,
-        decision = %payload.decision,
-        has_comments = payload.comments.is_some()
+        approval_id = %approval.id,
+        task_id = %task.id,
+        approver = %approval.approver,
+        approver_role = %approval.approver_role,
+        decision = %approval.decision,
+        task_status = %task.status,
+        has_comments = approval.comments.is_some()
     );
 
     let response = SubmitApprovalResponse {
-        approval_id,
+        approval_id: format!("approval-{}", approval.id),
         status: "accepted".to_string(),
+        task_id: task.id.to_string(),
+        task_status: task.status,
     };
 
-    (StatusCode::ACCEPTED, Json(response))
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// Get the approval chain for a task
+///
+/// Returns the task's ordered approval steps together with the decisions
+/// recorded against it.
+#[utoipa::path(
+    get,
+    path = "/approvals/{task_id}",
+    tag = "approvals",
+    params(
+        ("task_id" = Uuid, Path, description = "Task ID")
+    ),
+    responses(
+        (status = 200, description = "Approval chain", body = ApprovalChainResponse),
+        (status = 400, description = "Bad request - invalid task ID"),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "Database not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_approval_chain(
+    State(state): State<AppState>,
+    Path(task_id): Path<String>,
+) -> Result<Json<ApprovalChainResponse>, (StatusCode, String)> {
+    let parsed = task_id.strip_prefix("task-").unwrap_or(&task_id);
+    let parsed = Uuid::parse_str(parsed)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid task_id: {}", task_id)))?;
+
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let repo = ApprovalRepository::new(pool.clone());
+
+    let steps = repo.list_steps(parsed).await.map_err(|e| {
+        error!(message = "approval.steps.error", error = %e, task_id = %parsed);
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
+    })?;
+
+    let approvals = repo.list_by_task(parsed).await.map_err(|e| {
+        error!(message = "approval.list.error", error = %e, task_id = %parsed);
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
+    })?;
+
+    Ok(Json(ApprovalChainResponse {
+        task_id: parsed.to_string(),
+        steps,
+        approvals,
+    }))
 }
 
 #[cfg(test)]
@@ -0,0 +1,171 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+use utoipa::IntoParams;
+
+pub use crate::AppState;
+use crate::auth::Principal;
+use crate::events::TaskEvent;
+
+/// Query parameters for the event stream
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StreamEventsQuery {
+    /// Role to receive events for (tasks routed to or created by this role);
+    /// must be one the caller holds (defaults to the caller's org role)
+    pub role: Option<String>,
+}
+
+/// Stream task and approval events
+///
+/// Server-Sent Events stream of `task_created`, `task_updated` and
+/// `approval_decided` events for a role, so agents don't have to poll
+/// GET /tasks. Events are published by the database and fanned out across
+/// controller replicas via Postgres LISTEN/NOTIFY. A `lagged` event means the
+/// subscriber fell behind and should resync via GET /tasks.
+#[utoipa::path(
+    get,
+    path = "/events/stream",
+    tag = "tasks",
+    params(StreamEventsQuery),
+    responses(
+        (status = 200, description = "Event stream (text/event-stream)", body = TaskEvent),
+        (status = 400, description = "Bad request - no role given and none on the caller"),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 403, description = "Forbidden - caller does not hold the role"),
+        (status = 503, description = "Event bus not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    principal: Principal,
+    Query(params): Query<StreamEventsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let role = subscription_role(&principal, params.role)?;
+
+    let bus = state.event_bus.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Event bus not configured".to_string()))?;
+
+    info!(message = "events.subscribe", role = %role, principal = %principal.audit_identity());
+
+    let stream = role_stream(bus.subscribe(), role);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Resolve the role to stream events for
+///
+/// Task payloads are only visible to their roles, so callers may subscribe
+/// to a role they hold and default to their own org role.
+fn subscription_role(principal: &Principal, requested: Option<String>) -> Result<String, (StatusCode, String)> {
+    let role = requested
+        .filter(|role| !role.trim().is_empty())
+        .or_else(|| principal.role.clone())
+        .ok_or((StatusCode::BAD_REQUEST, "role is required".to_string()))?;
+
+    if !principal.has_role(&role) {
+        warn!(message = "events.subscribe.forbidden", role = %role, principal = %principal.audit_identity());
+        return Err((StatusCode::FORBIDDEN, format!("Role '{}' required", role)));
+    }
+
+    Ok(role)
+}
+
+/// Turn a broadcast receiver into an SSE stream filtered to one role
+fn role_stream(
+    rx: broadcast::Receiver<TaskEvent>,
+    role: String,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold((rx, role), |(mut rx, role)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if event.is_visible_to(&role) => {
+                    let sse = Event::default()
+                        .event(event.event.clone())
+                        .id(event.task_id.to_string())
+                        .json_data(&event)
+                        .unwrap_or_else(|_| Event::default().event("error"));
+                    return Some((Ok(sse), (rx, role)));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(message = "events.subscriber.lagged", role = %role, skipped = skipped);
+                    let sse = Event::default()
+                        .event("lagged")
+                        .data(skipped.to_string());
+                    return Some((Ok(sse), (rx, role)));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use futures_util::StreamExt;
+
+    fn event(source: &str, target: &str) -> TaskEvent {
+        TaskEvent {
+            event: "task_created".to_string(),
+            task_id: uuid::Uuid::new_v4(),
+            task_type: "budget_approval".to_string(),
+            source: source.to_string(),
+            target: target.to_string(),
+            status: Some("pending".to_string()),
+            decision: None,
+            approver_role: None,
+            occurred_at: chrono::Utc::now(),
+        }
+    }
+
+    fn principal(role: Option<&str>) -> Principal {
+        Principal {
+            subject: "user-1".to_string(),
+            email: None,
+            username: None,
+            role: role.map(str::to_string),
+            roles: vec![],
+            groups: vec!["legal".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_subscription_role_must_be_held() {
+        let manager = principal(Some("manager"));
+        assert_eq!(subscription_role(&manager, None).unwrap(), "manager");
+        assert_eq!(subscription_role(&manager, Some("legal".to_string())).unwrap(), "legal");
+        assert_eq!(
+            subscription_role(&manager, Some("finance".to_string())).unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(subscription_role(&principal(None), None).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_role_stream_filters_other_roles() {
+        let bus = EventBus::new(16);
+        let stream = role_stream(bus.subscribe(), "manager".to_string());
+        futures_util::pin_mut!(stream);
+
+        bus.dispatch(event("finance", "legal"));
+        bus.dispatch(event("finance", "manager"));
+
+        // Only the manager event comes through
+        assert!(stream.next().await.is_some());
+        drop(bus);
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -3,5 +3,7 @@ pub mod sessions;
 pub mod approvals;
 pub mod profiles;
 pub mod privacy;
+pub mod events;
+pub mod policy;
 
 pub mod admin;
@@ -0,0 +1,356 @@
+use axum::{
+    extract::{Query, State, Json},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use tracing::{info, error};
+
+pub use crate::AppState;
+use crate::auth::Principal;
+use crate::policy::{
+    PolicyAuditLog, PolicyCandidate, PolicyContext, PolicyDecisionFilter, PolicyDecisionRecord,
+    PolicyEngine,
+};
+
+/// Default page size for GET /policy/decisions
+const DEFAULT_DECISIONS_LIMIT: i64 = 100;
+
+/// Largest page GET /policy/decisions returns
+const MAX_DECISIONS_LIMIT: i64 = 1000;
+
+/// Roles that may read the decision log and evaluate other roles' policies
+const POLICY_REVIEWER_ROLES: [&str; 2] = ["admin", "compliance"];
+
+/// Request to evaluate a tool-use policy
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EvaluatePolicyRequest {
+    /// Role to evaluate for (defaults to the caller's role from the JWT;
+    /// roles the caller doesn't hold need an admin or compliance role)
+    #[schema(example = "finance")]
+    pub role: Option<String>,
+
+    /// Tool name (e.g., "developer__shell", "sql-mcp__query")
+    #[schema(example = "sql-mcp__query")]
+    pub tool: String,
+
+    /// ABAC context (database, file_path, extra attributes)
+    #[serde(default)]
+    pub context: PolicyContext,
+
+    /// Return every candidate policy and why it did or didn't match
+    /// (bypasses the policy cache)
+    #[serde(default)]
+    pub explain: bool,
+}
+
+/// Policy evaluation result
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EvaluatePolicyResponse {
+    /// Whether the role may call the tool in this context
+    pub allowed: bool,
+
+    /// Role the policy was evaluated for
+    #[schema(example = "finance")]
+    pub role: String,
+
+    /// Tool evaluated
+    #[schema(example = "sql-mcp__query")]
+    pub tool: String,
+
+    /// Why access was denied (absent when allowed)
+    pub reason: Option<String>,
+
+    /// Policy that decided the request (absent = default deny)
+    pub matched_policy_id: Option<i32>,
+
+    /// Whether the decision was served from the policy cache
+    pub cache_hit: bool,
+
+    /// Policy version the decision was made under
+    pub policy_version: i64,
+
+    /// Candidate policies in evaluation order (explain mode only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidates: Option<Vec<PolicyCandidate>>,
+}
+
+/// Query parameters for the policy decision audit log
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListPolicyDecisionsQuery {
+    /// Filter by role
+    pub role: Option<String>,
+
+    /// Filter by tool
+    pub tool: Option<String>,
+
+    /// Filter by outcome (false = denials only)
+    pub allowed: Option<bool>,
+
+    /// Filter by caller subject
+    pub subject: Option<String>,
+
+    /// Only decisions at or after this time (RFC 3339)
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only decisions before this time (RFC 3339)
+    pub until: Option<DateTime<Utc>>,
+
+    /// Maximum number of decisions to return (default: 100, max: 1000)
+    pub limit: Option<i64>,
+}
+
+/// Policy decision audit log page
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListPolicyDecisionsResponse {
+    /// Decisions, newest first
+    pub decisions: Vec<PolicyDecisionRecord>,
+}
+
+/// Evaluate a tool-use policy
+///
+/// Answers "may role X call tool Y with context Z" without executing
+/// anything, using the same RBAC/ABAC engine that guards tool invocations.
+/// goose clients and the agent-mesh MCP server call this before running a
+/// tool. A denial is a normal 200 response with `allowed: false`.
+///
+/// With `explain: true` the response lists every policy for the role in
+/// evaluation order and why each did or didn't match. Evaluations are what-if
+/// dry runs and are not recorded in the policy decision audit log, which only
+/// holds enforced decisions. Evaluating a role the caller doesn't hold
+/// requires an admin or compliance role.
+#[utoipa::path(
+    post,
+    path = "/policy/evaluate",
+    tag = "policy",
+    request_body = EvaluatePolicyRequest,
+    responses(
+        (status = 200, description = "Policy evaluated", body = EvaluatePolicyResponse),
+        (status = 400, description = "Bad request - missing tool or role"),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 403, description = "Forbidden - evaluating another role requires admin or compliance"),
+        (status = 500, description = "Policy evaluation error"),
+        (status = 503, description = "Database not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn evaluate_policy(
+    State(state): State<AppState>,
+    principal: Option<Principal>,
+    Json(payload): Json<EvaluatePolicyRequest>,
+) -> Result<(StatusCode, Json<EvaluatePolicyResponse>), (StatusCode, String)> {
+    if payload.tool.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "tool is required".to_string()));
+    }
+
+    let role = payload
+        .role
+        .clone()
+        .filter(|r| !r.trim().is_empty())
+        .or_else(|| principal.as_ref().and_then(|p| p.role.clone()))
+        .ok_or((StatusCode::BAD_REQUEST, "role is required (no role in token)".to_string()))?;
+
+    let may_evaluate = principal
+        .as_ref()
+        .is_some_and(|p| p.has_role(&role) || p.has_any_role(&POLICY_REVIEWER_ROLES));
+    if !may_evaluate {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("Evaluating policies for role '{}' requires that role or an admin/compliance role", role),
+        ));
+    }
+
+    let subject = principal.map(|p| p.subject);
+
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let version = state.policy_version.as_ref().map_or(0, |v| v.get());
+
+    let engine = PolicyEngine::new(pool.clone(), state.redis_client.clone())
+        .with_subject(subject)
+        .with_version(version)
+        .with_audit(false);
+
+    let decision = engine
+        .evaluate(&role, &payload.tool, &payload.context, payload.explain)
+        .await
+        .map_err(|e| {
+            error!(message = "policy.evaluate.error", error = %e, role = %role, tool = %payload.tool);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Policy evaluation error: {}", e))
+        })?;
+
+    info!(
+        message = "policy.evaluated",
+        role = %role,
+        tool = %payload.tool,
+        allowed = decision.allowed,
+        policy_id = ?decision.matched_policy_id,
+        cache_hit = decision.cache_hit
+    );
+
+    Ok((StatusCode::OK, Json(EvaluatePolicyResponse {
+        allowed: decision.allowed,
+        role,
+        tool: payload.tool,
+        reason: decision.reason,
+        matched_policy_id: decision.matched_policy_id,
+        cache_hit: decision.cache_hit,
+        policy_version: decision.policy_version,
+        candidates: payload.explain.then_some(decision.candidates),
+    })))
+}
+
+/// Query the policy decision audit log
+///
+/// Returns persisted tool-use decisions, newest first, so reviewers can see
+/// who was denied what, when, and by which policy. Requires an admin or
+/// compliance role.
+#[utoipa::path(
+    get,
+    path = "/policy/decisions",
+    tag = "policy",
+    params(ListPolicyDecisionsQuery),
+    responses(
+        (status = 200, description = "Policy decisions", body = ListPolicyDecisionsResponse),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 403, description = "Forbidden - admin or compliance role required"),
+        (status = 500, description = "Database error"),
+        (status = 503, description = "Database not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_policy_decisions(
+    State(state): State<AppState>,
+    principal: Principal,
+    Query(params): Query<ListPolicyDecisionsQuery>,
+) -> Result<(StatusCode, Json<ListPolicyDecisionsResponse>), (StatusCode, String)> {
+    if !principal.has_any_role(&POLICY_REVIEWER_ROLES) {
+        return Err((StatusCode::FORBIDDEN, "Admin or compliance role required".to_string()));
+    }
+
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let filter = PolicyDecisionFilter {
+        role: params.role,
+        tool: params.tool,
+        allowed: params.allowed,
+        subject: params.subject,
+        since: params.since,
+        until: params.until,
+        limit: params.limit.unwrap_or(DEFAULT_DECISIONS_LIMIT).clamp(1, MAX_DECISIONS_LIMIT),
+    };
+
+    let decisions = PolicyAuditLog::new(pool.clone())
+        .list(&filter)
+        .await
+        .map_err(|e| {
+            error!(message = "policy.decisions.list.error", error = %e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
+        })?;
+
+    Ok((StatusCode::OK, Json(ListPolicyDecisionsResponse { decisions })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, header},
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use crate::auth::Claims;
+    use crate::guard_client::GuardClient;
+
+    fn claims(role: &str) -> Claims {
+        serde_json::from_value(json!({"sub": "user-1", "exp": 0, "role": role})).unwrap()
+    }
+
+    fn create_test_app() -> axum::Router {
+        let app_state = AppState::new(Arc::new(GuardClient::from_env()), None);
+        axum::Router::new()
+            .route("/policy/evaluate", axum::routing::post(evaluate_policy))
+            .route("/policy/decisions", axum::routing::get(list_policy_decisions))
+            .with_state(app_state)
+    }
+
+    async fn post(payload: serde_json::Value, caller_role: Option<&str>) -> StatusCode {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/policy/evaluate")
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(role) = caller_role {
+            request = request.extension(claims(role));
+        }
+        create_test_app()
+            .oneshot(request.body(Body::from(payload.to_string())).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    async fn list_decisions(caller_role: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/policy/decisions?role=finance&allowed=false");
+        if let Some(role) = caller_role {
+            request = request.extension(claims(role));
+        }
+        create_test_app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_without_database() {
+        let status = post(json!({
+            "role": "finance",
+            "tool": "sql-mcp__query",
+            "context": {"database": "analytics_prod"}
+        }), Some("finance"))
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_other_role_requires_reviewer() {
+        let payload = json!({"role": "legal", "tool": "developer__shell"});
+        assert_eq!(post(payload.clone(), Some("finance")).await, StatusCode::FORBIDDEN);
+        assert_eq!(post(payload.clone(), None).await, StatusCode::FORBIDDEN);
+        assert_eq!(post(payload.clone(), Some("compliance")).await, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(post(payload, Some("admin")).await, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_requires_role() {
+        // No role in body and no JWT principal
+        let status = post(json!({"tool": "developer__shell"}), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_requires_tool() {
+        let status = post(json!({"role": "finance", "tool": ""}), Some("finance")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_list_decisions_without_database() {
+        assert_eq!(list_decisions(Some("compliance")).await, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_list_decisions_requires_reviewer() {
+        assert_eq!(list_decisions(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(list_decisions(Some("finance")).await, StatusCode::FORBIDDEN);
+    }
+}
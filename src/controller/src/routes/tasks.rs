@@ -10,8 +10,21 @@ use std::sync::Arc;
 
 // Import AppState and repositories
 pub use crate::AppState;
-use crate::repository::TaskRepository;
-use crate::models::{Task, CreateTaskRequest};
+use crate::approval::ApprovalChainEngine;
+use crate::repository::{ApprovalRepository, TaskRepository};
+use crate::auth::{Principal, ANONYMOUS_PRINCIPAL};
+use crate::models::{Task, TaskStatus, ClaimedTask, CreateTaskRequest};
+use crate::lifecycle::{TaskLifecycle, TaskTransitionError};
+
+/// Default lease duration for claimed tasks (seconds)
+pub const DEFAULT_VISIBILITY_TIMEOUT_SECS: i64 = 300;
+
+/// Bounds for caller-supplied visibility timeouts (seconds)
+const MIN_VISIBILITY_TIMEOUT_SECS: i64 = 10;
+const MAX_VISIBILITY_TIMEOUT_SECS: i64 = 3600;
+
+/// Maximum number of tasks a single claim may lease
+const MAX_CLAIM_BATCH: i64 = 50;
 
 /// Task payload for routing
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -81,6 +94,7 @@ pub struct RouteTaskResponse {
 )]
 pub async fn route_task(
     State(state): State<AppState>,
+    principal: Option<Principal>,
     headers: HeaderMap,
     Json(mut payload): Json<RouteTaskRequest>,
 ) -> Result<(StatusCode, Json<RouteTaskResponse>), StatusCode> {
@@ -206,9 +220,11 @@ pub async fn route_task(
         );
     }
 
-    // Extract source from JWT claims (or use "unknown" for now)
-    // TODO: Extract from JWT once auth middleware is enabled
-    let source = "unknown".to_string();
+    // Source is the caller's org role; "unknown" without a role-bearing JWT (dev mode)
+    let source = principal
+        .as_ref()
+        .and_then(|p| p.role.clone())
+        .unwrap_or_else(|| "unknown".to_string());
 
     // Create task in database
     let create_req = CreateTaskRequest {
@@ -222,10 +238,36 @@ pub async fn route_task(
         idempotency_key: Some(idempotency_uuid),
     };
 
-    let task = task_repo.create(&create_req).await.map_err(|e| {
-        error!(message = "failed to create task", error = %e, trace_id = %trace_id);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    // Plan the approval chain for the authenticated requester before creating
+    // the task so a bad rule set doesn't leave behind a task nobody is allowed
+    // to approve
+    let requester_email = principal.as_ref().and_then(|p| p.email.as_deref());
+    let steps = ApprovalChainEngine::new(db_pool.clone())
+        .plan(&create_req, requester_email)
+        .await
+        .map_err(|e| {
+            error!(message = "failed to plan approval chain", error = %e, trace_id = %trace_id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // The task and its approval chain are created together, so a failure
+    // cannot leave a task its original target could decide alone
+    let task = ApprovalRepository::new(db_pool.clone())
+        .create_task_with_steps(&create_req, &steps)
+        .await
+        .map_err(|e| {
+            error!(message = "failed to create task", error = %e, trace_id = %trace_id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !steps.is_empty() {
+        info!(
+            message = "approval.chain.created",
+            task_id = %task.id,
+            steps = steps.len(),
+            first_approver = %task.target
+        );
+    }
 
     // Emit audit event
     info!(
@@ -233,6 +275,8 @@ pub async fn route_task(
         task_id = %task.id,
         target = %task.target,
         task_type = %task.task_type,
+        source = %task.source,
+        created_by = %principal.as_ref().map(|p| p.audit_identity()).unwrap_or(ANONYMOUS_PRINCIPAL),
         trace_id = %trace_id,
         idempotency_key = %idempotency_key,
         has_context = payload.context.is_some()
@@ -247,6 +291,313 @@ pub async fn route_task(
     Ok((StatusCode::ACCEPTED, Json(response)))
 }
 
+/// Task lifecycle event request
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TaskEventRequest {
+    /// Event to trigger (start, complete, fail, cancel, release)
+    #[schema(example = "start")]
+    pub event: String,
+
+    /// Optional reason (e.g., why the task failed or was cancelled)
+    #[schema(example = "Upstream system unavailable")]
+    pub reason: Option<String>,
+
+    /// Lease token from POST /tasks/claim (required to start, complete, fail,
+    /// cancel or release a leased task)
+    pub lease_id: Option<Uuid>,
+}
+
+/// Handle task lifecycle event
+///
+/// Triggers a lifecycle event on a task using the TaskLifecycle FSM.
+/// Supported events: start, complete, fail, cancel, release; tasks are
+/// claimed through POST /tasks/claim, which issues the lease. Only
+/// callers holding the task's target role (or admins) may act on it. For a
+/// task leased via POST /tasks/claim, start/complete/fail/cancel/release
+/// require the worker's unexpired `lease_id`.
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}/events",
+    tag = "tasks",
+    params(
+        ("id" = Uuid, Path, description = "Task ID")
+    ),
+    request_body = TaskEventRequest,
+    responses(
+        (status = 200, description = "Event processed", body = Task),
+        (status = 400, description = "Invalid event or transition"),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 403, description = "Forbidden - caller does not hold the task's target role"),
+        (status = 404, description = "Task not found"),
+        (status = 409, description = "Task was modified concurrently, awaits approval, or its lease is not held"),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "Database not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn handle_task_event(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TaskEventRequest>,
+) -> Result<(StatusCode, Json<Task>), (StatusCode, String)> {
+    let new_status = event_status(&payload.event).ok_or_else(|| (
+        StatusCode::BAD_REQUEST,
+        format!("Invalid event: {}. Supported events: start, complete, fail, cancel, release", payload.event)
+    ))?;
+
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let task = TaskRepository::new(pool.clone())
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!(message = "task.event.error", error = %e, task_id = %id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Task not found".to_string()))?;
+
+    if !may_handle(&principal, &task.target) {
+        warn!(
+            message = "task.event.forbidden",
+            task_id = %id,
+            target = %task.target,
+            principal = %principal.audit_identity()
+        );
+        return Err((StatusCode::FORBIDDEN, format!("Role '{}' required", task.target)));
+    }
+
+    let result = TaskLifecycle::new(pool.clone())
+        .transition(id, new_status, payload.lease_id)
+        .await;
+
+    let task = result.map_err(|e| match e {
+        TaskTransitionError::TaskNotFound(_) => {
+            (StatusCode::NOT_FOUND, "Task not found".to_string())
+        }
+        TaskTransitionError::InvalidTransition { .. } => {
+            (StatusCode::BAD_REQUEST, e.to_string())
+        }
+        TaskTransitionError::AwaitingApproval(_)
+        | TaskTransitionError::LeaseNotHeld(_)
+        | TaskTransitionError::Conflict(_) => {
+            (StatusCode::CONFLICT, e.to_string())
+        }
+        TaskTransitionError::DatabaseError(msg) => {
+            error!(message = "task.event.error", error = %msg, task_id = %id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", msg))
+        }
+    })?;
+
+    info!(
+        message = "task.event.processed",
+        task_id = %id,
+        event = %payload.event,
+        new_status = %task.status,
+        reason = ?payload.reason
+    );
+
+    Ok((StatusCode::OK, Json(task)))
+}
+
+/// Status a task event moves the task to
+///
+/// There is no claim event: a task is only claimed through POST /tasks/claim,
+/// which issues the lease the other events are checked against.
+pub fn event_status(event: &str) -> Option<TaskStatus> {
+    match event.to_lowercase().as_str() {
+        "start" => Some(TaskStatus::InProgress),
+        "complete" => Some(TaskStatus::Completed),
+        "fail" => Some(TaskStatus::Failed),
+        "cancel" => Some(TaskStatus::Cancelled),
+        "release" => Some(TaskStatus::Pending),
+        _ => None,
+    }
+}
+
+/// Whether the caller may act on tasks routed to `target` (its role, or admin)
+fn may_handle(principal: &Principal, target: &str) -> bool {
+    principal.has_role(target) || principal.has_role("admin")
+}
+
+/// Request to claim the next pending task(s) for a role
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ClaimTasksRequest {
+    /// Role whose queue to claim from
+    #[schema(example = "finance")]
+    pub target: String,
+
+    /// Maximum number of tasks to lease (default: 1, max: 50)
+    #[schema(example = 1)]
+    pub max_tasks: Option<i64>,
+
+    /// Lease duration in seconds (default: 300, range: 10-3600)
+    #[schema(example = 300)]
+    pub visibility_timeout_secs: Option<i64>,
+
+    /// Worker label for logs (the lease holder is always the caller's identity)
+    #[schema(example = "finance-agent-1")]
+    pub worker_id: Option<String>,
+}
+
+/// Response for task claiming
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ClaimTasksResponse {
+    /// Tasks leased to the caller (empty if the queue is empty)
+    pub tasks: Vec<ClaimedTask>,
+}
+
+/// Request to renew a task lease
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RenewLeaseRequest {
+    /// Lease token returned by POST /tasks/claim
+    pub lease_id: Uuid,
+
+    /// New lease duration in seconds from now (default: 300, range: 10-3600)
+    #[schema(example = 300)]
+    pub visibility_timeout_secs: Option<i64>,
+}
+
+/// Clamp a requested visibility timeout to the supported range
+pub fn visibility_timeout(requested: Option<i64>) -> i64 {
+    requested
+        .unwrap_or(DEFAULT_VISIBILITY_TIMEOUT_SECS)
+        .clamp(MIN_VISIBILITY_TIMEOUT_SECS, MAX_VISIBILITY_TIMEOUT_SECS)
+}
+
+/// Clamp a requested claim batch size to the supported range
+pub fn claim_batch_size(requested: Option<i64>) -> i64 {
+    requested.unwrap_or(1).clamp(1, MAX_CLAIM_BATCH)
+}
+
+/// Claim pending tasks for a role
+///
+/// Atomically leases the oldest pending task(s) routed to a role. Workers of
+/// the same role polling concurrently never receive the same task. A lease
+/// that is not renewed (PUT /tasks/{id}/lease) or finished via
+/// PUT /tasks/{id}/events before its visibility timeout returns the task to
+/// the queue.
+#[utoipa::path(
+    post,
+    path = "/tasks/claim",
+    tag = "tasks",
+    request_body = ClaimTasksRequest,
+    responses(
+        (status = 200, description = "Tasks claimed (possibly none)", body = ClaimTasksResponse),
+        (status = 400, description = "Bad request - missing target"),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 403, description = "Forbidden - caller does not hold the target role"),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "Database not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn claim_tasks(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(payload): Json<ClaimTasksRequest>,
+) -> Result<(StatusCode, Json<ClaimTasksResponse>), (StatusCode, String)> {
+    if payload.target.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "target is required".to_string()));
+    }
+
+    if !principal.has_role(&payload.target) {
+        warn!(
+            message = "task.claim.forbidden",
+            target = %payload.target,
+            principal = %principal.audit_identity()
+        );
+        return Err((StatusCode::FORBIDDEN, format!("Role '{}' required", payload.target)));
+    }
+
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let claimed_by = principal.audit_identity();
+    let limit = claim_batch_size(payload.max_tasks);
+    let timeout_secs = visibility_timeout(payload.visibility_timeout_secs);
+
+    let task_repo = TaskRepository::new(pool.clone());
+    let tasks = task_repo
+        .claim_next(&payload.target, claimed_by, limit, timeout_secs)
+        .await
+        .map_err(|e| {
+            error!(message = "task.claim.error", error = %e, target = %payload.target);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
+        })?;
+
+    info!(
+        message = "task.claimed",
+        target = %payload.target,
+        claimed_by = %claimed_by,
+        worker_id = ?payload.worker_id,
+        count = tasks.len(),
+        visibility_timeout_secs = timeout_secs
+    );
+
+    Ok((StatusCode::OK, Json(ClaimTasksResponse { tasks })))
+}
+
+/// Renew a task lease
+///
+/// Extends the visibility timeout of a claimed or in-progress task so
+/// long-running work isn't handed to another worker.
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}/lease",
+    tag = "tasks",
+    params(
+        ("id" = Uuid, Path, description = "Task ID")
+    ),
+    request_body = RenewLeaseRequest,
+    responses(
+        (status = 200, description = "Lease renewed", body = ClaimedTask),
+        (status = 401, description = "Unauthorized - missing or invalid JWT"),
+        (status = 409, description = "Lease expired or held by another worker"),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "Database not available"),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn renew_task_lease(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RenewLeaseRequest>,
+) -> Result<(StatusCode, Json<ClaimedTask>), (StatusCode, String)> {
+    let pool = state.db_pool.as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "Database not configured".to_string()))?;
+
+    let timeout_secs = visibility_timeout(payload.visibility_timeout_secs);
+
+    let task_repo = TaskRepository::new(pool.clone());
+    let task = task_repo
+        .renew_lease(id, payload.lease_id, timeout_secs)
+        .await
+        .map_err(|e| {
+            error!(message = "task.lease.error", error = %e, task_id = %id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
+        })?
+        .ok_or_else(|| {
+            warn!(message = "task.lease.lost", task_id = %id, lease_id = %payload.lease_id);
+            (StatusCode::CONFLICT, "Lease expired or held by another worker".to_string())
+        })?;
+
+    info!(
+        message = "task.lease.renewed",
+        task_id = %id,
+        lease_expires_at = %task.lease_expires_at
+    );
+
+    Ok((StatusCode::OK, Json(task)))
+}
+
 /// Get a task by ID
 ///
 /// Retrieves a task by its unique identifier. Used by fetch_status tool.
@@ -1,20 +1,43 @@
 #[cfg(test)]
 mod tests {
-    use crate::routes::tasks::{route_task, RouteTaskRequest, RouteTaskResponse};
+    use crate::routes::tasks::{
+        claim_batch_size, claim_tasks, event_status, handle_task_event, may_handle, route_task, visibility_timeout,
+        RouteTaskRequest, RouteTaskResponse, DEFAULT_VISIBILITY_TIMEOUT_SECS,
+    };
     use axum::{
         body::Body,
         http::{Request, StatusCode, header},
     };
     use serde_json::json;
     use tower::ServiceExt; // for `oneshot`
-    use crate::{AppState, guard_client::GuardClient};
+    use crate::{AppState, auth::{Claims, Principal}, guard_client::GuardClient, models::TaskStatus};
     use std::sync::Arc;
 
+    /// Verified claims as the JWT middleware would attach them
+    fn worker_claims(role: &str) -> Claims {
+        Claims {
+            sub: "worker-1".to_string(),
+            exp: usize::MAX,
+            iat: None,
+            nbf: None,
+            iss: None,
+            aud: None,
+            email: Some(format!("{}-agent@example.com", role)),
+            preferred_username: None,
+            role: Some(role.to_string()),
+            department: None,
+            groups: vec![],
+            realm_access: None,
+        }
+    }
+
     fn create_test_app() -> axum::Router {
         let guard_client = Arc::new(GuardClient::from_env());
         let app_state = AppState::new(guard_client, None);
         axum::Router::new()
             .route("/tasks/route", axum::routing::post(route_task))
+            .route("/tasks/:id/events", axum::routing::put(handle_task_event))
+            .route("/tasks/claim", axum::routing::post(claim_tasks))
             .with_state(app_state)
     }
 
@@ -177,4 +200,164 @@ mod tests {
         // Axum with tower-http returns 400 for JSON deserialization errors
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[tokio::test]
+    async fn test_task_event_without_database() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/tasks/550e8400-e29b-41d4-a716-446655440000/events")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .extension(worker_claims("finance"))
+                    .body(Body::from(json!({"event": "start"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_task_event_requires_authentication() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/tasks/550e8400-e29b-41d4-a716-446655440000/events")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({"event": "cancel"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_task_events_are_limited_to_the_target_role() {
+        let finance = Principal::from_claims(&worker_claims("finance"));
+        assert!(may_handle(&finance, "finance"));
+        assert!(!may_handle(&finance, "legal"));
+        assert!(may_handle(&Principal::from_claims(&worker_claims("admin")), "legal"));
+    }
+
+    #[tokio::test]
+    async fn test_task_event_invalid_task_id() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/tasks/not-a-uuid/events")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .extension(worker_claims("finance"))
+                    .body(Body::from(json!({"event": "start"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_claim_tasks_without_database() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/tasks/claim")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .extension(worker_claims("finance"))
+                    .body(Body::from(json!({"target": "finance", "max_tasks": 5}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_claim_tasks_empty_target() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/tasks/claim")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .extension(worker_claims("finance"))
+                    .body(Body::from(json!({"target": " "}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_task_event_claim_is_not_supported() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/tasks/550e8400-e29b-41d4-a716-446655440000/events")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .extension(worker_claims("finance"))
+                    .body(Body::from(json!({"event": "claim"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Tasks are only claimed (and leased) through POST /tasks/claim
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(event_status("claim"), None);
+        assert_eq!(event_status("Cancel"), Some(TaskStatus::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_claim_tasks_requires_target_role() {
+        let app = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/tasks/claim")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .extension(worker_claims("legal"))
+                    .body(Body::from(json!({"target": "finance", "worker_id": "finance-agent-1"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_claim_limits_are_clamped() {
+        assert_eq!(visibility_timeout(None), DEFAULT_VISIBILITY_TIMEOUT_SECS);
+        assert_eq!(visibility_timeout(Some(1)), 10);
+        assert_eq!(visibility_timeout(Some(86400)), 3600);
+        assert_eq!(claim_batch_size(None), 1);
+        assert_eq!(claim_batch_size(Some(0)), 1);
+        assert_eq!(claim_batch_size(Some(500)), 50);
+    }
 }
@@ -0,0 +1,162 @@
+// Privacy Guard wire contract
+//
+// Request/response bodies exchanged between privacy-guard and its clients.
+// Both privacy-guard and privacy-guard-proxy include this file with
+// #[path = "../../guard-contract/mod.rs"], so a field renamed on one side
+// fails to compile on the other instead of silently breaking at runtime.
+
+// Each includer uses only part of the contract
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Mask endpoint path
+pub const MASK_PATH: &str = "/guard/mask";
+
+/// Reidentify endpoint path
+pub const REIDENTIFY_PATH: &str = "/guard/reidentify";
+
+/// Session flush endpoint path
+pub const FLUSH_SESSION_PATH: &str = "/internal/flush-session";
+
+/// Length of the hash part of a pseudonym: {TYPE}_{16 hex chars}
+pub const PSEUDONYM_HASH_LEN: usize = 16;
+
+/// Longest allowed name of a custom entity type
+pub const MAX_ENTITY_TYPE_LEN: usize = 64;
+
+/// Whether `name` can be used as an entity type (and so as a pseudonym prefix)
+///
+/// Privacy Guard only accepts entity types passing this check, and clients
+/// recognise (partial) pseudonyms with it, so the two never drift apart.
+pub fn is_entity_type_name(name: &str) -> bool {
+    name.len() <= MAX_ENTITY_TYPE_LEN
+        && name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_uppercase() || c == '_')
+}
+
+/// Verify if a pseudonym is valid format
+pub fn is_valid_pseudonym(pseudonym: &str) -> bool {
+    // Format: {TYPE}_{16_hex_chars}
+    // Find the last underscore to separate type from hash
+    match pseudonym.rfind('_') {
+        Some(pos) => {
+            let hash_part = &pseudonym[pos + 1..];
+            hash_part.len() == PSEUDONYM_HASH_LEN && hash_part.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// Length of the longest suffix of `text` that could be the start of a pseudonym
+///
+/// A text cut at an arbitrary point (e.g. a streamed chunk) may end in the
+/// middle of a pseudonym such as "PERSON_a3f7". Holding this suffix back until
+/// more text arrives lets the whole pseudonym be restored at once. Complete
+/// pseudonyms are not counted.
+pub fn pseudonym_prefix_len(text: &str) -> usize {
+    // Longest possible partial: a type name plus "_" and 15 hash characters
+    const MAX_PARTIAL: usize = MAX_ENTITY_TYPE_LEN + PSEUDONYM_HASH_LEN;
+
+    let word_start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+        .take(MAX_PARTIAL)
+        .last()
+        .map_or(text.len(), |(i, _)| i);
+
+    // Earliest start gives the longest candidate
+    (word_start..text.len())
+        .find(|&start| is_partial_pseudonym(&text[start..]))
+        .map_or(0, |start| text.len() - start)
+}
+
+/// `TYPE`, `TYPE_` or `TYPE_` followed by fewer than 16 hash characters
+fn is_partial_pseudonym(candidate: &str) -> bool {
+    if is_entity_type_name(candidate) {
+        return true;
+    }
+
+    match candidate.rfind('_') {
+        Some(pos) => {
+            let hash_part = &candidate[pos + 1..];
+            is_entity_type_name(&candidate[..pos])
+                && hash_part.len() < PSEUDONYM_HASH_LEN
+                && hash_part.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        }
+        None => false,
+    }
+}
+
+/// Request body for POST /guard/mask
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskRequest {
+    pub text: String,
+    pub tenant_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Detection method: "rules", "ai", or "hybrid"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection_method: Option<String>,
+    /// Privacy mode: "auto", "service-bypass", or "strict"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy_mode: Option<String>,
+    /// Detection rules applied on top of the guard's own for this request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<MaskRule>,
+}
+
+/// Extra detection rule of a mask request (e.g. from the caller's role profile)
+///
+/// `entity_type` may be a built-in type or a custom one such as
+/// "EMPLOYEE_ID"; it becomes the prefix of the pseudonyms issued for matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaskRule {
+    pub entity_type: String,
+    pub regex: String,
+}
+
+/// Response body for POST /guard/mask
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskResponse {
+    pub masked_text: String,
+    #[serde(default)]
+    pub redactions: HashMap<String, usize>,
+    pub session_id: String,
+}
+
+/// Request body for whole-text POST /guard/reidentify
+///
+/// Every pseudonym or FPE token issued in `session_id` that appears in
+/// `masked_text` is replaced with its original value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReidentifyTextRequest {
+    pub tenant_id: String,
+    pub session_id: String,
+    pub masked_text: String,
+}
+
+/// Response body for whole-text POST /guard/reidentify
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReidentifyTextResponse {
+    pub original_text: String,
+    /// Number of tokens substituted
+    #[serde(default)]
+    pub restored: usize,
+}
+
+/// Request body for POST /internal/flush-session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlushSessionRequest {
+    pub session_id: String,
+}
+
+/// Response body for POST /internal/flush-session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlushSessionResponse {
+    pub status: String,
+}
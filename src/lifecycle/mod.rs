@@ -1,3 +1,5 @@
 pub mod session_lifecycle;
+pub mod task_lifecycle;
 
 pub use session_lifecycle::{SessionLifecycle, TransitionError};
+pub use task_lifecycle::{TaskLifecycle, TaskTransitionError};
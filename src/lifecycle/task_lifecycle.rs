@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::models::{Task, TaskStatus};
+use crate::repository::TaskRepository;
+
+/// Task lifecycle manager
+///
+/// Manages task state transitions for agents acknowledging and finishing
+/// work routed to them.
+pub struct TaskLifecycle {
+    repo: TaskRepository,
+}
+
+impl TaskLifecycle {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: TaskRepository::new(pool),
+        }
+    }
+
+    /// Transition a task from its current state to another
+    ///
+    /// State machine:
+    /// - pending → claimed (agent leases the task via POST /tasks/claim)
+    /// - claimed → in_progress (agent starts working)
+    /// - claimed → pending (agent releases the task)
+    /// - claimed/in_progress → completed/failed (agent finishes)
+    /// - pending/claimed/in_progress → cancelled
+    ///
+    /// `completed_at` is set when the task reaches completed, failed or
+    /// cancelled. Invalid transitions are rejected, and a task with pending
+    /// approval steps cannot be completed (it is resolved via /approvals).
+    ///
+    /// A task leased via POST /tasks/claim can only be started, finished,
+    /// released or cancelled by the worker presenting its unexpired `lease_id`.
+    pub async fn transition(
+        &self,
+        task_id: Uuid,
+        new_status: TaskStatus,
+        lease_id: Option<Uuid>,
+    ) -> Result<Task, TaskTransitionError> {
+        let task = self
+            .repo
+            .get(task_id)
+            .await
+            .map_err(|e| TaskTransitionError::DatabaseError(e.to_string()))?
+            .ok_or(TaskTransitionError::TaskNotFound(task_id))?;
+
+        let current: TaskStatus = task
+            .status
+            .parse()
+            .map_err(TaskTransitionError::DatabaseError)?;
+
+        if !is_valid_transition(&current, &new_status) {
+            return Err(TaskTransitionError::InvalidTransition {
+                from: current,
+                to: new_status,
+            });
+        }
+
+        if current == new_status {
+            return Ok(task);
+        }
+
+        let lease_id = if requires_lease(&new_status) {
+            let (held, expires_at) = self
+                .repo
+                .lease(task_id)
+                .await
+                .map_err(|e| TaskTransitionError::DatabaseError(e.to_string()))?
+                .unwrap_or_default();
+            if !holds_lease(&current, held, expires_at, lease_id, Utc::now()) {
+                return Err(TaskTransitionError::LeaseNotHeld(task_id));
+            }
+            held
+        } else {
+            None
+        };
+
+        if new_status == TaskStatus::Completed {
+            let awaiting_approval = self
+                .repo
+                .has_pending_approval_steps(task_id)
+                .await
+                .map_err(|e| TaskTransitionError::DatabaseError(e.to_string()))?;
+            if awaiting_approval {
+                return Err(TaskTransitionError::AwaitingApproval(task_id));
+            }
+        }
+
+        // Conditional update: fails if another caller moved the task first
+        let updated = self
+            .repo
+            .transition_status(task_id, current.as_str(), new_status.as_str(), lease_id)
+            .await
+            .map_err(|e| TaskTransitionError::DatabaseError(e.to_string()))?
+            .ok_or(TaskTransitionError::Conflict(task_id))?;
+
+        info!(
+            message = "task.transition",
+            task_id = %task_id,
+            from = %current,
+            to = %new_status
+        );
+
+        Ok(updated)
+    }
+
+    /// Start work on a claimed task
+    pub async fn start(&self, task_id: Uuid, lease_id: Option<Uuid>) -> Result<Task, TaskTransitionError> {
+        self.transition(task_id, TaskStatus::InProgress, lease_id).await
+    }
+
+    /// Complete a task
+    pub async fn complete(&self, task_id: Uuid, lease_id: Option<Uuid>) -> Result<Task, TaskTransitionError> {
+        self.transition(task_id, TaskStatus::Completed, lease_id).await
+    }
+
+    /// Fail a task
+    pub async fn fail(&self, task_id: Uuid, lease_id: Option<Uuid>) -> Result<Task, TaskTransitionError> {
+        self.transition(task_id, TaskStatus::Failed, lease_id).await
+    }
+
+    /// Cancel a task that has not finished
+    pub async fn cancel(&self, task_id: Uuid, lease_id: Option<Uuid>) -> Result<Task, TaskTransitionError> {
+        self.transition(task_id, TaskStatus::Cancelled, lease_id).await
+    }
+
+    /// Release a claimed task back to pending
+    pub async fn release(&self, task_id: Uuid, lease_id: Option<Uuid>) -> Result<Task, TaskTransitionError> {
+        self.transition(task_id, TaskStatus::Pending, lease_id).await
+    }
+}
+
+/// Validate task state transition
+pub fn is_valid_transition(from: &TaskStatus, to: &TaskStatus) -> bool {
+    use TaskStatus::*;
+
+    match (from, to) {
+        // Terminal states cannot transition
+        (f, _) if f.is_terminal() => false,
+
+        // Pending can be claimed or cancelled
+        (Pending, Claimed) => true,
+        (Pending, Cancelled) => true,
+
+        // Claimed can start, finish, be released, or be cancelled
+        (Claimed, InProgress) => true,
+        (Claimed, Completed) => true,
+        (Claimed, Failed) => true,
+        (Claimed, Pending) => true,
+        (Claimed, Cancelled) => true,
+
+        // In progress (and legacy active) can finish or be cancelled
+        (InProgress | Active, Completed) => true,
+        (InProgress | Active, Failed) => true,
+        (InProgress | Active, Cancelled) => true,
+
+        // Same state is a no-op (allowed)
+        (a, b) if a == b => true,
+
+        // All other transitions are invalid
+        _ => false,
+    }
+}
+
+/// Whether moving a task to `to` is reserved to its lease holder
+///
+/// Claiming starts a lease; every other transition out of a leased task,
+/// cancelling included, needs the lease.
+pub fn requires_lease(to: &TaskStatus) -> bool {
+    !matches!(to, TaskStatus::Claimed)
+}
+
+/// Check a caller-supplied lease token against the task's lease
+///
+/// A pending task carries no lease and accepts any caller. A claimed or
+/// in-progress task only accepts its own, unexpired token; one without a
+/// lease (never claimed through POST /tasks/claim) accepts nobody.
+pub fn holds_lease(
+    current: &TaskStatus,
+    held: Option<Uuid>,
+    expires_at: Option<DateTime<Utc>>,
+    given: Option<Uuid>,
+    now: DateTime<Utc>,
+) -> bool {
+    match held {
+        None => !matches!(current, TaskStatus::Claimed | TaskStatus::InProgress),
+        Some(held) => given == Some(held) && expires_at.is_some_and(|expires_at| expires_at > now),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaskTransitionError {
+    #[error("Task not found: {0}")]
+    TaskNotFound(Uuid),
+
+    #[error("Invalid transition from {from} to {to}")]
+    InvalidTransition {
+        from: TaskStatus,
+        to: TaskStatus,
+    },
+
+    #[error("Task {0} has pending approval steps and can only be resolved via /approvals")]
+    AwaitingApproval(Uuid),
+
+    #[error("Lease on task {0} expired or is held by another worker")]
+    LeaseNotHeld(Uuid),
+
+    #[error("Task {0} was modified concurrently")]
+    Conflict(Uuid),
+
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_transitions() {
+        use TaskStatus::*;
+
+        // Pending transitions
+        assert!(is_valid_transition(&Pending, &Claimed));
+        assert!(is_valid_transition(&Pending, &Cancelled));
+        assert!(!is_valid_transition(&Pending, &InProgress));
+        assert!(!is_valid_transition(&Pending, &Completed));
+        assert!(!is_valid_transition(&Pending, &Failed));
+
+        // Claimed transitions
+        assert!(is_valid_transition(&Claimed, &InProgress));
+        assert!(is_valid_transition(&Claimed, &Completed));
+        assert!(is_valid_transition(&Claimed, &Failed));
+        assert!(is_valid_transition(&Claimed, &Pending));
+        assert!(is_valid_transition(&Claimed, &Cancelled));
+
+        // In progress transitions
+        assert!(is_valid_transition(&InProgress, &Completed));
+        assert!(is_valid_transition(&InProgress, &Failed));
+        assert!(is_valid_transition(&InProgress, &Cancelled));
+        assert!(!is_valid_transition(&InProgress, &Pending));
+        assert!(!is_valid_transition(&InProgress, &Claimed));
+
+        // Legacy active behaves like in_progress
+        assert!(is_valid_transition(&Active, &Completed));
+        assert!(!is_valid_transition(&Active, &Claimed));
+
+        // Terminal states cannot transition
+        assert!(!is_valid_transition(&Completed, &Failed));
+        assert!(!is_valid_transition(&Failed, &Pending));
+        assert!(!is_valid_transition(&Cancelled, &Claimed));
+        assert!(!is_valid_transition(&Approved, &Completed));
+        assert!(!is_valid_transition(&Rejected, &Rejected));
+
+        // Same state (no-op)
+        assert!(is_valid_transition(&Pending, &Pending));
+        assert!(is_valid_transition(&Claimed, &Claimed));
+        assert!(is_valid_transition(&InProgress, &InProgress));
+    }
+
+    #[test]
+    fn test_lease_checks() {
+        use TaskStatus::*;
+
+        assert!(requires_lease(&InProgress));
+        assert!(requires_lease(&Completed));
+        assert!(requires_lease(&Failed));
+        assert!(requires_lease(&Pending));
+        assert!(requires_lease(&Cancelled));
+        assert!(!requires_lease(&Claimed));
+
+        let now = Utc::now();
+        let lease = Uuid::new_v4();
+        let later = Some(now + chrono::Duration::seconds(60));
+        let earlier = Some(now - chrono::Duration::seconds(1));
+
+        // Pending tasks carry no lease and accept any caller
+        assert!(holds_lease(&Pending, None, None, None, now));
+        // Claimed or started tasks without a lease accept nobody
+        assert!(!holds_lease(&Claimed, None, None, None, now));
+        assert!(!holds_lease(&InProgress, None, None, Some(lease), now));
+        // The lease holder, while the lease is active
+        assert!(holds_lease(&Claimed, Some(lease), later, Some(lease), now));
+        assert!(holds_lease(&InProgress, Some(lease), later, Some(lease), now));
+        // Missing or foreign token, or an expired lease
+        assert!(!holds_lease(&Claimed, Some(lease), later, None, now));
+        assert!(!holds_lease(&Claimed, Some(lease), later, Some(Uuid::new_v4()), now));
+        assert!(!holds_lease(&InProgress, Some(lease), earlier, Some(lease), now));
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        use TaskStatus::*;
+
+        for status in [Pending, Claimed, InProgress, Active, Completed, Failed, Cancelled, Approved, Rejected] {
+            assert_eq!(status.as_str().parse::<TaskStatus>(), Ok(status));
+        }
+        assert!("done".parse::<TaskStatus>().is_err());
+    }
+}
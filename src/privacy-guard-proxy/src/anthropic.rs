@@ -0,0 +1,791 @@
+// anthropic.rs - Anthropic Messages API support
+//
+// Text-bearing fields of Messages requests, responses and stream events (so
+// they can be masked/unmasked like chat completions), holdback of streamed
+// block deltas, and translation between the Messages API and OpenAI chat
+// completions so one client config can target either kind of provider.
+
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::chat::schema_descriptions;
+use crate::stream::holdback_len;
+
+/// API version sent when the client didn't specify one
+pub const DEFAULT_API_VERSION: &str = "2023-06-01";
+
+/// max_tokens is required by the Messages API
+const DEFAULT_MAX_TOKENS: u64 = 4096;
+
+// ============================================================================
+// Text fields
+// ============================================================================
+
+/// Text fields of a Messages request that may carry PII
+///
+/// Covers the system prompt, text blocks, `tool_use` inputs, `tool_result`
+/// contents and tool descriptions.
+pub fn request_text_fields(body: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(body) = body.as_object_mut() else {
+        return fields;
+    };
+
+    for (key, value) in body.iter_mut() {
+        match key.as_str() {
+            "system" => blocks_text_fields(value, &mut fields),
+            "messages" => {
+                for message in value.as_array_mut().into_iter().flatten() {
+                    if let Some(content) = message.get_mut("content") {
+                        blocks_text_fields(content, &mut fields);
+                    }
+                }
+            }
+            "tools" => {
+                for tool in value.as_array_mut().into_iter().flatten() {
+                    let Some(tool) = tool.as_object_mut() else {
+                        continue;
+                    };
+                    for (key, value) in tool.iter_mut() {
+                        match (key.as_str(), value) {
+                            ("description", Value::String(description)) => fields.push(description),
+                            ("input_schema", schema) => schema_descriptions(schema, &mut fields),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fields
+}
+
+/// Text fields of a Messages response that may carry pseudonyms
+pub fn response_text_fields(response: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    if let Some(content) = response.get_mut("content") {
+        blocks_text_fields(content, &mut fields);
+    }
+    fields
+}
+
+/// Text fields of a Messages stream event
+pub fn stream_event_text_fields(event: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(event) = event.as_object_mut() else {
+        return fields;
+    };
+
+    for (key, value) in event.iter_mut() {
+        match key.as_str() {
+            "delta" => {
+                let Some(delta) = value.as_object_mut() else {
+                    continue;
+                };
+                for (key, value) in delta.iter_mut() {
+                    if let ("text" | "partial_json", Value::String(text)) = (key.as_str(), value) {
+                        fields.push(text);
+                    }
+                }
+            }
+            "content_block" => block_text_fields(value, &mut fields),
+            "message" => {
+                if let Some(content) = value.get_mut("content") {
+                    blocks_text_fields(content, &mut fields);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fields
+}
+
+/// A plain string, or the text of a list of content blocks
+fn blocks_text_fields<'a>(content: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    match content {
+        Value::String(text) => fields.push(text),
+        Value::Array(blocks) => {
+            for block in blocks.iter_mut() {
+                block_text_fields(block, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Text of one content block; images, documents and thinking blocks are left alone
+fn block_text_fields<'a>(block: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    let block_type = block.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+    let Some(block) = block.as_object_mut() else {
+        return;
+    };
+
+    for (key, value) in block.iter_mut() {
+        match (block_type.as_str(), key.as_str()) {
+            ("text", "text") => {
+                if let Value::String(text) = value {
+                    fields.push(text);
+                }
+            }
+            ("tool_use", "input") => json_string_leaves(value, fields),
+            ("tool_result", "content") => blocks_text_fields(value, fields),
+            _ => {}
+        }
+    }
+}
+
+/// Every string value (not key) in a JSON document
+fn json_string_leaves<'a>(value: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    match value {
+        Value::String(text) => fields.push(text),
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                json_string_leaves(item, fields);
+            }
+        }
+        Value::Object(obj) => {
+            for (_, item) in obj.iter_mut() {
+                json_string_leaves(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+// ============================================================================
+// Streaming
+// ============================================================================
+
+/// Holds back block delta text that may end in a partial token
+///
+/// Deltas are keyed by content block index. Held-back text is released as an
+/// extra `content_block_delta` event right before the block's
+/// `content_block_stop`.
+#[derive(Default)]
+pub struct BlockBuffer {
+    /// Block index -> (delta type, held-back text)
+    pending: BTreeMap<u64, (String, String)>,
+}
+
+impl BlockBuffer {
+    /// Rewrite a stream event so its delta ends on a safe boundary
+    ///
+    /// Returns events that must be sent before this one.
+    pub fn hold(&mut self, event: &mut Value) -> Vec<Value> {
+        let event_type = event.get("type").and_then(Value::as_str).unwrap_or_default();
+        let index = event.get("index").and_then(Value::as_u64).unwrap_or(0);
+
+        match event_type {
+            "content_block_delta" => {
+                let Some(delta) = event.get_mut("delta").and_then(Value::as_object_mut) else {
+                    return Vec::new();
+                };
+                let delta_type = delta.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+                let field = match delta_type.as_str() {
+                    "text_delta" => "text",
+                    "input_json_delta" => "partial_json",
+                    _ => return Vec::new(),
+                };
+                if let Some(Value::String(text)) = delta.get_mut(field) {
+                    let mut combined = self.pending.remove(&index).map(|(_, held)| held).unwrap_or_default();
+                    combined.push_str(text);
+                    let tail = combined.split_off(combined.len() - holdback_len(&combined));
+                    if !tail.is_empty() {
+                        self.pending.insert(index, (delta_type, tail));
+                    }
+                    *text = combined;
+                }
+                Vec::new()
+            }
+            "content_block_stop" => self.pending.remove(&index).map(|held| vec![release_event(index, held)]).unwrap_or_default(),
+            "message_delta" | "message_stop" => self.flush(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Events releasing everything still held back
+    pub fn flush(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|(index, held)| release_event(index, held))
+            .collect()
+    }
+}
+
+/// content_block_delta carrying held-back text
+fn release_event(index: u64, (delta_type, text): (String, String)) -> Value {
+    let field = if delta_type == "input_json_delta" { "partial_json" } else { "text" };
+    json!({
+        "type": "content_block_delta",
+        "index": index,
+        "delta": {"type": delta_type, field: text}
+    })
+}
+
+// ============================================================================
+// Translation
+// ============================================================================
+
+/// Translate an OpenAI chat completion request to a Messages request
+pub fn messages_request_from_chat(chat: &Value) -> Value {
+    let mut system = Vec::new();
+    let mut messages: Vec<(String, Vec<Value>)> = Vec::new();
+
+    for message in chat.get("messages").and_then(Value::as_array).into_iter().flatten() {
+        let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+        let content = message.get("content").unwrap_or(&Value::Null);
+
+        let (role, blocks) = match role {
+            "system" | "developer" => {
+                system.push(chat_content_text(content));
+                continue;
+            }
+            "assistant" => {
+                let mut blocks = chat_content_blocks(content);
+                for call in message.get("tool_calls").and_then(Value::as_array).into_iter().flatten() {
+                    let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.get("id").cloned().unwrap_or(Value::Null),
+                        "name": call["function"].get("name").cloned().unwrap_or(Value::Null),
+                        "input": serde_json::from_str::<Value>(arguments).unwrap_or_else(|_| json!({})),
+                    }));
+                }
+                ("assistant", blocks)
+            }
+            "tool" => (
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.get("tool_call_id").cloned().unwrap_or(Value::Null),
+                    "content": chat_content_text(content),
+                })],
+            ),
+            _ => ("user", chat_content_blocks(content)),
+        };
+
+        // The Messages API requires alternating roles
+        match messages.last_mut() {
+            Some((last_role, last_blocks)) if last_role == role => last_blocks.extend(blocks),
+            _ => messages.push((role.to_string(), blocks)),
+        }
+    }
+
+    let mut request = Map::new();
+    copy_field(chat, &mut request, "model", "model");
+    request.insert(
+        "max_tokens".to_string(),
+        chat.get("max_tokens")
+            .or_else(|| chat.get("max_completion_tokens"))
+            .cloned()
+            .unwrap_or_else(|| json!(DEFAULT_MAX_TOKENS)),
+    );
+    if !system.is_empty() {
+        request.insert("system".to_string(), Value::String(system.join("\n\n")));
+    }
+    request.insert(
+        "messages".to_string(),
+        messages
+            .into_iter()
+            .map(|(role, content)| json!({"role": role, "content": content}))
+            .collect(),
+    );
+    copy_field(chat, &mut request, "temperature", "temperature");
+    copy_field(chat, &mut request, "top_p", "top_p");
+    copy_field(chat, &mut request, "stream", "stream");
+    match chat.get("stop") {
+        Some(Value::String(stop)) => {
+            request.insert("stop_sequences".to_string(), json!([stop]));
+        }
+        Some(stop @ Value::Array(_)) => {
+            request.insert("stop_sequences".to_string(), stop.clone());
+        }
+        _ => {}
+    }
+
+    if let Some(tools) = chat.get("tools").and_then(Value::as_array) {
+        let tools: Vec<Value> = tools
+            .iter()
+            .filter_map(|tool| tool.get("function"))
+            .map(|function| {
+                let mut tool = Map::new();
+                copy_field(function, &mut tool, "name", "name");
+                copy_field(function, &mut tool, "description", "description");
+                tool.insert(
+                    "input_schema".to_string(),
+                    function.get("parameters").cloned().unwrap_or_else(|| json!({"type": "object"})),
+                );
+                Value::Object(tool)
+            })
+            .collect();
+        request.insert("tools".to_string(), Value::Array(tools));
+    }
+    match chat.get("tool_choice") {
+        Some(Value::String(choice)) => {
+            let choice = match choice.as_str() {
+                "required" => "any",
+                "none" => "none",
+                _ => "auto",
+            };
+            request.insert("tool_choice".to_string(), json!({"type": choice}));
+        }
+        Some(Value::Object(choice)) => {
+            if let Some(name) = choice.get("function").and_then(|f| f.get("name")) {
+                request.insert("tool_choice".to_string(), json!({"type": "tool", "name": name}));
+            }
+        }
+        _ => {}
+    }
+
+    Value::Object(request)
+}
+
+/// Translate a Messages response to an OpenAI chat completion
+pub fn chat_response_from_messages(response: &Value) -> Value {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in response.get("content").and_then(Value::as_array).into_iter().flatten() {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => text.push_str(block["text"].as_str().unwrap_or_default()),
+            Some("tool_use") => tool_calls.push(json!({
+                "id": block.get("id").cloned().unwrap_or(Value::Null),
+                "type": "function",
+                "function": {
+                    "name": block.get("name").cloned().unwrap_or(Value::Null),
+                    "arguments": block.get("input").unwrap_or(&json!({})).to_string(),
+                }
+            })),
+            _ => {}
+        }
+    }
+
+    let mut message = json!({
+        "role": "assistant",
+        "content": if text.is_empty() && !tool_calls.is_empty() { Value::Null } else { Value::String(text) },
+    });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = Value::Array(tool_calls);
+    }
+
+    let finish_reason = match response.get("stop_reason").and_then(Value::as_str) {
+        Some("max_tokens") => "length",
+        Some("tool_use") => "tool_calls",
+        _ => "stop",
+    };
+    let input_tokens = response["usage"]["input_tokens"].as_u64().unwrap_or(0);
+    let output_tokens = response["usage"]["output_tokens"].as_u64().unwrap_or(0);
+
+    json!({
+        "id": response.get("id").cloned().unwrap_or(Value::Null),
+        "object": "chat.completion",
+        "created": chrono::Utc::now().timestamp(),
+        "model": response.get("model").cloned().unwrap_or(Value::Null),
+        "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}],
+        "usage": {
+            "prompt_tokens": input_tokens,
+            "completion_tokens": output_tokens,
+            "total_tokens": input_tokens + output_tokens,
+        }
+    })
+}
+
+/// Translate a Messages request to an OpenAI chat completion request
+pub fn chat_request_from_messages(request: &Value) -> Value {
+    let mut messages = Vec::new();
+
+    if let Some(system) = request.get("system") {
+        messages.push(json!({"role": "system", "content": blocks_text(system)}));
+    }
+
+    for message in request.get("messages").and_then(Value::as_array).into_iter().flatten() {
+        let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+        let content = message.get("content").unwrap_or(&Value::Null);
+        let blocks = match content {
+            Value::String(text) => {
+                messages.push(json!({"role": role, "content": text}));
+                continue;
+            }
+            Value::Array(blocks) => blocks,
+            _ => continue,
+        };
+
+        let mut parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block.get("type").and_then(Value::as_str) {
+                Some("text") => parts.push(json!({"type": "text", "text": block["text"]})),
+                Some("image") => {
+                    let source = &block["source"];
+                    let url = match source.get("type").and_then(Value::as_str) {
+                        Some("base64") => format!(
+                            "data:{};base64,{}",
+                            source["media_type"].as_str().unwrap_or("image/png"),
+                            source["data"].as_str().unwrap_or_default()
+                        ),
+                        _ => source["url"].as_str().unwrap_or_default().to_string(),
+                    };
+                    parts.push(json!({"type": "image_url", "image_url": {"url": url}}));
+                }
+                Some("tool_use") => tool_calls.push(json!({
+                    "id": block["id"],
+                    "type": "function",
+                    "function": {
+                        "name": block["name"],
+                        "arguments": block.get("input").unwrap_or(&json!({})).to_string(),
+                    }
+                })),
+                // Tool results become tool messages ahead of the user's text
+                Some("tool_result") => messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": block["tool_use_id"],
+                    "content": blocks_text(block.get("content").unwrap_or(&Value::Null)),
+                })),
+                _ => {}
+            }
+        }
+
+        if role == "assistant" {
+            let text: String = parts.iter().filter_map(|p| p["text"].as_str()).collect();
+            let mut message = json!({
+                "role": "assistant",
+                "content": if text.is_empty() && !tool_calls.is_empty() { Value::Null } else { Value::String(text) },
+            });
+            if !tool_calls.is_empty() {
+                message["tool_calls"] = Value::Array(tool_calls);
+            }
+            messages.push(message);
+        } else if !parts.is_empty() {
+            messages.push(json!({"role": role, "content": parts}));
+        }
+    }
+
+    let mut chat = Map::new();
+    copy_field(request, &mut chat, "model", "model");
+    chat.insert("messages".to_string(), Value::Array(messages));
+    copy_field(request, &mut chat, "max_tokens", "max_tokens");
+    copy_field(request, &mut chat, "temperature", "temperature");
+    copy_field(request, &mut chat, "top_p", "top_p");
+    copy_field(request, &mut chat, "stream", "stream");
+    copy_field(request, &mut chat, "stop_sequences", "stop");
+
+    if let Some(tools) = request.get("tools").and_then(Value::as_array) {
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|tool| {
+                let mut function = Map::new();
+                copy_field(tool, &mut function, "name", "name");
+                copy_field(tool, &mut function, "description", "description");
+                copy_field(tool, &mut function, "input_schema", "parameters");
+                json!({"type": "function", "function": function})
+            })
+            .collect();
+        chat.insert("tools".to_string(), Value::Array(tools));
+    }
+    if let Some(choice) = request.get("tool_choice") {
+        let choice = match choice.get("type").and_then(Value::as_str) {
+            Some("any") => json!("required"),
+            Some("none") => json!("none"),
+            Some("tool") => json!({"type": "function", "function": {"name": choice["name"]}}),
+            _ => json!("auto"),
+        };
+        chat.insert("tool_choice".to_string(), choice);
+    }
+
+    Value::Object(chat)
+}
+
+/// Translate an OpenAI chat completion to a Messages response
+pub fn messages_response_from_chat(chat: &Value) -> Value {
+    let choice = &chat["choices"][0];
+    let message = &choice["message"];
+
+    let mut content = Vec::new();
+    if let Some(text) = message.get("content").and_then(Value::as_str).filter(|t| !t.is_empty()) {
+        content.push(json!({"type": "text", "text": text}));
+    }
+    for call in message.get("tool_calls").and_then(Value::as_array).into_iter().flatten() {
+        let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+        content.push(json!({
+            "type": "tool_use",
+            "id": call["id"],
+            "name": call["function"]["name"],
+            "input": serde_json::from_str::<Value>(arguments).unwrap_or_else(|_| json!({})),
+        }));
+    }
+
+    let stop_reason = match choice.get("finish_reason").and_then(Value::as_str) {
+        Some("length") => "max_tokens",
+        Some("tool_calls") | Some("function_call") => "tool_use",
+        _ => "end_turn",
+    };
+
+    json!({
+        "id": chat.get("id").cloned().unwrap_or(Value::Null),
+        "type": "message",
+        "role": "assistant",
+        "model": chat.get("model").cloned().unwrap_or(Value::Null),
+        "content": content,
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": chat["usage"]["prompt_tokens"].as_u64().unwrap_or(0),
+            "output_tokens": chat["usage"]["completion_tokens"].as_u64().unwrap_or(0),
+        }
+    })
+}
+
+/// Concatenated text of chat message content (string or parts)
+fn chat_content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts.iter().filter_map(|p| p["text"].as_str()).collect::<Vec<_>>().join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Chat message content (string or parts) as Messages content blocks
+fn chat_content_blocks(content: &Value) -> Vec<Value> {
+    match content {
+        Value::String(text) if !text.is_empty() => vec![json!({"type": "text", "text": text})],
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part.get("type").and_then(Value::as_str) {
+                Some("text") => Some(json!({"type": "text", "text": part["text"]})),
+                Some("image_url") => {
+                    let url = part["image_url"]["url"].as_str().unwrap_or_default();
+                    Some(image_block(url))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Image block for a data: or remote URL
+fn image_block(url: &str) -> Value {
+    let Some((media_type, data)) = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+    else {
+        return json!({"type": "image", "source": {"type": "url", "url": url}});
+    };
+    json!({"type": "image", "source": {"type": "base64", "media_type": media_type, "data": data}})
+}
+
+/// Concatenated text of a string or a list of content blocks
+fn blocks_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn copy_field(from: &Value, to: &mut Map<String, Value>, from_key: &str, to_key: &str) {
+    if let Some(value) = from.get(from_key).filter(|v| !v.is_null()) {
+        to.insert(to_key.to_string(), value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(fields: Vec<&mut String>) -> Vec<String> {
+        fields.into_iter().map(|s| s.clone()).collect()
+    }
+
+    #[test]
+    fn test_request_fields() {
+        let mut body = json!({
+            "model": "claude-sonnet-4",
+            "system": [{"type": "text", "text": "You help alice@example.com"}],
+            "messages": [
+                {"role": "user", "content": "Call 555-123-4567"},
+                {"role": "assistant", "content": [
+                    {"type": "text", "text": "Looking up"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "crm__lookup",
+                     "input": {"email": "alice@example.com", "limit": 1, "tags": ["vip"]}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "Alice Smith"},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAAA"}}
+                ]}
+            ],
+            "tools": [{"name": "crm__lookup", "description": "Look up a customer",
+                       "input_schema": {"type": "object", "properties": {"email": {"type": "string", "description": "Email"}}}}]
+        });
+
+        let mut fields = texts(request_text_fields(&mut body));
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec![
+                "Alice Smith", "Call 555-123-4567", "Email", "Look up a customer", "Looking up",
+                "You help alice@example.com", "alice@example.com", "vip",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_response_and_stream_fields() {
+        let mut response = json!({"content": [
+            {"type": "text", "text": "Hi PERSON_x"},
+            {"type": "tool_use", "id": "toolu_1", "name": "send", "input": {"to": "EMAIL_y"}}
+        ]});
+        assert_eq!(texts(response_text_fields(&mut response)), vec!["Hi PERSON_x", "EMAIL_y"]);
+
+        let mut delta = json!({"type": "content_block_delta", "index": 0,
+                               "delta": {"type": "input_json_delta", "partial_json": "{\"to\": \"EMAIL_y"}});
+        assert_eq!(texts(stream_event_text_fields(&mut delta)), vec!["{\"to\": \"EMAIL_y"]);
+    }
+
+    #[test]
+    fn test_block_buffer_releases_before_stop() {
+        let mut buffer = BlockBuffer::default();
+
+        let mut delta = json!({"type": "content_block_delta", "index": 0,
+                               "delta": {"type": "text_delta", "text": "Hello PERSON_a3"}});
+        assert!(buffer.hold(&mut delta).is_empty());
+        assert_eq!(delta["delta"]["text"], "Hello ");
+
+        let mut stop = json!({"type": "content_block_stop", "index": 0});
+        let before = buffer.hold(&mut stop);
+        assert_eq!(
+            before,
+            vec![json!({"type": "content_block_delta", "index": 0,
+                        "delta": {"type": "text_delta", "text": "PERSON_a3"}})]
+        );
+        assert!(buffer.flush().is_empty());
+    }
+
+    #[test]
+    fn test_chat_to_messages_request() {
+        let chat = json!({
+            "model": "claude-sonnet-4",
+            "messages": [
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": "Look up alice"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": {"name": "crm__lookup", "arguments": "{\"name\":\"alice\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "found"},
+                {"role": "user", "content": "thanks"}
+            ],
+            "tools": [{"type": "function", "function": {"name": "crm__lookup", "parameters": {"type": "object"}}}],
+            "tool_choice": "required",
+            "stop": "END"
+        });
+
+        let request = messages_request_from_chat(&chat);
+
+        assert_eq!(request["system"], "Be brief");
+        assert_eq!(request["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(request["stop_sequences"], json!(["END"]));
+        assert_eq!(request["tool_choice"], json!({"type": "any"}));
+        assert_eq!(request["tools"][0]["input_schema"], json!({"type": "object"}));
+        assert_eq!(
+            request["messages"],
+            json!([
+                {"role": "user", "content": [{"type": "text", "text": "Look up alice"}]},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "call_1", "name": "crm__lookup", "input": {"name": "alice"}}
+                ]},
+                // Tool result and the next user turn are merged to keep roles alternating
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "call_1", "content": "found"},
+                    {"type": "text", "text": "thanks"}
+                ]}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_messages_to_chat_response() {
+        let response = json!({
+            "id": "msg_1", "type": "message", "role": "assistant", "model": "claude-sonnet-4",
+            "content": [
+                {"type": "text", "text": "Sending"},
+                {"type": "tool_use", "id": "toolu_1", "name": "send", "input": {"to": "alice"}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        });
+
+        let chat = chat_response_from_messages(&response);
+
+        assert_eq!(chat["object"], "chat.completion");
+        assert_eq!(chat["choices"][0]["finish_reason"], "tool_calls");
+        assert_eq!(chat["choices"][0]["message"]["content"], "Sending");
+        assert_eq!(chat["choices"][0]["message"]["tool_calls"][0]["function"]["arguments"], "{\"to\":\"alice\"}");
+        assert_eq!(chat["usage"]["total_tokens"], 15);
+    }
+
+    #[test]
+    fn test_messages_to_chat_request_and_back() {
+        let request = json!({
+            "model": "gpt-4o",
+            "max_tokens": 100,
+            "system": "Be brief",
+            "messages": [
+                {"role": "user", "content": "Send it"},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "toolu_1", "name": "send", "input": {"to": "alice"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": [{"type": "text", "text": "sent"}]},
+                    {"type": "text", "text": "ok?"}
+                ]}
+            ],
+            "tools": [{"name": "send", "description": "Send", "input_schema": {"type": "object"}}],
+            "tool_choice": {"type": "tool", "name": "send"}
+        });
+
+        let chat = chat_request_from_messages(&request);
+        assert_eq!(
+            chat["messages"],
+            json!([
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": "Send it"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "toolu_1", "type": "function",
+                    "function": {"name": "send", "arguments": "{\"to\":\"alice\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "toolu_1", "content": "sent"},
+                {"role": "user", "content": [{"type": "text", "text": "ok?"}]}
+            ])
+        );
+        assert_eq!(chat["tools"][0]["function"]["parameters"], json!({"type": "object"}));
+        assert_eq!(chat["tool_choice"], json!({"type": "function", "function": {"name": "send"}}));
+
+        let response = messages_response_from_chat(&json!({
+            "id": "chatcmpl-1", "model": "gpt-4o",
+            "choices": [{"index": 0, "finish_reason": "stop", "message": {"role": "assistant", "content": "Done"}}],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 1}
+        }));
+        assert_eq!(response["content"], json!([{"type": "text", "text": "Done"}]));
+        assert_eq!(response["stop_reason"], "end_turn");
+        assert_eq!(response["usage"], json!({"input_tokens": 3, "output_tokens": 1}));
+    }
+
+    #[test]
+    fn test_image_blocks_translate_both_ways() {
+        assert_eq!(
+            image_block("data:image/png;base64,AAAA"),
+            json!({"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAAA"}})
+        );
+        assert_eq!(
+            image_block("https://example.com/a.png"),
+            json!({"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}})
+        );
+    }
+}
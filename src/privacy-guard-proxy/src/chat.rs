@@ -0,0 +1,279 @@
+// chat.rs - Text-bearing fields of OpenAI chat completion payloads
+//
+// Goose sends more than plain-string message content: multi-part content
+// arrays, tool-call arguments, tool results and tool definitions all carry
+// user data. These helpers collect every such field so the proxy can mask
+// requests and unmask responses field by field. Structural fields (roles,
+// tool names, ids, types) are never returned, so masking can't break routing.
+
+use serde_json::Value;
+
+/// Text fields of a chat completion request that may carry PII
+///
+/// Covers message content (string or `[{type: "text", text}]` parts, for
+/// every role including `tool`), assistant `tool_calls[].function.arguments`
+/// and legacy `function_call.arguments`, and the descriptions in `tools` /
+/// `functions` definitions.
+pub fn request_text_fields(body: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(body) = body.as_object_mut() else {
+        return fields;
+    };
+
+    for (key, value) in body.iter_mut() {
+        match key.as_str() {
+            "messages" => {
+                for message in value.as_array_mut().into_iter().flatten() {
+                    message_text_fields(message, &mut fields);
+                }
+            }
+            "tools" => {
+                for tool in value.as_array_mut().into_iter().flatten() {
+                    if let Some(function) = tool.get_mut("function") {
+                        function_definition_text_fields(function, &mut fields);
+                    }
+                }
+            }
+            "functions" => {
+                for function in value.as_array_mut().into_iter().flatten() {
+                    function_definition_text_fields(function, &mut fields);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fields
+}
+
+/// Text fields of a chat completion response that may carry pseudonyms
+///
+/// Covers `choices[].message` (and `choices[].delta` for stream chunks):
+/// content and tool-call arguments, so tools receive real values locally.
+pub fn response_text_fields(response: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(choices) = response.get_mut("choices").and_then(Value::as_array_mut) else {
+        return fields;
+    };
+
+    for choice in choices.iter_mut() {
+        let Some(choice) = choice.as_object_mut() else {
+            continue;
+        };
+        for (key, value) in choice.iter_mut() {
+            if key == "message" || key == "delta" {
+                message_text_fields(value, &mut fields);
+            }
+        }
+    }
+
+    fields
+}
+
+/// Content and tool-call arguments of one message
+fn message_text_fields<'a>(message: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    let Some(message) = message.as_object_mut() else {
+        return;
+    };
+
+    for (key, value) in message.iter_mut() {
+        match key.as_str() {
+            "content" => content_text_fields(value, fields),
+            "tool_calls" => {
+                for call in value.as_array_mut().into_iter().flatten() {
+                    if let Some(Value::String(arguments)) =
+                        call.get_mut("function").and_then(|f| f.get_mut("arguments"))
+                    {
+                        fields.push(arguments);
+                    }
+                }
+            }
+            "function_call" => {
+                if let Some(Value::String(arguments)) = value.get_mut("arguments") {
+                    fields.push(arguments);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Plain-string content, or the text of `type: "text"` content parts
+fn content_text_fields<'a>(content: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    match content {
+        Value::String(text) => fields.push(text),
+        Value::Array(parts) => {
+            for part in parts.iter_mut() {
+                if part.get("type").and_then(Value::as_str) != Some("text") {
+                    continue;
+                }
+                if let Some(Value::String(text)) = part.get_mut("text") {
+                    fields.push(text);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Description of a tool/function definition and of its parameters
+fn function_definition_text_fields<'a>(function: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    let Some(function) = function.as_object_mut() else {
+        return;
+    };
+
+    for (key, value) in function.iter_mut() {
+        match key.as_str() {
+            "description" => {
+                if let Value::String(description) = value {
+                    fields.push(description);
+                }
+            }
+            "parameters" => schema_descriptions(value, fields),
+            _ => {}
+        }
+    }
+}
+
+/// Every string `description` in a JSON schema
+pub(crate) fn schema_descriptions<'a>(schema: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    match schema {
+        Value::Object(obj) => {
+            for (key, value) in obj.iter_mut() {
+                if key == "description" && value.is_string() {
+                    if let Value::String(description) = value {
+                        fields.push(description);
+                    }
+                } else {
+                    schema_descriptions(value, fields);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                schema_descriptions(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn texts(fields: Vec<&mut String>) -> Vec<String> {
+        fields.into_iter().map(|s| s.clone()).collect()
+    }
+
+    #[test]
+    fn test_request_fields_cover_goose_shapes() {
+        let mut body = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "You help alice@example.com"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "Call 555-123-4567"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}}
+                ]},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "crm__lookup", "arguments": "{\"email\":\"alice@example.com\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "Alice Smith, SSN 123-45-6789"}
+            ],
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": "crm__lookup",
+                    "description": "Look up a customer",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "email": {"type": "string", "description": "Customer email"}
+                        }
+                    }
+                }
+            }]
+        });
+
+        let fields = texts(request_text_fields(&mut body));
+
+        assert!(fields.contains(&"You help alice@example.com".to_string()));
+        assert!(fields.contains(&"Call 555-123-4567".to_string()));
+        assert!(fields.contains(&"{\"email\":\"alice@example.com\"}".to_string()));
+        assert!(fields.contains(&"Alice Smith, SSN 123-45-6789".to_string()));
+        assert!(fields.contains(&"Look up a customer".to_string()));
+        assert!(fields.contains(&"Customer email".to_string()));
+
+        // Structural fields are left alone
+        for structural in ["crm__lookup", "call_1", "function", "tool", "gpt-4o", "string"] {
+            assert!(!fields.contains(&structural.to_string()), "{}", structural);
+        }
+        assert!(!fields.iter().any(|f| f.starts_with("data:image")));
+        assert_eq!(fields.len(), 6);
+    }
+
+    #[test]
+    fn test_request_fields_are_mutable_in_place() {
+        let mut body = json!({
+            "messages": [
+                {"role": "user", "content": [{"type": "text", "text": "alice"}]},
+                {"role": "assistant", "function_call": {"name": "f", "arguments": "{\"who\":\"alice\"}"}}
+            ],
+            "functions": [{"name": "f", "description": "alice's tool"}]
+        });
+
+        for field in request_text_fields(&mut body) {
+            *field = field.replace("alice", "PERSON_x");
+        }
+
+        assert_eq!(body["messages"][0]["content"][0]["text"], "PERSON_x");
+        assert_eq!(body["messages"][1]["function_call"]["arguments"], "{\"who\":\"PERSON_x\"}");
+        assert_eq!(body["functions"][0]["description"], "PERSON_x's tool");
+        assert_eq!(body["functions"][0]["name"], "f");
+    }
+
+    #[test]
+    fn test_schema_property_named_description() {
+        let mut body = json!({
+            "tools": [{"type": "function", "function": {
+                "name": "f",
+                "parameters": {"properties": {
+                    "description": {"type": "string", "description": "Free text"}
+                }}
+            }}]
+        });
+
+        assert_eq!(texts(request_text_fields(&mut body)), vec!["Free text"]);
+    }
+
+    #[test]
+    fn test_response_fields_cover_all_choices_and_tool_calls() {
+        let mut response = json!({
+            "choices": [
+                {"index": 0, "message": {"role": "assistant", "content": "Hi PERSON_x"}},
+                {"index": 1, "message": {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "crm__lookup", "arguments": "{\"email\":\"EMAIL_y\"}"}
+                }]}},
+                {"index": 2, "delta": {"content": "PERSON_x again"}}
+            ]
+        });
+
+        assert_eq!(
+            texts(response_text_fields(&mut response)),
+            vec!["Hi PERSON_x", "{\"email\":\"EMAIL_y\"}", "PERSON_x again"]
+        );
+    }
+
+    #[test]
+    fn test_fields_of_unexpected_shapes() {
+        assert!(request_text_fields(&mut json!("text")).is_empty());
+        assert!(request_text_fields(&mut json!({"messages": "nope"})).is_empty());
+        assert!(response_text_fields(&mut json!({"choices": [1, null]})).is_empty());
+    }
+}
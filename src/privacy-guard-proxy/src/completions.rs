@@ -0,0 +1,135 @@
+// completions.rs - Text-bearing fields of OpenAI completions and embeddings payloads
+//
+// The legacy completions API takes a `prompt` (a string or an array of
+// strings) and returns `choices[].text`; the embeddings API takes an `input`
+// of the same shape and returns only vectors. Prompts may also be given as
+// token ids, which carry no maskable text and are left alone.
+
+use serde_json::Value;
+
+/// Text fields of a completions request: `prompt` and `suffix`
+pub fn request_text_fields(body: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(body) = body.as_object_mut() else {
+        return fields;
+    };
+
+    for (key, value) in body.iter_mut() {
+        match key.as_str() {
+            "prompt" => string_or_strings(value, &mut fields),
+            "suffix" => {
+                if let Value::String(text) = value {
+                    fields.push(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fields
+}
+
+/// Text fields of an embeddings request: `input`
+pub fn embeddings_text_fields(body: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    if let Some(input) = body.get_mut("input") {
+        string_or_strings(input, &mut fields);
+    }
+    fields
+}
+
+/// Text fields of a completions response (or stream chunk): `choices[].text`
+pub fn response_text_fields(response: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(choices) = response.get_mut("choices").and_then(Value::as_array_mut) else {
+        return fields;
+    };
+
+    for choice in choices.iter_mut() {
+        if let Some(Value::String(text)) = choice.get_mut("text") {
+            fields.push(text);
+        }
+    }
+
+    fields
+}
+
+/// Whether a response or stream chunk is a completions (not chat) payload
+pub fn is_completion(response: &Value) -> bool {
+    response
+        .get("choices")
+        .and_then(Value::as_array)
+        .is_some_and(|choices| choices.iter().any(|choice| choice.get("text").is_some()))
+}
+
+/// A string, or the strings of an array (token id arrays are skipped)
+fn string_or_strings<'a>(value: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    match value {
+        Value::String(text) => fields.push(text),
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                if let Value::String(text) = item {
+                    fields.push(text);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn texts(fields: Vec<&mut String>) -> Vec<String> {
+        fields.into_iter().map(|f| f.clone()).collect()
+    }
+
+    #[test]
+    fn test_prompt_shapes() {
+        let mut single = json!({"model": "gpt-3.5-turbo-instruct", "prompt": "Email alice@example.com", "suffix": "Thanks"});
+        assert_eq!(texts(request_text_fields(&mut single)), vec!["Email alice@example.com", "Thanks"]);
+
+        let mut batch = json!({"prompt": ["Call 555-123-4567", "Hi Bob"]});
+        assert_eq!(texts(request_text_fields(&mut batch)), vec!["Call 555-123-4567", "Hi Bob"]);
+
+        // Token ids carry no text
+        let mut tokens = json!({"prompt": [[1212, 318], [257]]});
+        assert!(request_text_fields(&mut tokens).is_empty());
+        let mut tokens = json!({"prompt": [1212, 318]});
+        assert!(request_text_fields(&mut tokens).is_empty());
+    }
+
+    #[test]
+    fn test_embeddings_input() {
+        let mut body = json!({"model": "text-embedding-3-small", "input": ["SSN 123-45-6789", "plain"]});
+        for field in embeddings_text_fields(&mut body) {
+            *field = field.to_uppercase();
+        }
+        assert_eq!(body["input"], json!(["SSN 123-45-6789", "PLAIN"]));
+        assert_eq!(body["model"], "text-embedding-3-small");
+
+        let mut single = json!({"input": "alice@example.com"});
+        assert_eq!(texts(embeddings_text_fields(&mut single)), vec!["alice@example.com"]);
+    }
+
+    #[test]
+    fn test_response_text() {
+        let mut response = json!({
+            "object": "text_completion",
+            "choices": [
+                {"index": 0, "text": "Dear PERSON_a3f7b2c8e1d4f9a2", "finish_reason": "stop"},
+                {"index": 1, "text": "Hello", "finish_reason": "stop"}
+            ]
+        });
+        assert!(is_completion(&response));
+        assert_eq!(
+            texts(response_text_fields(&mut response)),
+            vec!["Dear PERSON_a3f7b2c8e1d4f9a2", "Hello"]
+        );
+
+        let chat = json!({"choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}}]});
+        assert!(!is_completion(&chat));
+    }
+}
@@ -1,17 +1,33 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{Html, IntoResponse, Json},
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Json, Response},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::proxy::profile_error;
 use crate::state::{ActivityLogEntry, DetectionMethod, PrivacyMode, RoutingMode, ProxyState};
+use crate::tenant;
+
+/// Header carrying the PROXY_ADMIN_TOKEN (Authorization carries the caller's API key)
+pub const ADMIN_TOKEN_HEADER: &str = "x-proxy-admin-token";
+
+/// Tenant selection (`?tenant=<id>`) of the /api/* endpoints
+///
+/// Without it the endpoints operate on the caller's own tenant. Selecting
+/// another tenant requires the admin token.
+#[derive(Deserialize)]
+pub struct TenantQuery {
+    pub tenant: Option<String>,
+}
 
 /// Response for /api/status endpoint
 #[derive(Serialize)]
 pub struct StatusResponse {
     pub status: String,
+    pub tenant: String,
     pub mode: PrivacyMode,
     pub detection_method: DetectionMethod,
     pub allow_override: bool,
@@ -38,28 +54,156 @@ pub struct SetDetectionMethodRequest {
     pub method: DetectionMethod,
 }
 
+/// One tenant in the /api/tenants response
+#[derive(Serialize)]
+pub struct TenantSummary {
+    pub id: String,
+    #[serde(flatten)]
+    pub settings: Settings,
+    pub activity_count: usize,
+}
+
+/// Response for /api/tenants endpoint
+#[derive(Serialize)]
+pub struct TenantsResponse {
+    pub tenants: Vec<TenantSummary>,
+}
+
+/// Whether the request carries the PROXY_ADMIN_TOKEN
+fn is_admin(state: &ProxyState, headers: &HeaderMap) -> bool {
+    let Some(admin_token) = state.admin_token.as_deref() else {
+        return false;
+    };
+    let token = headers
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    // Compare digests so the time taken doesn't reveal how much of the token matched
+    !token.is_empty() && Sha256::digest(token.as_bytes()) == Sha256::digest(admin_token.as_bytes())
+}
+
+/// 403 response for a request on other tenants without the admin token
+fn admin_required() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({
+            "error": "Selecting a tenant requires the admin token"
+        })),
+    ).into_response()
+}
+
+/// State of the selected tenant
+///
+/// The caller's own tenant (resolved like a proxied request), or with the
+/// admin token the tenant named by `?tenant=`.
+async fn selected_tenant(state: ProxyState, headers: &HeaderMap, query: &TenantQuery) -> Result<ProxyState, Response> {
+    let Some(id) = query.tenant.as_deref().filter(|id| !id.is_empty()) else {
+        return match tenant::resolve(&state, headers).await {
+            Ok((tenant, _)) => Ok(tenant),
+            Err(e) => Err(profile_error(&state, e, "system").await),
+        };
+    };
+    if !is_admin(&state, headers) {
+        return Err(admin_required());
+    }
+
+    state.existing_tenant(id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Unknown tenant '{}'", id)
+            })),
+        ).into_response()
+    })
+}
+
+/// Current settings of a tenant
+async fn tenant_settings(state: &ProxyState) -> Settings {
+    Settings {
+        routing: state.get_routing_mode().await,
+        detection: state.get_detection_method().await,
+        privacy: state.get_mode().await,
+    }
+}
+
+/// 403 response for a setting locked by the tenant's profile
+fn locked(error: String) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({
+            "error": error
+        })),
+    ).into_response()
+}
+
 /// Serve the Control Panel UI (embedded HTML)
 pub async fn serve_ui() -> Html<&'static str> {
     Html(include_str!("ui/index.html"))
 }
 
+/// GET /api/tenants - List tenants with their settings (admin token only)
+pub async fn get_tenants(State(state): State<ProxyState>, headers: HeaderMap) -> Response {
+    if !is_admin(&state, &headers) {
+        return admin_required();
+    }
+
+    let mut tenants = Vec::new();
+    for id in state.tenant_ids().await {
+        let Some(tenant) = state.existing_tenant(&id).await else {
+            continue;
+        };
+        tenants.push(TenantSummary {
+            id,
+            settings: tenant_settings(&tenant).await,
+            activity_count: tenant.get_activity_count().await,
+        });
+    }
+
+    Json(TenantsResponse { tenants }).into_response()
+}
+
 /// GET /api/mode - Get current privacy mode
-pub async fn get_mode(State(state): State<ProxyState>) -> Json<PrivacyMode> {
+pub async fn get_mode(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
     let mode = state.get_mode().await;
-    Json(mode)
+    Json(mode).into_response()
 }
 
-/// PUT /api/mode - Set privacy mode
+/// PUT /api/mode - Set privacy mode (only if override allowed)
 pub async fn set_mode(
     State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
     Json(request): Json<SetModeRequest>,
-) -> impl IntoResponse {
-    state.set_mode(request.mode).await;
-    (StatusCode::OK, Json(request.mode))
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
+    match state.set_mode(request.mode).await {
+        Ok(_) => (StatusCode::OK, Json(request.mode)).into_response(),
+        Err(e) => locked(e),
+    }
 }
 
 /// GET /api/status - Get service status
-pub async fn get_status(State(state): State<ProxyState>) -> Json<StatusResponse> {
+pub async fn get_status(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
     let mode = state.get_mode().await;
     let detection_method = state.get_detection_method().await;
     let allow_override = state.get_allow_override().await;
@@ -67,49 +211,67 @@ pub async fn get_status(State(state): State<ProxyState>) -> Json<StatusResponse>
     
     Json(StatusResponse {
         status: "healthy".to_string(),
+        tenant: state.tenant_id().to_string(),
         mode,
         detection_method,
         allow_override,
         last_updated: Utc::now(),
         activity_count,
-    })
+    }).into_response()
 }
 
 /// GET /api/activity - Get recent activity log
-pub async fn get_activity(State(state): State<ProxyState>) -> Json<ActivityResponse> {
+pub async fn get_activity(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
     let entries = state.get_recent_activity(20).await;
     let total_count = state.get_activity_count().await;
     
     Json(ActivityResponse {
         entries,
         total_count,
-    })
+    }).into_response()
 }
 
 /// GET /api/detection - Get current detection method
-pub async fn get_detection_method(State(state): State<ProxyState>) -> Json<DetectionMethod> {
+pub async fn get_detection_method(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
     let method = state.get_detection_method().await;
-    Json(method)
+    Json(method).into_response()
 }
 
 /// PUT /api/detection - Set detection method (only if override allowed)
 pub async fn set_detection_method(
     State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
     Json(request): Json<SetDetectionMethodRequest>,
-) -> impl IntoResponse {
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
     match state.set_detection_method(request.method).await {
         Ok(_) => (StatusCode::OK, Json(request.method)).into_response(),
-        Err(e) => (
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({
-                "error": e
-            })),
-        ).into_response(),
+        Err(e) => locked(e),
     }
 }
 
 /// Combined settings request/response
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, PartialEq)]
 pub struct Settings {
     pub routing: RoutingMode,
     pub detection: DetectionMethod,
@@ -117,38 +279,117 @@ pub struct Settings {
 }
 
 /// GET /api/settings - Get all current settings
-pub async fn get_settings(State(state): State<ProxyState>) -> Json<Settings> {
-    let routing = state.get_routing_mode().await;
-    let detection = state.get_detection_method().await;
-    let privacy = state.get_mode().await;
-    
-    Json(Settings {
-        routing,
-        detection,
-        privacy,
-    })
+pub async fn get_settings(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
+) -> Response {
+    match selected_tenant(state, &headers, &query).await {
+        Ok(state) => Json(tenant_settings(&state).await).into_response(),
+        Err(response) => response,
+    }
 }
 
 /// PUT /api/settings - Update all settings at once
+///
+/// A tenant whose profile disallows overrides can't change any of them.
 pub async fn set_settings(
     State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantQuery>,
     Json(request): Json<Settings>,
-) -> impl IntoResponse {
+) -> Response {
+    let state = match selected_tenant(state, &headers, &query).await {
+        Ok(state) => state,
+        Err(response) => return response,
+    };
+
+    // Refuse before changing anything, so a locked tenant isn't half-updated
+    if !state.get_allow_override().await && tenant_settings(&state).await != request {
+        return locked("Settings are locked by profile configuration".to_string());
+    }
+
     // Update routing mode
-    state.set_routing_mode(request.routing).await;
+    if let Err(e) = state.set_routing_mode(request.routing).await {
+        return locked(e);
+    }
     
-    // Update detection method (only if override allowed)
+    // Update detection method
     if let Err(e) = state.set_detection_method(request.detection).await {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({
-                "error": e
-            })),
-        ).into_response();
+        return locked(e);
     }
     
     // Update privacy mode
-    state.set_mode(request.privacy).await;
+    if let Err(e) = state.set_mode(request.privacy).await {
+        return locked(e);
+    }
     
     (StatusCode::OK, Json(request)).into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{ProfileSource, DEFAULT_CACHE_TTL};
+    use crate::registry::ProviderRegistry;
+    use axum::http::HeaderValue;
+
+    fn state() -> ProxyState {
+        ProxyState::new(
+            "http://localhost:8089".to_string(),
+            None,
+            ProviderRegistry::builtin(),
+            ProfileSource::new("http://localhost:8088".to_string(), None, None, false, DEFAULT_CACHE_TTL),
+        )
+        .with_admin_token(Some("admin-token".to_string()))
+    }
+
+    fn caller(api_key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_str(&format!("Bearer {}", api_key)).unwrap());
+        headers
+    }
+
+    fn query(tenant: Option<&str>) -> TenantQuery {
+        TenantQuery {
+            tenant: tenant.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caller_gets_own_tenant() {
+        let state = state();
+        let (own, _) = tenant::resolve(&state, &caller("sk-or-v1-abc")).await.unwrap();
+
+        let selected = selected_tenant(state.clone(), &caller("sk-or-v1-abc"), &query(None)).await.ok().unwrap();
+        assert_eq!(selected.tenant_id(), own.tenant_id());
+        assert_ne!(selected.tenant_id(), state.tenant_id());
+    }
+
+    #[tokio::test]
+    async fn test_selecting_a_tenant_requires_admin_token() {
+        let state = state();
+        let (other, _) = tenant::resolve(&state, &caller("sk-or-v1-abc")).await.unwrap();
+        let other_id = Some(other.tenant_id());
+
+        // Another caller can't pick the tenant, with or without a wrong token
+        let mut headers = caller("sk-or-v1-xyz");
+        let response = selected_tenant(state.clone(), &headers, &query(other_id)).await.err().unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        headers.insert(ADMIN_TOKEN_HEADER, HeaderValue::from_static("guess"));
+        let response = selected_tenant(state.clone(), &headers, &query(other_id)).await.err().unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(get_tenants(State(state.clone()), headers.clone()).await.status(), StatusCode::FORBIDDEN);
+
+        // The admin can
+        headers.insert(ADMIN_TOKEN_HEADER, HeaderValue::from_static("admin-token"));
+        let selected = selected_tenant(state.clone(), &headers, &query(other_id)).await.ok().unwrap();
+        assert_eq!(selected.tenant_id(), other.tenant_id());
+        assert_eq!(get_tenants(State(state.clone()), headers.clone()).await.status(), StatusCode::OK);
+
+        // Nobody can without a configured token
+        let state = state.with_admin_token(None);
+        let response = selected_tenant(state.clone(), &headers, &query(other_id)).await.err().unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
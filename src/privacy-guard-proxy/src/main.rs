@@ -1,17 +1,28 @@
+mod anthropic;
+mod chat;
+mod completions;
 mod content;
+#[path = "../../guard-contract/mod.rs"]
+mod contract;
 mod control_panel;
 mod masking;
+mod profile;
 mod provider;
 mod proxy;
+mod registry;
+mod session;
 mod state;
+mod stream;
+mod tenant;
 
 use axum::{
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
 };
 use std::net::SocketAddr;
+use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{info, Level};
+use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 use state::ProxyState;
@@ -31,18 +42,75 @@ async fn main() {
     // Get configuration from environment
     let privacy_guard_url = std::env::var("PRIVACY_GUARD_URL")
         .unwrap_or_else(|_| "http://privacy-guard:8089".to_string());
+    let privacy_guard_token = std::env::var("PRIVACY_GUARD_TOKEN").ok().filter(|t| !t.is_empty());
     
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| "8090".to_string())
         .parse::<u16>()
         .expect("PORT must be a valid number");
 
+    let session_idle_timeout = std::env::var("MASKING_SESSION_IDLE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(session::DEFAULT_IDLE_TIMEOUT);
+
+    // LLM providers: config file if given, otherwise the built-in providers
+    let providers = match std::env::var("PROVIDERS_CONFIG") {
+        Ok(path) => registry::ProviderRegistry::load(&path).unwrap_or_else(|e| panic!("{}", e)),
+        Err(_) => registry::ProviderRegistry::builtin(),
+    };
+    let provider_names: Vec<&str> = providers.providers().iter().map(|p| p.name.as_str()).collect();
+    info!("LLM providers: {}", provider_names.join(", "));
+
+    // Role profiles: the role this instance serves, or the role of each request's token
+    let controller_url = std::env::var("CONTROLLER_URL")
+        .unwrap_or_else(|_| "http://controller:8088".to_string());
+    let proxy_role = std::env::var("PROXY_ROLE").ok().filter(|r| !r.is_empty());
+    let controller_token = std::env::var("CONTROLLER_TOKEN").ok().filter(|t| !t.is_empty());
+    // Requests without a role are refused unless explicitly allowed
+    let require_role = std::env::var("PROXY_REQUIRE_ROLE")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "false" | "0" | "no"))
+        .unwrap_or(true);
+    let profile_cache_ttl = std::env::var("PROFILE_CACHE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(profile::DEFAULT_CACHE_TTL);
+    let profiles = profile::ProfileSource::new(
+        controller_url.clone(),
+        proxy_role,
+        controller_token,
+        require_role,
+        profile_cache_ttl,
+    );
+
     // Initialize shared state
-    let state = ProxyState::new(privacy_guard_url.clone());
+    if privacy_guard_token.is_none() {
+        warn!("PRIVACY_GUARD_TOKEN is not set: Privacy Guard will refuse to unmask responses");
+    }
+    // Control panel requests on other tenants than the caller's need this token
+    let admin_token = std::env::var("PROXY_ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+    if admin_token.is_none() {
+        info!("PROXY_ADMIN_TOKEN not set, the control panel only shows each caller's own tenant");
+    }
+    let state = ProxyState::new(privacy_guard_url.clone(), privacy_guard_token, providers, profiles)
+        .with_admin_token(admin_token);
+
+    // Flush masking sessions of conversations that have gone quiet
+    session::spawn_idle_sweeper(state.clone(), session_idle_timeout);
     
     info!("Privacy Guard Proxy starting...");
     info!("Privacy Guard URL: {}", privacy_guard_url);
     info!("Default mode: Auto");
+    info!("Masking session idle timeout: {}s", session_idle_timeout.as_secs());
+    match state.profiles.instance_role() {
+        Some(role) => info!("Enforcing profile of role '{}' (controller: {})", role, controller_url),
+        None => info!("Enforcing profiles of role tokens (controller: {})", controller_url),
+    }
+    if !require_role {
+        warn!("PROXY_REQUIRE_ROLE=false: requests without a role token are not limited by any profile");
+    }
 
     // Build Control Panel routes
     let control_panel_routes = Router::new()
@@ -54,19 +122,30 @@ async fn main() {
         .route("/api/settings", get(control_panel::get_settings))
         .route("/api/settings", put(control_panel::set_settings))
         .route("/api/status", get(control_panel::get_status))
-        .route("/api/activity", get(control_panel::get_activity));
+        .route("/api/activity", get(control_panel::get_activity))
+        .route("/api/tenants", get(control_panel::get_tenants));
+
+    // Masking session lifecycle
+    let session_routes = Router::new()
+        .route("/api/sessions/:session_id", delete(session::end_session));
 
     // Build Proxy routes (support both /v1 and /api/v1 paths)
     let proxy_routes = Router::new()
         .route("/v1/chat/completions", post(proxy::proxy_chat_completions))
         .route("/v1/completions", post(proxy::proxy_completions))
+        .route("/v1/embeddings", post(proxy::proxy_embeddings))
+        // Anthropic Messages API
+        .route("/v1/messages", post(proxy::proxy_messages))
         // Add OpenRouter-compatible /api/v1 paths
         .route("/api/v1/chat/completions", post(proxy::proxy_chat_completions))
-        .route("/api/v1/completions", post(proxy::proxy_completions));
+        .route("/api/v1/completions", post(proxy::proxy_completions))
+        .route("/api/v1/embeddings", post(proxy::proxy_embeddings))
+        .route("/api/v1/messages", post(proxy::proxy_messages));
 
     // Combine routes
     let app = Router::new()
         .merge(control_panel_routes)
+        .merge(session_routes)
         .merge(proxy_routes)
         .layer(
             CorsLayer::new()
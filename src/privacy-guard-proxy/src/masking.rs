@@ -1,11 +1,15 @@
 // masking.rs - PII masking/unmasking integration with Privacy Guard service
 
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::contract::{
+    FlushSessionRequest, FlushSessionResponse, MaskRequest, MaskResponse, MaskRule,
+    ReidentifyTextRequest, ReidentifyTextResponse, FLUSH_SESSION_PATH, MASK_PATH, REIDENTIFY_PATH,
+};
+
 /// Masking context - stores PII mappings for a single request
 #[derive(Debug, Clone)]
 pub struct MaskingContext {
@@ -33,62 +37,41 @@ impl MaskingContext {
     }
 }
 
-/// Request to Privacy Guard /guard/mask endpoint
-#[derive(Debug, Serialize)]
-struct MaskRequest {
-    tenant_id: String,
-    text: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    session_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    detection_method: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    privacy_mode: Option<String>,
-}
-
-/// Response from Privacy Guard /guard/mask endpoint
-#[derive(Debug, Deserialize)]
-struct MaskResponse {
-    masked_text: String,
-    session_id: String,
-    #[serde(default)]
-    redactions: HashMap<String, u32>,
-}
-
-/// Request to Privacy Guard /guard/reidentify endpoint
-#[derive(Debug, Serialize)]
-struct ReidentifyRequest {
-    tenant_id: String,
-    masked_text: String,
-    session_id: String,
-}
-
-/// Response from Privacy Guard /guard/reidentify endpoint
-#[derive(Debug, Deserialize)]
-struct ReidentifyResponse {
-    original_text: String,
+/// Caller settings sent to Privacy Guard with every text to mask
+#[derive(Debug, Clone, Default)]
+pub struct MaskSettings {
+    /// Detection method: "rules", "ai", or "hybrid"
+    pub detection_method: Option<String>,
+    /// Privacy mode: "auto", "service-bypass", or "strict"
+    pub privacy_mode: Option<String>,
+    /// Detection rules of the caller's role profile
+    pub rules: Vec<MaskRule>,
 }
 
 /// Mask a message using Privacy Guard service
 ///
-/// Returns (masked_text, session_id) where session_id is used for reidentification
+/// Mappings are added to `session_id` when given, otherwise to a new session.
+/// Returns the masked text, the entity counts and the session_id used for
+/// reidentification
 pub async fn mask_message(
     privacy_guard_url: &str,
     message: &str,
     tenant_id: &str,
+    session_id: Option<&str>,
     client: &Client,
-    detection_method: Option<String>,
-    privacy_mode: Option<String>,
-) -> Result<(String, String), String> {
+    settings: &MaskSettings,
+) -> Result<MaskResponse, String> {
     let request = MaskRequest {
         tenant_id: tenant_id.to_string(),
         text: message.to_string(),
-        session_id: None,
-        detection_method,
-        privacy_mode,
+        session_id: session_id.map(str::to_string),
+        mode: None,
+        detection_method: settings.detection_method.clone(),
+        privacy_mode: settings.privacy_mode.clone(),
+        rules: settings.rules.clone(),
     };
 
-    let url = format!("{}/guard/mask", privacy_guard_url);
+    let url = format!("{}{}", privacy_guard_url, MASK_PATH);
     
     let response = client
         .post(&url)
@@ -103,35 +86,38 @@ pub async fn mask_message(
         return Err(format!("Privacy Guard /guard/mask failed: {} - {}", status, body));
     }
 
-    let mask_response: MaskResponse = response
+    response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse /guard/mask response: {}", e))?;
-
-    Ok((mask_response.masked_text, mask_response.session_id))
+        .map_err(|e| format!("Failed to parse /guard/mask response: {}", e))
 }
 
 /// Unmask a response using Privacy Guard service
 ///
+/// Every pseudonym/FPE token issued in `session_id` is restored in one call.
+/// Privacy Guard only reidentifies for callers with a bearer token.
 /// Returns unmasked_text
 pub async fn unmask_response(
     privacy_guard_url: &str,
+    privacy_guard_token: Option<&str>,
     masked_text: &str,
     tenant_id: &str,
     session_id: &str,
     client: &Client,
 ) -> Result<String, String> {
-    let request = ReidentifyRequest {
+    let request = ReidentifyTextRequest {
         tenant_id: tenant_id.to_string(),
         masked_text: masked_text.to_string(),
         session_id: session_id.to_string(),
     };
 
-    let url = format!("{}/guard/reidentify", privacy_guard_url);
+    let url = format!("{}{}", privacy_guard_url, REIDENTIFY_PATH);
     
-    let response = client
-        .post(&url)
-        .json(&request)
+    let mut request = client.post(&url).json(&request);
+    if let Some(token) = privacy_guard_token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
         .send()
         .await
         .map_err(|e| format!("Failed to call Privacy Guard /guard/reidentify: {}", e))?;
@@ -142,7 +128,7 @@ pub async fn unmask_response(
         return Err(format!("Privacy Guard /guard/reidentify failed: {} - {}", status, body));
     }
 
-    let reidentify_response: ReidentifyResponse = response
+    let reidentify_response: ReidentifyTextResponse = response
         .json()
         .await
         .map_err(|e| format!("Failed to parse /guard/reidentify response: {}", e))?;
@@ -150,6 +136,45 @@ pub async fn unmask_response(
     Ok(reidentify_response.original_text)
 }
 
+/// Flush a masking session in Privacy Guard service
+///
+/// Returns false if Privacy Guard had no such session
+pub async fn flush_session(
+    privacy_guard_url: &str,
+    session_id: &str,
+    client: &Client,
+) -> Result<bool, String> {
+    let request = FlushSessionRequest {
+        session_id: session_id.to_string(),
+    };
+
+    let url = format!("{}{}", privacy_guard_url, FLUSH_SESSION_PATH);
+
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to call Privacy Guard /internal/flush-session: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Privacy Guard /internal/flush-session failed: {} - {}", status, body));
+    }
+
+    let flush_response: FlushSessionResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse /internal/flush-session response: {}", e))?;
+
+    Ok(flush_response.status == "flushed")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,6 +217,29 @@ mod tests {
         assert_eq!(ctx.get_original("PHONE_001"), Some(&"+1-555-1234".to_string()));
     }
 
-    // Note: Integration tests for mask_message() and unmask_response() 
+    #[test]
+    fn test_reidentify_request_matches_guard_contract() {
+        let request = ReidentifyTextRequest {
+            tenant_id: "org1".to_string(),
+            session_id: "sess_abc".to_string(),
+            masked_text: "Hello PERSON_a1b2c3d4e5f6a7b8".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "tenant_id": "org1",
+                "session_id": "sess_abc",
+                "masked_text": "Hello PERSON_a1b2c3d4e5f6a7b8"
+            })
+        );
+
+        let response: ReidentifyTextResponse =
+            serde_json::from_str(r#"{"original_text": "Hello John Doe", "restored": 1}"#).unwrap();
+        assert_eq!(response.original_text, "Hello John Doe");
+        assert_eq!(response.restored, 1);
+    }
+
+    // Note: Integration tests for mask_message() and unmask_response()
     // require a running Privacy Guard service and should be in tests/integration/
 }
@@ -0,0 +1,534 @@
+// profile.rs - Role profile provider/model enforcement
+//
+// A role's profile (controller GET /profiles/{role}) limits which LLM
+// providers and models the role may use. The proxy enforces those limits on
+// every request. The role is the one this proxy instance serves (PROXY_ROLE),
+// or else the role claim of an X-Goose-Role-Token JWT sent by the client. The
+// profile is fetched from the controller with that JWT (or the proxy's service
+// token), so the controller verifies the token and the role, and is cached
+// for a short time.
+//
+// A profile's `privacy.local_only` keeps the role's requests on the local
+// provider (see registry.rs), its `privacy.rules` are sent to Privacy Guard
+// with every text to mask, and the rest of its `privacy` block sets the
+// defaults of the role's proxy settings (see tenant.rs).
+
+use axum::http::HeaderMap;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::contract::MaskRule;
+
+/// Header carrying the caller's JWT
+pub const ROLE_TOKEN_HEADER: &str = "x-goose-role-token";
+
+/// Default time a fetched profile is reused
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Keycloak default realm roles that never identify an org role
+const DEFAULT_REALM_ROLES: [&str; 2] = ["offline_access", "uma_authorization"];
+
+/// Provider and model of a profile's primary/planner/worker configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelConfig {
+    pub provider: String,
+    pub model: String,
+}
+
+/// Provider section of a role profile
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderPolicy {
+    pub primary: ModelConfig,
+    #[serde(default)]
+    pub planner: Option<ModelConfig>,
+    #[serde(default)]
+    pub worker: Option<ModelConfig>,
+    /// Empty list means all providers allowed
+    #[serde(default)]
+    pub allowed_providers: Vec<String>,
+    /// Takes precedence over allowed_providers
+    #[serde(default)]
+    pub forbidden_providers: Vec<String>,
+}
+
+impl ProviderPolicy {
+    /// Check a request's provider and model against the profile
+    ///
+    /// When the profile configures models for the provider (primary, planner
+    /// or worker), the request must use one of them; `models` are the names
+    /// the request model is known by (as sent, and as forwarded upstream).
+    pub fn check(&self, provider: &str, models: &[&str]) -> Result<(), String> {
+        if self.forbidden_providers.iter().any(|p| p.eq_ignore_ascii_case(provider)) {
+            return Err(format!("Provider '{}' is forbidden for this role", provider));
+        }
+        if !self.allowed_providers.is_empty()
+            && !self.allowed_providers.iter().any(|p| p.eq_ignore_ascii_case(provider))
+        {
+            return Err(format!(
+                "Provider '{}' is not in the role's allowed providers: {}",
+                provider,
+                self.allowed_providers.join(", ")
+            ));
+        }
+
+        let configured: Vec<&str> = [Some(&self.primary), self.planner.as_ref(), self.worker.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|c| c.provider.eq_ignore_ascii_case(provider))
+            .map(|c| c.model.as_str())
+            .collect();
+        if !configured.is_empty() && !models.iter().any(|m| configured.contains(m)) {
+            return Err(format!(
+                "Model '{}' is not configured for provider '{}' in the role's profile (allowed: {})",
+                models.first().copied().unwrap_or_default(),
+                provider,
+                configured.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Privacy section of a role profile
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PrivacyPolicy {
+    /// Detection mode ("rules", "ner", "hybrid")
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Strictness level ("strict", "moderate", "permissive")
+    #[serde(default)]
+    pub strictness: Option<String>,
+    /// Whether users may change routing, privacy mode and detection method
+    #[serde(default)]
+    pub allow_override: Option<bool>,
+    /// Never send the role's requests to cloud providers
+    #[serde(default)]
+    pub local_only: Option<bool>,
+    /// Role-specific detection rules
+    #[serde(default)]
+    pub rules: Vec<ProfileRule>,
+}
+
+/// Detection rule of a role profile (`privacy.rules`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileRule {
+    pub pattern: String,
+    /// Placeholder such as "[EMP_ID]"; Privacy Guard masks matches with its own tokens
+    #[serde(default)]
+    pub replacement: String,
+    /// Entity type of matches, e.g. "EMPLOYEE_ID"
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl PrivacyPolicy {
+    /// Requests must be routed to the local provider
+    pub fn is_local_only(&self) -> bool {
+        self.local_only.unwrap_or(false)
+    }
+
+    /// The role's rules, as sent to Privacy Guard
+    ///
+    /// A rule's entity type is its category, or else its replacement without
+    /// brackets ("[EMP_ID]" is EMP_ID). Rules with neither are left out.
+    pub fn mask_rules(&self) -> Vec<MaskRule> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let entity_type = rule
+                    .category
+                    .as_deref()
+                    .unwrap_or_else(|| rule.replacement.trim_start_matches('[').trim_end_matches(']'))
+                    .trim();
+                (!entity_type.is_empty()).then(|| MaskRule {
+                    entity_type: entity_type.to_string(),
+                    regex: rule.pattern.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Provider and privacy limits of the role a request is made for
+#[derive(Debug, Clone)]
+pub struct RolePolicy {
+    pub role: String,
+    pub providers: ProviderPolicy,
+    pub privacy: PrivacyPolicy,
+}
+
+/// The parts of a profile the proxy reads
+#[derive(Deserialize)]
+struct ProfileDocument {
+    role: String,
+    providers: ProviderPolicy,
+    #[serde(default)]
+    privacy: PrivacyPolicy,
+}
+
+/// Why a request's profile could not be determined
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileError {
+    /// The token or role was rejected (malformed JWT, 401/403/404 from the controller)
+    Rejected(String),
+    /// The controller could not be reached or failed
+    Unavailable(String),
+}
+
+impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::Rejected(msg) => write!(f, "{}", msg),
+            ProfileError::Unavailable(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Cache key of a fetched profile: role and the token it was fetched with
+type CacheKey = (String, Option<String>);
+
+/// Fetches and caches role profiles from the controller
+pub struct ProfileSource {
+    controller_url: String,
+    /// Role this proxy instance serves, if any
+    role: Option<String>,
+    /// Token used to fetch the instance role's profile
+    service_token: Option<String>,
+    /// Refuse requests whose role can't be determined (otherwise they go
+    /// unlimited)
+    require_role: bool,
+    ttl: Duration,
+    /// Profiles with the time they were fetched
+    cache: RwLock<HashMap<CacheKey, (Instant, Arc<RolePolicy>)>>,
+}
+
+impl ProfileSource {
+    pub fn new(
+        controller_url: String,
+        role: Option<String>,
+        service_token: Option<String>,
+        require_role: bool,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            controller_url: controller_url.trim_end_matches('/').to_string(),
+            role,
+            service_token,
+            require_role,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Role this proxy instance serves, if any
+    pub fn instance_role(&self) -> Option<&str> {
+        self.role.as_deref()
+    }
+
+    /// Provider limits for a request; None when no role applies (only if
+    /// roles aren't required)
+    pub async fn policy_for(&self, headers: &HeaderMap) -> Result<Option<Arc<RolePolicy>>, ProfileError> {
+        let Some((role, token)) = self.role_and_token(headers)? else {
+            return Ok(None);
+        };
+
+        let key = (role, token);
+        if let Some((fetched_at, policy)) = self.cache.read().await.get(&key) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(Some(policy.clone()));
+            }
+        }
+
+        let policy = Arc::new(self.fetch(&key.0, key.1.as_deref()).await?);
+        let mut cache = self.cache.write().await;
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < self.ttl);
+        cache.insert(key, (Instant::now(), policy.clone()));
+        Ok(Some(policy))
+    }
+
+    /// Role of a request and the token to fetch its profile with
+    ///
+    /// The instance role wins over a client token, so a per-role proxy can't
+    /// be used under another role. Without either, the request is rejected
+    /// unless roles aren't required.
+    fn role_and_token(&self, headers: &HeaderMap) -> Result<Option<(String, Option<String>)>, ProfileError> {
+        if let Some(role) = &self.role {
+            return Ok(Some((role.clone(), self.service_token.clone())));
+        }
+
+        let Some(token) = headers
+            .get(ROLE_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().trim_start_matches("Bearer ").trim())
+            .filter(|v| !v.is_empty())
+        else {
+            if self.require_role {
+                return Err(ProfileError::Rejected(format!(
+                    "No role for request: send {} (or run the proxy with PROXY_ROLE)",
+                    ROLE_TOKEN_HEADER
+                )));
+            }
+            return Ok(None);
+        };
+
+        let role = role_from_jwt(token).map_err(ProfileError::Rejected)?;
+        // The role becomes part of the controller URL
+        if !role.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(ProfileError::Rejected(format!("Invalid role '{}' in role token", role)));
+        }
+        Ok(Some((role, Some(token.to_string()))))
+    }
+
+    /// GET /profiles/{role} from the controller
+    async fn fetch(&self, role: &str, token: Option<&str>) -> Result<RolePolicy, ProfileError> {
+        let url = format!("{}/profiles/{}", self.controller_url, role);
+        let client = reqwest::Client::new();
+
+        let mut request = client.get(&url);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ProfileError::Unavailable(format!("Failed to fetch profile for role '{}': {}", role, e)))?;
+
+        let status = response.status();
+        if matches!(status.as_u16(), 401 | 403 | 404) {
+            return Err(ProfileError::Rejected(format!(
+                "Controller rejected profile request for role '{}': {}",
+                role, status
+            )));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProfileError::Unavailable(format!(
+                "Controller returned {} for role '{}': {}",
+                status, role, body
+            )));
+        }
+
+        let profile: ProfileDocument = response
+            .json()
+            .await
+            .map_err(|e| ProfileError::Unavailable(format!("Failed to parse profile for role '{}': {}", role, e)))?;
+
+        Ok(RolePolicy {
+            role: profile.role,
+            providers: profile.providers,
+            privacy: profile.privacy,
+        })
+    }
+}
+
+/// Org role claimed by a JWT, without verifying it
+///
+/// Same rules as the controller: the `role` claim, otherwise the first
+/// non-default realm role, otherwise the first group. The controller verifies
+/// the token when the profile is fetched with it.
+pub fn role_from_jwt(token: &str) -> Result<String, String> {
+    let payload = token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| "Role token is not a JWT".to_string())?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|_| "Role token payload is not valid base64url".to_string())?;
+    let claims: serde_json::Value =
+        serde_json::from_slice(&payload).map_err(|_| "Role token payload is not JSON".to_string())?;
+
+    let explicit = claims
+        .get("role")
+        .and_then(|r| r.as_str())
+        .filter(|r| !r.is_empty());
+    let realm_role = claims["realm_access"]["roles"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r.as_str())
+        .find(|r| !DEFAULT_REALM_ROLES.contains(r) && !r.starts_with("default-roles-"));
+    let group = claims["groups"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|g| g.as_str())
+        .map(|g| g.trim_start_matches('/'))
+        .find(|g| !g.is_empty());
+
+    explicit
+        .or(realm_role)
+        .or(group)
+        .map(str::to_string)
+        .ok_or_else(|| "Role token has no role claim".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    fn policy(value: serde_json::Value) -> ProviderPolicy {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn jwt(claims: serde_json::Value) -> String {
+        format!("eyJhbGciOiJSUzI1NiJ9.{}.sig", URL_SAFE_NO_PAD.encode(claims.to_string()))
+    }
+
+    fn source(role: Option<&str>) -> ProfileSource {
+        ProfileSource::new(
+            "http://controller:8088/".to_string(),
+            role.map(str::to_string),
+            Some("svc-token".to_string()),
+            true,
+            DEFAULT_CACHE_TTL,
+        )
+    }
+
+    #[test]
+    fn test_forbidden_provider_wins() {
+        let policy = policy(json!({
+            "primary": {"provider": "openrouter", "model": "anthropic/claude-3.5-sonnet"},
+            "allowed_providers": ["openrouter", "openai"],
+            "forbidden_providers": ["OpenAI"]
+        }));
+
+        assert!(policy.check("openrouter", &["anthropic/claude-3.5-sonnet"]).is_ok());
+        assert!(policy.check("openai", &["gpt-4o"]).unwrap_err().contains("forbidden"));
+        assert!(policy.check("anthropic", &["claude-sonnet-4"]).unwrap_err().contains("allowed providers"));
+    }
+
+    #[test]
+    fn test_configured_models_limit_provider() {
+        let policy = policy(json!({
+            "primary": {"provider": "openrouter", "model": "anthropic/claude-3.5-sonnet"},
+            "worker": {"provider": "openrouter", "model": "openai/gpt-4o-mini"},
+            "planner": {"provider": "ollama", "model": "llama3.2"}
+        }));
+
+        assert!(policy.check("openrouter", &["openai/gpt-4o-mini"]).is_ok());
+        let err = policy.check("openrouter", &["openai/gpt-4o"]).unwrap_err();
+        assert!(err.contains("'openai/gpt-4o'"));
+        assert!(err.contains("anthropic/claude-3.5-sonnet, openai/gpt-4o-mini"));
+
+        // Matches the model as forwarded once the routing prefix is stripped
+        assert!(policy.check("ollama", &["ollama/llama3.2", "llama3.2"]).is_ok());
+
+        // Empty allow-list and no configured model: any model
+        assert!(policy.check("openai", &["gpt-4o"]).is_ok());
+    }
+
+    #[test]
+    fn test_profile_privacy() {
+        let providers = json!({"primary": {"provider": "ollama", "model": "llama3.2"}});
+
+        let profile: ProfileDocument = serde_json::from_value(json!({
+            "role": "legal",
+            "providers": providers,
+            "privacy": {"mode": "hybrid", "strictness": "strict", "local_only": true, "retention_days": 0}
+        }))
+        .unwrap();
+        assert!(profile.privacy.is_local_only());
+        assert_eq!(profile.privacy.mode.as_deref(), Some("hybrid"));
+        assert_eq!(profile.privacy.strictness.as_deref(), Some("strict"));
+
+        // Profiles without a privacy section, or without local_only, allow cloud providers
+        let profile: ProfileDocument =
+            serde_json::from_value(json!({"role": "finance", "providers": providers})).unwrap();
+        assert!(!profile.privacy.is_local_only());
+        assert!(profile.privacy.mask_rules().is_empty());
+    }
+
+    #[test]
+    fn test_profile_mask_rules() {
+        let privacy: PrivacyPolicy = serde_json::from_value(json!({
+            "mode": "hybrid",
+            "rules": [
+                {"pattern": "\\b[A-Z]{2}\\d{6,8}\\b", "replacement": "[EMP_ID]", "category": "EMPLOYEE_ID"},
+                {"pattern": "\\bPRJ-\\d{4}\\b", "replacement": "[PROJECT_CODENAME]"},
+                {"pattern": "\\d+", "replacement": "[]"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            privacy.mask_rules(),
+            vec![
+                MaskRule { entity_type: "EMPLOYEE_ID".to_string(), regex: "\\b[A-Z]{2}\\d{6,8}\\b".to_string() },
+                MaskRule { entity_type: "PROJECT_CODENAME".to_string(), regex: "\\bPRJ-\\d{4}\\b".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_role_from_jwt() {
+        assert_eq!(role_from_jwt(&jwt(json!({"sub": "u1", "role": "finance"}))).unwrap(), "finance");
+        assert_eq!(
+            role_from_jwt(&jwt(json!({"realm_access": {"roles": ["default-roles-dev", "offline_access", "legal"]}}))).unwrap(),
+            "legal"
+        );
+        assert_eq!(role_from_jwt(&jwt(json!({"groups": ["/hr"]}))).unwrap(), "hr");
+
+        assert!(role_from_jwt("not-a-jwt").is_err());
+        assert!(role_from_jwt("a.!!!.c").is_err());
+        assert!(role_from_jwt(&jwt(json!({"sub": "u1"}))).unwrap_err().contains("no role"));
+    }
+
+    #[test]
+    fn test_role_resolution() {
+        let token = jwt(json!({"role": "finance"}));
+        let mut headers = HeaderMap::new();
+        headers.insert(ROLE_TOKEN_HEADER, HeaderValue::from_str(&format!("Bearer {}", token)).unwrap());
+
+        // Client token names the role
+        assert_eq!(
+            source(None).role_and_token(&headers).unwrap(),
+            Some(("finance".to_string(), Some(token.clone())))
+        );
+
+        // A per-role proxy ignores client tokens
+        assert_eq!(
+            source(Some("manager")).role_and_token(&headers).unwrap(),
+            Some(("manager".to_string(), Some("svc-token".to_string())))
+        );
+
+        // No role: rejected, unless roles aren't required
+        assert!(matches!(source(None).role_and_token(&HeaderMap::new()), Err(ProfileError::Rejected(_))));
+        let optional = ProfileSource::new("http://controller:8088".to_string(), None, None, false, DEFAULT_CACHE_TTL);
+        assert_eq!(optional.role_and_token(&HeaderMap::new()).unwrap(), None);
+
+        let mut bad = HeaderMap::new();
+        bad.insert(ROLE_TOKEN_HEADER, HeaderValue::from_static("garbage"));
+        assert!(matches!(source(None).role_and_token(&bad), Err(ProfileError::Rejected(_))));
+
+        let mut traversal = HeaderMap::new();
+        let token = jwt(json!({"role": "../admin/profiles"}));
+        traversal.insert(ROLE_TOKEN_HEADER, HeaderValue::from_str(&token).unwrap());
+        assert!(matches!(source(None).role_and_token(&traversal), Err(ProfileError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cached_policy_is_reused() {
+        let source = source(Some("finance"));
+        let cached = Arc::new(RolePolicy {
+            role: "finance".to_string(),
+            providers: policy(json!({"primary": {"provider": "openrouter", "model": "m"}})),
+            privacy: PrivacyPolicy::default(),
+        });
+        source.cache.write().await.insert(
+            ("finance".to_string(), Some("svc-token".to_string())),
+            (Instant::now(), cached.clone()),
+        );
+
+        let policy = source.policy_for(&HeaderMap::new()).await.unwrap().unwrap();
+        assert!(Arc::ptr_eq(&policy, &cached));
+        assert_eq!(source.controller_url, "http://controller:8088");
+    }
+}
@@ -1,7 +1,42 @@
-// provider.rs - LLM provider detection and routing
+// provider.rs - Built-in LLM providers and chat API flavors
 
 use serde::{Deserialize, Serialize};
 
+/// Request/response schema of a chat API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiFlavor {
+    /// OpenAI chat completions (`/v1/chat/completions`)
+    #[default]
+    OpenAI,
+    /// Anthropic Messages API (`/v1/messages`)
+    Anthropic,
+}
+
+impl ApiFlavor {
+    /// Chat endpoint of this API
+    pub fn chat_endpoint(&self) -> &'static str {
+        match self {
+            ApiFlavor::OpenAI => "/v1/chat/completions",
+            ApiFlavor::Anthropic => "/v1/messages",
+        }
+    }
+
+    /// Get API name as string
+    pub fn name(&self) -> &'static str {
+        match self {
+            ApiFlavor::OpenAI => "OpenAI",
+            ApiFlavor::Anthropic => "Anthropic",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 /// Supported LLM providers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LLMProvider {
@@ -11,21 +46,16 @@ pub enum LLMProvider {
 }
 
 impl LLMProvider {
-    /// Detect provider from API key format
+    /// API key prefix identifying this provider
     ///
     /// - sk-or-* → OpenRouter
-    /// - sk-ant-* → Anthropic  
+    /// - sk-ant-* → Anthropic
     /// - sk-* → OpenAI
-    pub fn from_api_key(api_key: &str) -> Self {
-        if api_key.starts_with("sk-or-") {
-            LLMProvider::OpenRouter
-        } else if api_key.starts_with("sk-ant-") {
-            LLMProvider::Anthropic
-        } else if api_key.starts_with("sk-") {
-            LLMProvider::OpenAI
-        } else {
-            // Default to OpenRouter for unknown formats
-            LLMProvider::OpenRouter
+    pub fn key_prefix(&self) -> &'static str {
+        match self {
+            LLMProvider::OpenRouter => "sk-or-",
+            LLMProvider::Anthropic => "sk-ant-",
+            LLMProvider::OpenAI => "sk-",
         }
     }
 
@@ -61,6 +91,15 @@ impl LLMProvider {
         matches!(self, LLMProvider::OpenRouter | LLMProvider::OpenAI)
     }
 
+    /// Get the chat API this provider speaks
+    pub fn api_flavor(&self) -> ApiFlavor {
+        if self.is_openai_compatible() {
+            ApiFlavor::OpenAI
+        } else {
+            ApiFlavor::Anthropic
+        }
+    }
+
     /// Get the full URL for chat completions
     pub fn chat_completions_url(&self) -> String {
         format!("{}{}", self.base_url(), self.chat_completions_endpoint())
@@ -92,30 +131,10 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_detect_openrouter() {
-        let api_key = "sk-or-v1-1234567890abcdef";
-        assert_eq!(LLMProvider::from_api_key(api_key), LLMProvider::OpenRouter);
-    }
-
-    #[test]
-    fn test_detect_anthropic() {
-        let api_key = "sk-ant-REDACTED";
-        assert_eq!(LLMProvider::from_api_key(api_key), LLMProvider::Anthropic);
-    }
-
-    #[test]
-    fn test_detect_openai() {
-        let api_key = "sk-proj-1234567890abcdef";
-        assert_eq!(LLMProvider::from_api_key(api_key), LLMProvider::OpenAI);
-        
-        let api_key2 = "sk-1234567890abcdef";
-        assert_eq!(LLMProvider::from_api_key(api_key2), LLMProvider::OpenAI);
-    }
-
-    #[test]
-    fn test_unknown_defaults_to_openrouter() {
-        let api_key = "unknown-format-key";
-        assert_eq!(LLMProvider::from_api_key(api_key), LLMProvider::OpenRouter);
+    fn test_key_prefixes() {
+        assert_eq!(LLMProvider::OpenRouter.key_prefix(), "sk-or-");
+        assert_eq!(LLMProvider::Anthropic.key_prefix(), "sk-ant-");
+        assert_eq!(LLMProvider::OpenAI.key_prefix(), "sk-");
     }
 
     #[test]
@@ -149,6 +168,17 @@ mod tests {
         assert!(!LLMProvider::Anthropic.is_openai_compatible());
     }
 
+    #[test]
+    fn test_api_flavor() {
+        assert_eq!(LLMProvider::OpenRouter.api_flavor(), ApiFlavor::OpenAI);
+        assert_eq!(LLMProvider::OpenAI.api_flavor(), ApiFlavor::OpenAI);
+        assert_eq!(LLMProvider::Anthropic.api_flavor(), ApiFlavor::Anthropic);
+        assert_eq!(
+            LLMProvider::Anthropic.chat_completions_endpoint(),
+            ApiFlavor::Anthropic.chat_endpoint()
+        );
+    }
+
     #[test]
     fn test_provider_names() {
         assert_eq!(LLMProvider::OpenRouter.name(), "OpenRouter");
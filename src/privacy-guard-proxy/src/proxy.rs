@@ -1,41 +1,439 @@
 use axum::{
     extract::State,
     http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde_json::Value;
+use std::collections::HashMap;
 
+use crate::anthropic;
+use crate::chat;
+use crate::completions;
 use crate::content::ContentType;
-use crate::masking::{mask_message, unmask_response};
-use crate::provider::LLMProvider;
+use crate::contract::is_valid_pseudonym;
+use crate::masking::{mask_message, unmask_response, MaskSettings};
+use crate::profile::{ProfileError, RolePolicy};
+use crate::provider::ApiFlavor;
+use crate::registry::{AuthStyle, ProviderEntry, PROVIDER_HEADER};
+use crate::session;
 use crate::state::{PrivacyMode, RoutingMode, ProxyState};
+use crate::stream::{self, StreamUnmask};
+use crate::tenant;
 
 /// POST /v1/chat/completions - Proxy chat completions to LLM with PII masking
 pub async fn proxy_chat_completions(
     State(state): State<ProxyState>,
     headers: HeaderMap,
-    Json(mut body): Json<Value>,
+    Json(body): Json<Value>,
 ) -> impl IntoResponse {
-    // LEVEL 1: Check routing mode first
-    let routing_mode = state.get_routing_mode().await;
-    let privacy_mode = state.get_mode().await;
-    let detection_method = state.get_detection_method().await;
-    let privacy_guard_url = state.privacy_guard_url.clone();
+    proxy_request(state, headers, body, ClientApi::Chat(ApiFlavor::OpenAI)).await
+}
+
+/// POST /v1/messages - Proxy Anthropic Messages API requests to LLM with PII masking
+pub async fn proxy_messages(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    proxy_request(state, headers, body, ClientApi::Chat(ApiFlavor::Anthropic)).await
+}
+
+/// POST /v1/completions - Proxy legacy completions to LLM with PII masking
+pub async fn proxy_completions(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    proxy_request(state, headers, body, ClientApi::Completions).await
+}
+
+/// POST /v1/embeddings - Proxy embeddings to LLM with PII masking
+pub async fn proxy_embeddings(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    proxy_request(state, headers, body, ClientApi::Embeddings).await
+}
+
+/// API a client request is made in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClientApi {
+    /// Chat completions or Messages, in the client's flavor
+    Chat(ApiFlavor),
+    /// Legacy text completions (OpenAI)
+    Completions,
+    /// Embeddings (OpenAI)
+    Embeddings,
+}
+
+impl ClientApi {
+    /// Flavor of the client's payloads
+    fn flavor(self) -> ApiFlavor {
+        match self {
+            ClientApi::Chat(flavor) => flavor,
+            ClientApi::Completions | ClientApi::Embeddings => ApiFlavor::OpenAI,
+        }
+    }
+
+    /// Provider endpoint of a request
+    fn endpoint(self, provider: &ProviderEntry, model: &str) -> String {
+        match self {
+            ClientApi::Chat(_) => provider.chat_path(model),
+            ClientApi::Completions => "/v1/completions".to_string(),
+            ClientApi::Embeddings => "/v1/embeddings".to_string(),
+        }
+    }
+
+    /// Activity log action of a request
+    fn action(self) -> &'static str {
+        match self {
+            ClientApi::Chat(_) => "chat_completion",
+            ClientApi::Completions => "completion",
+            ClientApi::Embeddings => "embedding",
+        }
+    }
+}
+
+impl std::fmt::Display for ClientApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientApi::Chat(flavor) => write!(f, "{}", flavor),
+            ClientApi::Completions => write!(f, "OpenAI completions"),
+            ClientApi::Embeddings => write!(f, "OpenAI embeddings"),
+        }
+    }
+}
+
+/// Provider and endpoint a request is sent to
+struct Upstream {
+    provider: ProviderEntry,
+    endpoint: String,
+}
+
+/// Select the provider for a request from the registry
+///
+/// A routing model prefix is stripped from `body.model`, and the role
+/// profile's and the provider's model allow-lists are enforced. Requests of
+/// local-only roles go to the registry's local provider.
+async fn select_upstream(
+    state: &ProxyState,
+    headers: &HeaderMap,
+    policy: Option<&RolePolicy>,
+    body: &mut Value,
+    content_type: &str,
+    endpoint: impl Fn(&ProviderEntry, &str) -> String,
+) -> Result<Upstream, Response> {
+    let explicit = headers.get(PROVIDER_HEADER).and_then(|v| v.to_str().ok());
+    let model = body.get("model").and_then(Value::as_str).map(str::to_string);
+    let api_key = client_api_key(headers);
+
+    let route = match state.providers.select(explicit, model.as_deref(), api_key.as_deref()) {
+        Ok(route) => route,
+        Err(e) => {
+            state.log_activity("provider_selection_error", content_type, e.clone()).await;
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": {
+                        "message": e,
+                        "type": "unknown_provider"
+                    }
+                })),
+            ).into_response());
+        }
+    };
+    
+    // Local-only roles never reach cloud providers
+    let route = match policy {
+        Some(policy) if policy.privacy.is_local_only() => match state.providers.local_route(model.as_deref()) {
+            Some(local) => {
+                state.log_activity(
+                    "local_routing",
+                    content_type,
+                    format!(
+                        "Role '{}' is local-only: {} -> {}",
+                        policy.role, route.provider.name, local.provider.name
+                    ),
+                ).await;
+                local
+            }
+            None => {
+                state.log_activity(
+                    "local_only_blocked",
+                    content_type,
+                    format!("Role '{}' is local-only but no local provider is configured", policy.role),
+                ).await;
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    Json(serde_json::json!({
+                        "error": {
+                            "message": format!(
+                                "The '{}' profile requires local-only processing, but no local provider is configured",
+                                policy.role
+                            ),
+                            "type": "local_only_unavailable"
+                        }
+                    })),
+                ).into_response());
+            }
+        },
+        _ => route,
+    };
+
+    let provider = route.provider.clone();
+    let upstream_model = route.model.unwrap_or_default();
     
+    // The role's profile limits providers and models
+    if let Some(policy) = policy {
+        let models = [model.as_deref().unwrap_or_default(), upstream_model.as_str()];
+        if let Err(reason) = policy.providers.check(&provider.name, &models) {
+            state.log_activity(
+                "profile_blocked",
+                content_type,
+                format!("Role '{}': {}", policy.role, reason),
+            ).await;
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": {
+                        "message": format!("Blocked by the '{}' profile: {}", policy.role, reason),
+                        "type": "provider_not_allowed"
+                    }
+                })),
+            ).into_response());
+        }
+    }
+    
+    if !provider.allows_model(&upstream_model) {
+        state.log_activity(
+            "model_blocked",
+            content_type,
+            format!("Model '{}' is not allowed for provider {}", upstream_model, provider.name),
+        ).await;
+        return Err(model_not_allowed(&upstream_model, &provider.name));
+    }
+    if model.as_deref().is_some_and(|model| model != upstream_model) {
+        body["model"] = Value::String(upstream_model.clone());
+    }
+
+    let endpoint = endpoint(&provider, &upstream_model);
+    state.log_activity(
+        "provider_detected",
+        content_type,
+        format!(
+            "Provider: {} (selected by {}), URL: {}{}",
+            provider.name, route.selected_by, provider.base_url, endpoint
+        ),
+    ).await;
+
+    Ok(Upstream { provider, endpoint })
+}
+
+/// Reroute a masked request to the local provider when its detections are sensitive
+///
+/// Applies the registry's `local.min_detections` and `local.entity_types` to
+/// the entity counts of masking. Requests already sent to the local provider,
+/// and registries without one, are left alone.
+async fn route_sensitive(
+    state: &ProxyState,
+    upstream: Upstream,
+    detections: &HashMap<String, usize>,
+    body: &mut Value,
+    content_type: &str,
+    endpoint: impl Fn(&ProviderEntry, &str) -> String,
+) -> Result<Upstream, Response> {
+    let Some(local) = state.providers.local() else {
+        return Ok(upstream);
+    };
+    if upstream.provider.name.eq_ignore_ascii_case(&local.provider) {
+        return Ok(upstream);
+    }
+    let Some(reason) = local.sensitive_reason(detections) else {
+        return Ok(upstream);
+    };
+
+    let model = body.get("model").and_then(Value::as_str).map(str::to_string);
+    let route = state
+        .providers
+        .local_route(model.as_deref())
+        .expect("local provider is configured");
+    let upstream_model = route.model.unwrap_or_default();
+
+    // Refuse rather than fall back to the cloud provider
+    if !route.provider.allows_model(&upstream_model) {
+        state.log_activity(
+            "model_blocked",
+            content_type,
+            format!(
+                "Sensitive request ({}) needs local provider {}, which does not allow model '{}'",
+                reason, route.provider.name, upstream_model
+            ),
+        ).await;
+        return Err(model_not_allowed(&upstream_model, &route.provider.name));
+    }
+    if model.as_deref() != Some(upstream_model.as_str()) {
+        body["model"] = Value::String(upstream_model.clone());
+    }
+
+    state.log_activity(
+        "local_routing",
+        content_type,
+        format!(
+            "Sensitive request ({}): {} -> {}",
+            reason, upstream.provider.name, route.provider.name
+        ),
+    ).await;
+
+    Ok(Upstream {
+        provider: route.provider.clone(),
+        endpoint: endpoint(route.provider, &upstream_model),
+    })
+}
+
+/// Response for a request whose role profile could not be determined
+pub(crate) async fn profile_error(state: &ProxyState, e: ProfileError, content_type: &str) -> Response {
+    state.log_activity("profile_error", content_type, e.to_string()).await;
+    let (status, error_type) = match e {
+        ProfileError::Rejected(_) => (StatusCode::FORBIDDEN, "profile_rejected"),
+        ProfileError::Unavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, "profile_unavailable"),
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": e.to_string(),
+                "type": error_type
+            }
+        })),
+    ).into_response()
+}
+
+/// 403 response for a model outside a provider's allow-list
+fn model_not_allowed(model: &str, provider: &str) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({
+            "error": {
+                "message": format!("Model '{}' is not allowed for provider '{}'", model, provider),
+                "type": "model_not_allowed"
+            }
+        })),
+    ).into_response()
+}
+
+/// Refuse requests the provider can't serve in the client's API
+///
+/// Only chat requests are translated between APIs, and event streams are
+/// relayed as-is, so they can't be translated.
+async fn check_upstream_api(
+    state: &ProxyState,
+    upstream: &Upstream,
+    api: ClientApi,
+    is_stream: bool,
+    content_type: &str,
+) -> Result<(), Response> {
+    let client_flavor = api.flavor();
+    if !matches!(api, ClientApi::Chat(_)) && upstream.provider.flavor != client_flavor {
+        state.log_activity(
+            "api_unsupported",
+            content_type,
+            format!("Provider {} does not support the {} API", upstream.provider.name, api),
+        ).await;
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": {
+                    "message": format!(
+                        "Provider '{}' speaks the {} API and does not support {} requests",
+                        upstream.provider.name,
+                        upstream.provider.flavor,
+                        api
+                    ),
+                    "type": "invalid_request_error"
+                }
+            })),
+        ).into_response());
+    }
+    if !is_stream || upstream.provider.flavor == client_flavor {
+        return Ok(());
+    }
+
+    state.log_activity(
+        "stream_translation_unsupported",
+        content_type,
+        format!("Cannot stream {} responses to a {} client", upstream.provider.flavor, client_flavor),
+    ).await;
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({
+            "error": {
+                "message": format!(
+                    "Streaming is not supported when translating between the {} and {} APIs; use the provider's native endpoint or disable streaming",
+                    client_flavor,
+                    upstream.provider.flavor
+                ),
+                "type": "invalid_request_error"
+            }
+        })),
+    ).into_response())
+}
+
+/// Request handling shared by the chat completions, Messages, completions
+/// and embeddings routes
+///
+/// `api` is the API the client speaks. Masking and unmasking work on the
+/// client's payloads; when the provider speaks the other chat API, the
+/// request and response are translated at the provider boundary.
+async fn proxy_request(
+    state: ProxyState,
+    headers: HeaderMap,
+    mut body: Value,
+    api: ClientApi,
+) -> Response {
     // Extract content type from request
     let content_type_str = headers
         .get("content-type")
         .and_then(|v| v.to_str().ok())
         .unwrap_or("application/json");
     
+    // Settings, activity and masking are those of the caller's tenant
+    let (state, policy) = match tenant::resolve(&state, &headers).await {
+        Ok(resolved) => resolved,
+        Err(e) => return profile_error(&state, e, content_type_str).await,
+    };
+    
+    // LEVEL 1: Check routing mode first
+    let routing_mode = state.get_routing_mode().await;
+    let privacy_mode = state.get_mode().await;
+    let detection_method = state.get_detection_method().await;
+    let privacy_guard_url = state.privacy_guard_url.clone();
+    let privacy_guard_token = state.privacy_guard_token.clone();
+    
+    let is_stream = stream::is_stream_request(&body);
+    
     // Log the request
     state.log_activity(
-        "chat_completion",
+        api.action(),
         content_type_str,
-        format!("Routing: {}, Privacy: {}, Detection: {}", routing_mode, privacy_mode, detection_method),
+        format!(
+            "API: {}, Routing: {}, Privacy: {}, Detection: {}",
+            api, routing_mode, privacy_mode, detection_method
+        ),
     ).await;
     
+    // Select provider and build URL
+    let endpoint = |provider: &ProviderEntry, model: &str| api.endpoint(provider, model);
+    let upstream = match select_upstream(&state, &headers, policy.as_deref(), &mut body, content_type_str, endpoint).await {
+        Ok(upstream) => upstream,
+        Err(response) => return response,
+    };
+    
+    if let Err(response) = check_upstream_api(&state, &upstream, api, is_stream, content_type_str).await {
+        return response;
+    }
+    
     // If routing mode is BYPASS, skip Privacy Guard entirely
     if routing_mode == RoutingMode::Bypass {
         state.log_activity(
@@ -44,18 +442,12 @@ pub async fn proxy_chat_completions(
             "Routing mode: BYPASS - Going direct to LLM (Privacy Guard skipped)",
         ).await;
         
-        let (provider_url, endpoint) = match detect_provider(&headers) {
-            Ok(provider) => {
-                (provider.base_url().to_string(), provider.chat_completions_endpoint().to_string())
-            }
-            Err(_) => {
-                let base = std::env::var("LLM_PROVIDER_URL")
-                    .unwrap_or_else(|_| "https://openrouter.ai/api".to_string());
-                (base, "/v1/chat/completions".to_string())
-            }
-        };
+        if is_stream {
+            let response = send_upstream(&upstream, api, body, &headers).await;
+            return stream::relay(state.clone(), response, None, content_type_str.to_string()).await;
+        }
         
-        return match forward_request(&provider_url, &endpoint, body, &headers).await {
+        return match forward_upstream(&upstream, api, body, &headers).await {
             Ok(response) => {
                 state.log_activity(
                     "bypass_success",
@@ -121,18 +513,12 @@ pub async fn proxy_chat_completions(
         ).await;
         
         // Forward request without masking
-        let (provider_url, endpoint) = match detect_provider(&headers) {
-            Ok(provider) => {
-                (provider.base_url().to_string(), provider.chat_completions_endpoint().to_string())
-            }
-            Err(_) => {
-                let base = std::env::var("LLM_PROVIDER_URL")
-                    .unwrap_or_else(|_| "https://openrouter.ai/api".to_string());
-                (base, "/v1/chat/completions".to_string())
-            }
-        };
+        if is_stream {
+            let response = send_upstream(&upstream, api, body, &headers).await;
+            return stream::relay(state.clone(), response, None, content_type_str.to_string()).await;
+        }
         
-        return match forward_request(&provider_url, &endpoint, body, &headers).await {
+        return match forward_upstream(&upstream, api, body, &headers).await {
             Ok(response) => {
                 state.log_activity(
                     "passthrough_success",
@@ -161,8 +547,8 @@ pub async fn proxy_chat_completions(
     }
     
     // Task B.2: Add masking logic based on privacy mode (for maskable content)
-    // Use "proxy" as tenant_id for all requests
-    let tenant_id = "proxy";
+    // Privacy Guard scopes mappings to the caller's tenant
+    let tenant_id = state.tenant_id();
     
     // Convert detection_method and privacy_mode to strings for Privacy Guard Service
     let detection_method_str = format!("{:?}", detection_method).to_lowercase();
@@ -171,23 +557,51 @@ pub async fn proxy_chat_completions(
         PrivacyMode::ServiceBypass => "service-bypass".to_string(),
         PrivacyMode::Strict => "strict".to_string(),
     };
+    let mask_settings = MaskSettings {
+        detection_method: Some(detection_method_str),
+        privacy_mode: Some(privacy_mode_str),
+        rules: policy.as_deref().map(|p| p.privacy.mask_rules()).unwrap_or_default(),
+    };
     
+    let mut upstream = upstream;
     let masking_session_id = match privacy_mode {
         PrivacyMode::Auto | PrivacyMode::Strict => {
+            // One masking session per conversation, derived before masking
+            let session_id = session::masking_session_id(tenant_id, &headers, &body);
+
             // Mask messages before sending to LLM (pass user settings to Privacy Guard)
-            match mask_messages(
+            match mask_request(
                 &privacy_guard_url,
+                api,
                 &mut body,
                 tenant_id,
-                Some(detection_method_str),
-                Some(privacy_mode_str),
+                &session_id,
+                &mask_settings,
+                &state.http_client,
             ).await {
-                Ok(session_id) => {
+                Ok(detections) => {
+                    state.touch_masking_session(&session_id).await;
                     state.log_activity(
                         "masking_success",
                         content_type_str,
                         format!("Messages masked, session_id: {}", session_id),
                     ).await;
+
+                    // Highly sensitive requests stay on the local provider, even masked
+                    upstream = match route_sensitive(
+                        &state,
+                        upstream,
+                        &detections,
+                        &mut body,
+                        content_type_str,
+                        endpoint,
+                    ).await {
+                        Ok(upstream) => upstream,
+                        Err(response) => return response,
+                    };
+                    if let Err(response) = check_upstream_api(&state, &upstream, api, is_stream, content_type_str).await {
+                        return response;
+                    }
                     Some(session_id)
                 }
                 Err(e) => {
@@ -219,60 +633,53 @@ pub async fn proxy_chat_completions(
         }
     };
     
-    // Detect provider and build URL
-    let (provider_url, endpoint) = match detect_provider(&headers) {
-        Ok(provider) => {
-            let url = provider.chat_completions_url();
-            state.log_activity(
-                "provider_detected",
-                content_type_str,
-                format!("Provider: {}, URL: {}", provider.name(), url),
-            ).await;
-            (provider.base_url().to_string(), provider.chat_completions_endpoint().to_string())
-        }
-        Err(e) => {
-            state.log_activity(
-                "provider_detection_error",
-                content_type_str,
-                format!("Failed to detect provider: {}, using default", e),
-            ).await;
-            // Fallback to env var or default
-            let base = std::env::var("LLM_PROVIDER_URL")
-                .unwrap_or_else(|_| "https://openrouter.ai/api".to_string());
-            (base, "/v1/chat/completions".to_string())
-        }
-    };
+    // Stream the response, unmasking deltas as they arrive
+    if is_stream {
+        let response = send_upstream(&upstream, api, body, &headers).await;
+        let unmask = masking_session_id.map(|session_id| StreamUnmask {
+            privacy_guard_url: privacy_guard_url.clone(),
+            privacy_guard_token: privacy_guard_token.clone(),
+            tenant_id: tenant_id.to_string(),
+            session_id,
+            flavor: api.flavor(),
+        });
+        return stream::relay(state.clone(), response, unmask, content_type_str.to_string()).await;
+    }
     
     // Forward the request to the LLM provider
-    match forward_request(&provider_url, &endpoint, body, &headers).await {
+    match forward_upstream(&upstream, api, body, &headers).await {
         Ok(mut response) => {
-            // Unmask response if we have a session_id
-            if let Some(session_id) = masking_session_id {
-                if let Some(response_content) = extract_response_content(&mut response) {
-                    let client = reqwest::Client::new();
-                    match unmask_response(&privacy_guard_url, &response_content, tenant_id, &session_id, &client).await {
-                        Ok(unmasked) => {
-                            update_response_content(&mut response, unmasked);
-                            state.log_activity(
-                                "unmasking_success",
-                                content_type_str,
-                                "Response unmasked successfully",
-                            ).await;
-                        }
-                        Err(e) => {
-                            state.log_activity(
-                                "unmasking_error",
-                                content_type_str,
-                                format!("Unmasking failed (returning masked response): {}", e),
-                            ).await;
-                            // Return masked response rather than error
-                        }
+            // Unmask response if we have a session_id (embeddings are only vectors)
+            if let Some(session_id) = masking_session_id.filter(|_| api != ClientApi::Embeddings) {
+                let fields = response_text_fields(api, &mut response);
+                match unmask_fields(
+                    &privacy_guard_url,
+                    privacy_guard_token.as_deref(),
+                    fields,
+                    tenant_id,
+                    &session_id,
+                    &state.http_client,
+                ).await {
+                    Ok(()) => {
+                        state.log_activity(
+                            "unmasking_success",
+                            content_type_str,
+                            "Response unmasked successfully",
+                        ).await;
+                    }
+                    Err(e) => {
+                        state.log_activity(
+                            "unmasking_error",
+                            content_type_str,
+                            format!("Unmasking failed (returning masked response): {}", e),
+                        ).await;
+                        // Return masked response rather than error
                     }
                 }
             }
             
             state.log_activity(
-                "chat_completion_success",
+                format!("{}_success", api.action()),
                 content_type_str,
                 "Request completed successfully",
             ).await;
@@ -281,7 +688,7 @@ pub async fn proxy_chat_completions(
         }
         Err(e) => {
             state.log_activity(
-                "chat_completion_error",
+                format!("{}_error", api.action()),
                 content_type_str,
                 format!("Error: {}", e),
             ).await;
@@ -299,114 +706,96 @@ pub async fn proxy_chat_completions(
     }
 }
 
-/// POST /v1/completions - Proxy completions to LLM
-pub async fn proxy_completions(
-    State(state): State<ProxyState>,
-    headers: HeaderMap,
-    Json(body): Json<Value>,
-) -> impl IntoResponse {
-    let mode = state.get_mode().await;
+/// Client API key from `Authorization: Bearer ...` (OpenAI style) or `x-api-key` (Anthropic style)
+pub(crate) fn client_api_key(headers: &HeaderMap) -> Option<String> {
+    if let Some(auth_str) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
+        // Extract API key from "Bearer sk-..." format
+        return Some(auth_str.strip_prefix("Bearer ").unwrap_or(auth_str).to_string());
+    }
     
-    let content_type = headers
-        .get("content-type")
+    headers
+        .get("x-api-key")
         .and_then(|v| v.to_str().ok())
-        .unwrap_or("application/json");
-    
-    state.log_activity(
-        "completion",
-        content_type,
-        format!("Mode: {}, Provider: determining...", mode),
-    ).await;
-    
-    // Detect provider and build URL
-    let (provider_url, endpoint) = match detect_provider(&headers) {
-        Ok(provider) => {
-            (provider.base_url().to_string(), provider.completions_endpoint().to_string())
-        }
-        Err(_) => {
-            let base = std::env::var("LLM_PROVIDER_URL")
-                .unwrap_or_else(|_| "https://openrouter.ai/api".to_string());
-            (base, "/v1/completions".to_string())
-        }
-    };
-    
-    match forward_request(&provider_url, &endpoint, body, &headers).await {
-        Ok(response) => {
-            state.log_activity(
-                "completion_success",
-                content_type,
-                "Forwarded to provider (pass-through mode for B.1)",
-            ).await;
-            
-            (StatusCode::OK, Json(response)).into_response()
-        }
-        Err(e) => {
-            state.log_activity(
-                "completion_error",
-                content_type,
-                format!("Error: {}", e),
-            ).await;
-            
-            (
-                StatusCode::BAD_GATEWAY,
-                Json(serde_json::json!({
-                    "error": {
-                        "message": format!("Failed to forward request: {}", e),
-                        "type": "proxy_error"
-                    }
-                })),
-            ).into_response()
-        }
-    }
+        .map(str::to_string)
 }
 
-/// Determine the LLM provider from API key in headers
-fn detect_provider(headers: &HeaderMap) -> Result<LLMProvider, String> {
-    // Extract Authorization header
-    let auth_header = headers
-        .get("authorization")
-        .or_else(|| headers.get("Authorization"))
-        .ok_or_else(|| "Missing Authorization header".to_string())?;
-    
-    let auth_str = auth_header
-        .to_str()
-        .map_err(|_| "Invalid Authorization header format".to_string())?;
-    
-    // Extract API key from "Bearer sk-..." format
-    let api_key = if auth_str.starts_with("Bearer ") {
-        &auth_str[7..] // Skip "Bearer "
-    } else {
-        auth_str
+/// Send a request to the provider, translating chat requests to the provider's API
+async fn send_upstream(
+    upstream: &Upstream,
+    api: ClientApi,
+    body: Value,
+    headers: &HeaderMap,
+) -> Result<reqwest::Response, String> {
+    let body = match (api.flavor(), upstream.provider.flavor) {
+        (ApiFlavor::OpenAI, ApiFlavor::Anthropic) => anthropic::messages_request_from_chat(&body),
+        (ApiFlavor::Anthropic, ApiFlavor::OpenAI) => anthropic::chat_request_from_messages(&body),
+        _ => body,
     };
+    send_request(&upstream.provider, &upstream.endpoint, body, headers).await
+}
+
+/// Forward a request to the provider, returning the response in the client's API
+async fn forward_upstream(
+    upstream: &Upstream,
+    api: ClientApi,
+    body: Value,
+    headers: &HeaderMap,
+) -> Result<Value, String> {
+    let response = send_upstream(upstream, api, body, headers)
+        .await?
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
     
-    Ok(LLMProvider::from_api_key(api_key))
+    Ok(match (api.flavor(), upstream.provider.flavor) {
+        (ApiFlavor::OpenAI, ApiFlavor::Anthropic) => anthropic::chat_response_from_messages(&response),
+        (ApiFlavor::Anthropic, ApiFlavor::OpenAI) => anthropic::messages_response_from_chat(&response),
+        _ => response,
+    })
 }
 
-/// Forward a request to the LLM provider
-/// Task B.3: Now uses API key from headers (not environment)
-async fn forward_request(
-    base_url: &str,
+/// Send a request to the LLM provider, returning the successful response unread
+///
+/// The client's API key (or the provider's server-side key) is sent in the
+/// provider's auth style.
+async fn send_request(
+    provider: &ProviderEntry,
     path: &str,
     body: Value,
     headers: &HeaderMap,
-) -> Result<Value, String> {
+) -> Result<reqwest::Response, String> {
     let client = reqwest::Client::new();
-    let url = format!("{}{}", base_url, path);
+    let url = format!("{}{}", provider.base_url, path);
+    let mut request = client.post(&url);
     
-    // Extract Authorization header from incoming request
-    let auth_header = headers
-        .get("authorization")
-        .or_else(|| headers.get("Authorization"))
-        .ok_or_else(|| "Missing Authorization header".to_string())?;
+    let auth_style = provider.auth_style();
+    if auth_style != AuthStyle::None {
+        let api_key = provider.api_key(client_api_key(headers)).ok_or_else(|| {
+            format!(
+                "Missing API key for provider '{}' (send Authorization or x-api-key)",
+                provider.name
+            )
+        })?;
+        request = match auth_style {
+            AuthStyle::Bearer => request.header("Authorization", format!("Bearer {}", api_key)),
+            AuthStyle::XApiKey => request.header("x-api-key", api_key),
+            AuthStyle::ApiKey => request.header("api-key", api_key),
+            AuthStyle::None => request,
+        };
+    }
     
-    let auth_str = auth_header
-        .to_str()
-        .map_err(|_| "Invalid Authorization header".to_string())?;
+    if provider.flavor == ApiFlavor::Anthropic {
+        let version = headers
+            .get("anthropic-version")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or(anthropic::DEFAULT_API_VERSION);
+        request = request.header("anthropic-version", version);
+        if let Some(beta) = headers.get("anthropic-beta").and_then(|v| v.to_str().ok()) {
+            request = request.header("anthropic-beta", beta);
+        }
+    }
     
-    // Forward request with original Authorization header
-    let response = client
-        .post(&url)
-        .header("Authorization", auth_str)
+    let response = request
         .header("Content-Type", "application/json")
         .json(&body)
         .send()
@@ -419,68 +808,118 @@ async fn forward_request(
         return Err(format!("Provider returned {}: {}", status, error_body));
     }
     
-    response
-        .json::<Value>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    Ok(response)
+}
+
+/// Text fields of a request in the given API
+fn request_text_fields(api: ClientApi, body: &mut Value) -> Vec<&mut String> {
+    match api {
+        ClientApi::Chat(ApiFlavor::OpenAI) => chat::request_text_fields(body),
+        ClientApi::Chat(ApiFlavor::Anthropic) => anthropic::request_text_fields(body),
+        ClientApi::Completions => completions::request_text_fields(body),
+        ClientApi::Embeddings => completions::embeddings_text_fields(body),
+    }
+}
+
+/// Text fields of a response in the given API
+fn response_text_fields(api: ClientApi, response: &mut Value) -> Vec<&mut String> {
+    match api {
+        ClientApi::Chat(ApiFlavor::OpenAI) => chat::response_text_fields(response),
+        ClientApi::Chat(ApiFlavor::Anthropic) => anthropic::response_text_fields(response),
+        ClientApi::Completions => completions::response_text_fields(response),
+        ClientApi::Embeddings => Vec::new(),
+    }
 }
 
-/// Mask all messages in a chat completion request
-/// Returns session_id from Privacy Guard (used for reidentification)
-async fn mask_messages(
+/// Mask every text-bearing field of a request
+/// Mappings for every field are stored in the conversation's `session_id`
+///
+/// Returns the number of entities detected per type across all fields.
+async fn mask_request(
     privacy_guard_url: &str,
+    api: ClientApi,
     body: &mut Value,
     tenant_id: &str,
-    detection_method: Option<String>,
-    privacy_mode: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let mut last_session_id = String::new();
-    
-    // Extract messages array from request body
-    if let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) {
-        for message in messages.iter_mut() {
-            if let Some(content) = message.get("content").and_then(|c| c.as_str()) {
-                // Mask this message (pass user settings to Privacy Guard)
-                let (masked, session_id) = mask_message(
-                    privacy_guard_url,
-                    content,
-                    tenant_id,
-                    &client,
-                    detection_method.clone(),
-                    privacy_mode.clone(),
-                ).await?;
-                
-                // Update message content with masked version
-                message["content"] = Value::String(masked);
-                
-                // Store session_id (all messages in same request use same session)
-                last_session_id = session_id;
-            }
+    session_id: &str,
+    settings: &MaskSettings,
+    client: &reqwest::Client,
+) -> Result<HashMap<String, usize>, String> {
+    let mut detections = HashMap::new();
+
+    // Message content (all roles and content parts/blocks), tool-call
+    // arguments and results, system prompt, and tool descriptions; or the
+    // prompt / embeddings input
+    for field in request_text_fields(api, body) {
+        if field.is_empty() {
+            continue;
+        }
+
+        let masked = mask_message(
+            privacy_guard_url,
+            field,
+            tenant_id,
+            Some(session_id),
+            client,
+            settings,
+        ).await?;
+
+        for (entity, count) in masked.redactions {
+            *detections.entry(entity).or_insert(0) += count;
         }
+        *field = masked.masked_text;
     }
-    
-    Ok(last_session_id)
-}
 
-/// Extract response content from LLM response
-fn extract_response_content(response: &Value) -> Option<String> {
-    response
-        .get("choices")?
-        .get(0)?
-        .get("message")?
-        .get("content")?
-        .as_str()
-        .map(|s| s.to_string())
+    Ok(detections)
 }
 
-/// Update response content with unmasked text
-fn update_response_content(response: &mut Value, unmasked: String) {
-    if let Some(choices) = response.get_mut("choices").and_then(|c| c.as_array_mut()) {
-        if let Some(first_choice) = choices.get_mut(0) {
-            if let Some(message) = first_choice.get_mut("message") {
-                message["content"] = Value::String(unmasked);
-            }
+/// Unmask text fields of an LLM response (or stream event)
+///
+/// Either every field is unmasked or the response is left untouched.
+pub(crate) async fn unmask_fields(
+    privacy_guard_url: &str,
+    privacy_guard_token: Option<&str>,
+    mut fields: Vec<&mut String>,
+    tenant_id: &str,
+    session_id: &str,
+    client: &reqwest::Client,
+) -> Result<(), String> {
+    let mut unmasked = Vec::with_capacity(fields.len());
+    for field in fields.iter() {
+        if !may_contain_token(field) {
+            unmasked.push(field.to_string());
+            continue;
         }
+        unmasked.push(unmask_response(privacy_guard_url, privacy_guard_token, field, tenant_id, session_id, client).await?);
+    }
+
+    for (field, text) in fields.iter_mut().zip(unmasked) {
+        **field = text;
+    }
+
+    Ok(())
+}
+
+/// Whether text can contain a restorable token
+///
+/// Format-preserving tokens contain digits and pseudonyms are whole
+/// `{TYPE}_{hash}` words; anything else (most stream deltas) needs no round
+/// trip to Privacy Guard.
+fn may_contain_token(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+        || text
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .any(is_valid_pseudonym)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_may_contain_token() {
+        assert!(!may_contain_token("Hello, how can I help?"));
+        assert!(!may_contain_token("let user_name = get_user_name();"));
+        assert!(may_contain_token("Call 555-987-6543"));
+        assert!(may_contain_token("Hi PERSON_abcdefabcdefabcd,"));
     }
 }
@@ -0,0 +1,663 @@
+// registry.rs - Configurable LLM provider registry
+//
+// Providers are loaded from a YAML file (PROVIDERS_CONFIG) so the proxy can
+// point at self-hosted OpenAI-compatible servers, Azure-style deployments or a
+// local Ollama. Without a config file the built-in providers are used
+// (OpenRouter, Anthropic, OpenAI and a local Ollama).
+//
+// A request's provider is selected, in order, by:
+//   1. the X-LLM-Provider header (provider name)
+//   2. a provider's `model_prefix` on the request model ("ollama/llama3.2"),
+//      which is stripped before forwarding
+//   3. a provider's `key_prefixes` on the client's API key ("sk-ant-")
+//   4. the registry default
+//
+// The optional `local` section names the provider that requests are routed to
+// when they must not leave the network: always for roles whose profile sets
+// `privacy.local_only`, and for requests in which masking finds many or
+// highly sensitive entities.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::provider::{ApiFlavor, LLMProvider};
+
+/// Header selecting a provider by name
+pub const PROVIDER_HEADER: &str = "x-llm-provider";
+
+/// Base URL of the built-in Ollama provider
+const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// How the API key is sent to a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthStyle {
+    /// `Authorization: Bearer <key>` (OpenAI, OpenRouter, most compatible servers)
+    Bearer,
+    /// `x-api-key: <key>` (Anthropic)
+    XApiKey,
+    /// `api-key: <key>` (Azure OpenAI)
+    ApiKey,
+    /// No credentials (local servers)
+    None,
+}
+
+/// A configured LLM provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderEntry {
+    /// Provider name, as used in profiles ("openrouter", "ollama", ...)
+    pub name: String,
+
+    /// Base URL, without the API path
+    pub base_url: String,
+
+    /// API the provider speaks
+    #[serde(default)]
+    pub flavor: ApiFlavor,
+
+    /// Auth header style (defaults to the flavor's usual style)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthStyle>,
+
+    /// Environment variable holding a server-side API key, used when the
+    /// client sends none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+
+    /// Chat endpoint path (defaults to the flavor's endpoint); `{model}` is
+    /// replaced by the request model, e.g. for Azure deployments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_path: Option<String>,
+
+    /// Model prefix routing requests to this provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_prefix: Option<String>,
+
+    /// API key prefixes routing requests to this provider
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_prefixes: Vec<String>,
+
+    /// Models this provider may be used with (globs; empty = any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_models: Vec<String>,
+}
+
+impl ProviderEntry {
+    /// Auth header style, defaulting by API flavor
+    pub fn auth_style(&self) -> AuthStyle {
+        self.auth.unwrap_or(match self.flavor {
+            ApiFlavor::OpenAI => AuthStyle::Bearer,
+            ApiFlavor::Anthropic => AuthStyle::XApiKey,
+        })
+    }
+
+    /// Chat endpoint path for a model
+    pub fn chat_path(&self, model: &str) -> String {
+        self.chat_path
+            .as_deref()
+            .unwrap_or(self.flavor.chat_endpoint())
+            .replace("{model}", model)
+    }
+
+    /// Check whether a model may be used with this provider
+    pub fn allows_model(&self, model: &str) -> bool {
+        self.allowed_models.is_empty() || self.allowed_models.iter().any(|p| glob_match(p, model))
+    }
+
+    /// API key to send: the client's key, or the configured server-side key
+    pub fn api_key(&self, client_key: Option<String>) -> Option<String> {
+        client_key.or_else(|| {
+            self.api_key_env
+                .as_ref()
+                .and_then(|var| std::env::var(var).ok())
+                .filter(|key| !key.is_empty())
+        })
+    }
+}
+
+impl From<LLMProvider> for ProviderEntry {
+    fn from(provider: LLMProvider) -> Self {
+        Self {
+            name: provider.name().to_lowercase(),
+            base_url: provider.base_url().to_string(),
+            flavor: provider.api_flavor(),
+            auth: None,
+            api_key_env: None,
+            chat_path: Some(provider.chat_completions_endpoint().to_string()),
+            model_prefix: None,
+            key_prefixes: vec![provider.key_prefix().to_string()],
+            allowed_models: Vec::new(),
+        }
+    }
+}
+
+/// How a provider was selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Header,
+    ModelPrefix,
+    ApiKey,
+    Default,
+    /// The registry's local provider
+    Local,
+}
+
+impl std::fmt::Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selection::Header => write!(f, "header"),
+            Selection::ModelPrefix => write!(f, "model prefix"),
+            Selection::ApiKey => write!(f, "API key"),
+            Selection::Default => write!(f, "default"),
+            Selection::Local => write!(f, "local routing"),
+        }
+    }
+}
+
+/// Provider chosen for a request
+#[derive(Debug)]
+pub struct Route<'a> {
+    pub provider: &'a ProviderEntry,
+    /// Model to send upstream (routing prefix stripped)
+    pub model: Option<String>,
+    pub selected_by: Selection,
+}
+
+/// Local provider for requests that must stay on the network
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LocalRouting {
+    /// Provider name (must be defined in the registry)
+    pub provider: String,
+
+    /// Model sent to the local provider (the request's model when unset)
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Route locally when masking detects at least this many entities
+    #[serde(default)]
+    pub min_detections: Option<usize>,
+
+    /// Route locally when masking detects any of these entity types ("SSN", "CREDIT_CARD")
+    #[serde(default)]
+    pub entity_types: Vec<String>,
+}
+
+impl LocalRouting {
+    /// Why a request with these entity counts must be routed locally, if it must
+    pub fn sensitive_reason(&self, detections: &HashMap<String, usize>) -> Option<String> {
+        let mut found: Vec<&str> = detections
+            .iter()
+            .filter(|(entity, count)| {
+                **count > 0 && self.entity_types.iter().any(|t| t.eq_ignore_ascii_case(entity))
+            })
+            .map(|(entity, _)| entity.as_str())
+            .collect();
+        if !found.is_empty() {
+            found.sort_unstable();
+            return Some(format!("detected {}", found.join(", ")));
+        }
+
+        let total: usize = detections.values().sum();
+        match self.min_detections {
+            Some(min) if total >= min => Some(format!("{} entities detected (threshold {})", total, min)),
+            _ => None,
+        }
+    }
+}
+
+/// Providers file format
+#[derive(Deserialize)]
+struct RegistryFile {
+    /// Default provider name (defaults to the first provider)
+    default: Option<String>,
+    providers: Vec<ProviderEntry>,
+    #[serde(default)]
+    local: Option<LocalRouting>,
+}
+
+/// Registry of LLM providers
+#[derive(Debug, Clone)]
+pub struct ProviderRegistry {
+    providers: Vec<ProviderEntry>,
+    default: String,
+    local: Option<LocalRouting>,
+}
+
+impl ProviderRegistry {
+    /// Built-in providers: OpenRouter (default), Anthropic, OpenAI, local Ollama
+    ///
+    /// Ollama is the local provider, with no detection thresholds.
+    pub fn builtin() -> Self {
+        let mut providers: Vec<ProviderEntry> = [LLMProvider::OpenRouter, LLMProvider::Anthropic, LLMProvider::OpenAI]
+            .into_iter()
+            .map(ProviderEntry::from)
+            .collect();
+        providers.push(ProviderEntry {
+            name: "ollama".to_string(),
+            base_url: OLLAMA_BASE_URL.to_string(),
+            flavor: ApiFlavor::OpenAI,
+            auth: Some(AuthStyle::None),
+            api_key_env: None,
+            chat_path: None,
+            model_prefix: Some("ollama/".to_string()),
+            key_prefixes: Vec::new(),
+            allowed_models: Vec::new(),
+        });
+
+        Self {
+            providers,
+            default: "openrouter".to_string(),
+            local: Some(LocalRouting {
+                provider: "ollama".to_string(),
+                model: None,
+                min_detections: None,
+                entity_types: Vec::new(),
+            }),
+        }
+    }
+
+    /// Parse a providers file
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let file: RegistryFile =
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid providers config: {}", e))?;
+
+        let Some(first) = file.providers.first() else {
+            return Err("Providers config must define at least one provider".to_string());
+        };
+        let default = file.default.unwrap_or_else(|| first.name.clone());
+
+        let registry = Self {
+            providers: file.providers,
+            default,
+            local: file.local,
+        };
+
+        for (i, provider) in registry.providers.iter().enumerate() {
+            if provider.name.trim().is_empty() {
+                return Err(format!("Provider #{} has no name", i + 1));
+            }
+            if registry.providers[..i].iter().any(|p| p.name.eq_ignore_ascii_case(&provider.name)) {
+                return Err(format!("Duplicate provider '{}'", provider.name));
+            }
+            if !provider.base_url.starts_with("http://") && !provider.base_url.starts_with("https://") {
+                return Err(format!("Provider '{}' base_url must be an http(s) URL", provider.name));
+            }
+        }
+        if registry.get(&registry.default).is_none() {
+            return Err(format!("Default provider '{}' is not defined", registry.default));
+        }
+        if let Some(local) = &registry.local {
+            let Some(provider) = registry.get(&local.provider) else {
+                return Err(format!("Local provider '{}' is not defined", local.provider));
+            };
+            if let Some(model) = local.model.as_deref().filter(|m| !provider.allows_model(m)) {
+                return Err(format!(
+                    "Local model '{}' is not allowed for provider '{}'",
+                    model, provider.name
+                ));
+            }
+        }
+
+        Ok(registry)
+    }
+
+    /// Load a providers file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read providers config {}: {}", path, e))?;
+        Self::from_yaml(&yaml)
+    }
+
+    /// Look up a provider by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&ProviderEntry> {
+        self.providers.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// All providers, in config order
+    pub fn providers(&self) -> &[ProviderEntry] {
+        &self.providers
+    }
+
+    /// Local routing settings, if a local provider is configured
+    pub fn local(&self) -> Option<&LocalRouting> {
+        self.local.as_ref()
+    }
+
+    /// Route a request to the local provider
+    ///
+    /// The local model replaces the request's model when configured; the
+    /// request's model otherwise loses the provider's routing prefix. None
+    /// when no local provider is configured.
+    pub fn local_route(&self, model: Option<&str>) -> Option<Route<'_>> {
+        let local = self.local.as_ref()?;
+        let provider = self.get(&local.provider).expect("local provider is validated");
+        let model = local.model.as_deref().or(model).map(|model| {
+            provider
+                .model_prefix
+                .as_deref()
+                .and_then(|prefix| model.strip_prefix(prefix))
+                .unwrap_or(model)
+        });
+        Some(Route {
+            provider,
+            model: model.map(str::to_string),
+            selected_by: Selection::Local,
+        })
+    }
+
+    /// Select the provider for a request
+    ///
+    /// Fails only when the header names an unknown provider.
+    pub fn select(
+        &self,
+        explicit: Option<&str>,
+        model: Option<&str>,
+        api_key: Option<&str>,
+    ) -> Result<Route<'_>, String> {
+        let route = |provider, model: Option<&str>, selected_by| Route {
+            provider,
+            model: model.map(str::to_string),
+            selected_by,
+        };
+
+        if let Some(name) = explicit {
+            let provider = self.get(name).ok_or_else(|| {
+                let names: Vec<&str> = self.providers.iter().map(|p| p.name.as_str()).collect();
+                format!("Unknown provider '{}' (configured: {})", name, names.join(", "))
+            })?;
+            return Ok(route(provider, model, Selection::Header));
+        }
+
+        if let Some(model) = model {
+            for provider in &self.providers {
+                let stripped = provider
+                    .model_prefix
+                    .as_deref()
+                    .and_then(|prefix| model.strip_prefix(prefix));
+                if let Some(stripped) = stripped {
+                    return Ok(route(provider, Some(stripped), Selection::ModelPrefix));
+                }
+            }
+        }
+
+        // Longest matching key prefix wins ("sk-or-" over "sk-")
+        if let Some(api_key) = api_key {
+            let by_key = self
+                .providers
+                .iter()
+                .flat_map(|p| p.key_prefixes.iter().map(move |prefix| (p, prefix)))
+                .filter(|(_, prefix)| api_key.starts_with(prefix.as_str()))
+                .max_by_key(|(_, prefix)| prefix.len());
+            if let Some((provider, _)) = by_key {
+                return Ok(route(provider, model, Selection::ApiKey));
+            }
+        }
+
+        let provider = self.get(&self.default).expect("default provider is validated");
+        Ok(route(provider, model, Selection::Default))
+    }
+}
+
+/// Glob match: `*` matches within one path segment, `**` across segments,
+/// `?` matches one character (same rules as controller policy globs)
+fn glob_match(pattern: &str, value: &str) -> bool {
+    fn matches(p: &[char], v: &[char]) -> bool {
+        match p.first() {
+            None => v.is_empty(),
+            Some('*') if p.get(1) == Some(&'*') => {
+                let rest = &p[2..];
+                (0..=v.len()).any(|i| matches(rest, &v[i..]))
+            }
+            Some('*') => {
+                let rest = &p[1..];
+                for i in 0..=v.len() {
+                    if matches(rest, &v[i..]) {
+                        return true;
+                    }
+                    if v.get(i) == Some(&'/') {
+                        break;
+                    }
+                }
+                false
+            }
+            Some('?') => !v.is_empty() && v[0] != '/' && matches(&p[1..], &v[1..]),
+            Some(c) => v.first() == Some(c) && matches(&p[1..], &v[1..]),
+        }
+    }
+
+    let p: Vec<char> = pattern.chars().collect();
+    let v: Vec<char> = value.chars().collect();
+    matches(&p, &v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+default: local
+providers:
+  - name: local
+    base_url: http://ollama:11434
+    auth: none
+    model_prefix: "ollama/"
+    allowed_models: ["llama3.2*", "qwen2.5:*"]
+  - name: azure
+    base_url: https://example.openai.azure.com
+    auth: api-key
+    api_key_env: AZURE_OPENAI_KEY
+    chat_path: /openai/deployments/{model}/chat/completions?api-version=2024-06-01
+  - name: anthropic
+    base_url: https://api.anthropic.com
+    flavor: anthropic
+    key_prefixes: ["sk-ant-"]
+"#;
+
+    fn select<'a>(
+        registry: &'a ProviderRegistry,
+        explicit: Option<&str>,
+        model: Option<&str>,
+        key: Option<&str>,
+    ) -> (&'a str, Option<String>, Selection) {
+        let route = registry.select(explicit, model, key).unwrap();
+        (route.provider.name.as_str(), route.model, route.selected_by)
+    }
+
+    #[test]
+    fn test_builtin_key_detection() {
+        let registry = ProviderRegistry::builtin();
+
+        assert_eq!(select(&registry, None, None, Some("sk-or-v1-1234567890abcdef")).0, "openrouter");
+        assert_eq!(select(&registry, None, None, Some("sk-ant-REDACTED")).0, "anthropic");
+        assert_eq!(select(&registry, None, None, Some("sk-proj-1234567890abcdef")).0, "openai");
+        assert_eq!(select(&registry, None, None, Some("sk-1234567890abcdef")).0, "openai");
+        assert_eq!(
+            select(&registry, None, None, Some("unknown-format-key")),
+            ("openrouter", None, Selection::Default)
+        );
+    }
+
+    #[test]
+    fn test_builtin_providers_match_llm_provider() {
+        let registry = ProviderRegistry::builtin();
+
+        let anthropic = registry.get("Anthropic").unwrap();
+        assert_eq!(anthropic.base_url, "https://api.anthropic.com");
+        assert_eq!(anthropic.flavor, ApiFlavor::Anthropic);
+        assert_eq!(anthropic.auth_style(), AuthStyle::XApiKey);
+        assert_eq!(anthropic.chat_path("claude-sonnet-4"), "/v1/messages");
+
+        let openrouter = registry.get("openrouter").unwrap();
+        assert_eq!(openrouter.chat_path("x"), "/v1/chat/completions");
+        assert_eq!(openrouter.auth_style(), AuthStyle::Bearer);
+    }
+
+    #[test]
+    fn test_builtin_ollama_model_prefix() {
+        let registry = ProviderRegistry::builtin();
+
+        assert_eq!(
+            select(&registry, None, Some("ollama/llama3.2"), Some("sk-or-v1-abc")),
+            ("ollama", Some("llama3.2".to_string()), Selection::ModelPrefix)
+        );
+        // OpenRouter model ids keep their vendor prefix
+        assert_eq!(
+            select(&registry, None, Some("anthropic/claude-3.5-sonnet"), Some("sk-or-v1-abc")),
+            ("openrouter", Some("anthropic/claude-3.5-sonnet".to_string()), Selection::ApiKey)
+        );
+        assert_eq!(registry.get("ollama").unwrap().auth_style(), AuthStyle::None);
+    }
+
+    #[test]
+    fn test_header_overrides_model_and_key() {
+        let registry = ProviderRegistry::builtin();
+
+        assert_eq!(
+            select(&registry, Some("OpenAI"), Some("ollama/llama3.2"), Some("sk-ant-x")),
+            ("openai", Some("ollama/llama3.2".to_string()), Selection::Header)
+        );
+
+        let err = registry.select(Some("nope"), None, None).unwrap_err();
+        assert!(err.contains("Unknown provider 'nope'"));
+        assert!(err.contains("ollama"));
+    }
+
+    #[test]
+    fn test_config_file() {
+        let registry = ProviderRegistry::from_yaml(CONFIG).unwrap();
+
+        assert_eq!(select(&registry, None, None, None), ("local", None, Selection::Default));
+        assert_eq!(
+            select(&registry, None, Some("ollama/qwen2.5:7b"), None),
+            ("local", Some("qwen2.5:7b".to_string()), Selection::ModelPrefix)
+        );
+        assert_eq!(select(&registry, None, None, Some("sk-ant-x")).0, "anthropic");
+
+        let azure = registry.get("azure").unwrap();
+        assert_eq!(azure.flavor, ApiFlavor::OpenAI);
+        assert_eq!(azure.auth_style(), AuthStyle::ApiKey);
+        assert_eq!(
+            azure.chat_path("gpt-4o-prod"),
+            "/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-06-01"
+        );
+        assert_eq!(registry.get("anthropic").unwrap().auth_style(), AuthStyle::XApiKey);
+    }
+
+    #[test]
+    fn test_example_config_parses() {
+        let registry = ProviderRegistry::from_yaml(include_str!("../providers.example.yaml")).unwrap();
+
+        assert_eq!(select(&registry, None, None, Some("sk-or-v1-abc")).0, "openrouter");
+        assert_eq!(select(&registry, None, Some("ollama/llama3.2"), None).0, "ollama");
+        assert_eq!(registry.get("azure").unwrap().auth_style(), AuthStyle::ApiKey);
+    }
+
+    #[test]
+    fn test_allowed_models() {
+        let registry = ProviderRegistry::from_yaml(CONFIG).unwrap();
+
+        let local = registry.get("local").unwrap();
+        assert!(local.allows_model("llama3.2"));
+        assert!(local.allows_model("llama3.2:3b"));
+        assert!(local.allows_model("qwen2.5:7b"));
+        assert!(!local.allows_model("mistral"));
+
+        // No allow-list: any model
+        assert!(registry.get("azure").unwrap().allows_model("anything/at-all"));
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        assert!(ProviderRegistry::from_yaml("providers: []").is_err());
+        assert!(ProviderRegistry::from_yaml("providers: [{name: a}]").is_err());
+
+        let err = ProviderRegistry::from_yaml(
+            "default: b\nproviders: [{name: a, base_url: 'http://a'}]",
+        )
+        .unwrap_err();
+        assert!(err.contains("Default provider 'b'"));
+
+        let err = ProviderRegistry::from_yaml(
+            "providers: [{name: a, base_url: 'http://a'}, {name: A, base_url: 'http://b'}]",
+        )
+        .unwrap_err();
+        assert!(err.contains("Duplicate provider"));
+
+        let err = ProviderRegistry::from_yaml("providers: [{name: a, base_url: 'ftp://a'}]").unwrap_err();
+        assert!(err.contains("http(s) URL"));
+    }
+
+    #[test]
+    fn test_server_side_api_key() {
+        let mut provider = ProviderRegistry::builtin().get("openai").unwrap().clone();
+        provider.api_key_env = Some("PGP_TEST_REGISTRY_KEY".to_string());
+        std::env::set_var("PGP_TEST_REGISTRY_KEY", "sk-server");
+
+        assert_eq!(provider.api_key(None), Some("sk-server".to_string()));
+        assert_eq!(provider.api_key(Some("sk-client".to_string())), Some("sk-client".to_string()));
+    }
+
+    #[test]
+    fn test_local_route() {
+        let registry = ProviderRegistry::builtin();
+
+        let route = registry.local_route(Some("ollama/llama3.2")).unwrap();
+        assert_eq!(route.provider.name, "ollama");
+        assert_eq!(route.model.as_deref(), Some("llama3.2"));
+        assert_eq!(route.selected_by, Selection::Local);
+
+        let registry = ProviderRegistry::from_yaml(include_str!("../providers.example.yaml")).unwrap();
+        let route = registry.local_route(Some("anthropic/claude-3.5-sonnet")).unwrap();
+        assert_eq!((route.provider.name.as_str(), route.model.as_deref()), ("ollama", Some("llama3.2")));
+
+        // No local section: nowhere to route
+        assert!(ProviderRegistry::from_yaml(CONFIG).unwrap().local_route(None).is_none());
+    }
+
+    #[test]
+    fn test_local_sensitive_reason() {
+        let local = LocalRouting {
+            provider: "ollama".to_string(),
+            model: None,
+            min_detections: Some(3),
+            entity_types: vec!["SSN".to_string(), "credit_card".to_string()],
+        };
+        let counts = |pairs: &[(&str, usize)]| -> HashMap<String, usize> {
+            pairs.iter().map(|(e, c)| (e.to_string(), *c)).collect()
+        };
+
+        assert_eq!(local.sensitive_reason(&counts(&[("EMAIL", 1), ("PERSON", 1)])), None);
+        assert_eq!(local.sensitive_reason(&counts(&[("SSN", 0)])), None);
+        assert_eq!(
+            local.sensitive_reason(&counts(&[("SSN", 1), ("CREDIT_CARD", 1)])).as_deref(),
+            Some("detected CREDIT_CARD, SSN")
+        );
+        assert_eq!(
+            local.sensitive_reason(&counts(&[("EMAIL", 2), ("PHONE", 1)])).as_deref(),
+            Some("3 entities detected (threshold 3)")
+        );
+    }
+
+    #[test]
+    fn test_invalid_local_config() {
+        let err = ProviderRegistry::from_yaml(
+            "providers: [{name: a, base_url: 'http://a'}]\nlocal: {provider: b}",
+        )
+        .unwrap_err();
+        assert!(err.contains("Local provider 'b'"));
+
+        let err = ProviderRegistry::from_yaml(
+            "providers: [{name: a, base_url: 'http://a', allowed_models: [llama*]}]\nlocal: {provider: a, model: gpt-4o}",
+        )
+        .unwrap_err();
+        assert!(err.contains("Local model 'gpt-4o'"));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("gpt-4o*", "gpt-4o-mini"));
+        assert!(glob_match("anthropic/*", "anthropic/claude-3.5-sonnet"));
+        assert!(!glob_match("*", "anthropic/claude"));
+        assert!(glob_match("**", "anthropic/claude"));
+    }
+}
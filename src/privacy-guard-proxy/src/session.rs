@@ -0,0 +1,303 @@
+// session.rs - Conversation-scoped masking sessions
+//
+// Privacy Guard keeps pseudonym mappings per session. The proxy uses one
+// session per conversation so every message and every turn shares the same
+// mappings, and pseudonyms from earlier turns can still be restored in later
+// responses. The session is derived from the X-Goose-Session header when the
+// client sends it, otherwise from the conversation's opening messages, which
+// are resent unchanged on every turn. Sessions belong to the caller's tenant,
+// so identical conversations of two tenants never share a session.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use crate::masking::flush_session;
+use crate::proxy::profile_error;
+use crate::state::ProxyState;
+use crate::tenant;
+
+/// Header carrying the goose session id
+pub const SESSION_HEADER: &str = "x-goose-session";
+
+/// Prefix of masking session ids created by the proxy
+const SESSION_PREFIX: &str = "conv_";
+
+/// Default idle time before a masking session is flushed
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often idle sessions are swept
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Goose session id from request headers, if present
+pub fn goose_session(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Masking session of a tenant for a goose session
+pub fn session_for_goose_session(tenant_id: &str, goose_session: &str) -> String {
+    derive_session_id(tenant_id, "header", goose_session)
+}
+
+/// Masking session of a tenant for a chat request
+///
+/// Computed from the unmasked request, before any masking is applied.
+pub fn masking_session_id(tenant_id: &str, headers: &HeaderMap, body: &Value) -> String {
+    match goose_session(headers) {
+        Some(goose_session) => session_for_goose_session(tenant_id, goose_session),
+        None => derive_session_id(tenant_id, "prefix", &conversation_prefix(body)),
+    }
+}
+
+/// Role and content of the messages up to and including the first user message
+///
+/// A Messages API system prompt, which is not part of `messages`, is included,
+/// as are the prompt of a completions request and the input of an embeddings
+/// request, which have no conversation.
+fn conversation_prefix(body: &Value) -> String {
+    let messages = body
+        .get("messages")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let end = messages
+        .iter()
+        .position(|m| m.get("role").and_then(Value::as_str) == Some("user"))
+        .map_or(messages.len(), |i| i + 1);
+
+    let standalone = ["system", "prompt", "input"]
+        .into_iter()
+        .filter_map(|key| body.get(key))
+        .map(|text| (&Value::Null, text));
+    let prefix: Vec<(&Value, &Value)> = standalone
+        .chain(messages[..end].iter().map(|m| {
+            (
+                m.get("role").unwrap_or(&Value::Null),
+                m.get("content").unwrap_or(&Value::Null),
+            )
+        }))
+        .collect();
+
+    serde_json::to_string(&prefix).unwrap_or_default()
+}
+
+fn derive_session_id(tenant_id: &str, source: &str, value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tenant_id.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(source.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(value.as_bytes());
+    let digest = hasher.finalize();
+
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", SESSION_PREFIX, hex)
+}
+
+/// DELETE /api/sessions/:goose_session - Flush the masking session when a goose session ends
+///
+/// The caller is identified like on proxy requests, to find its tenant.
+pub async fn end_session(
+    State(state): State<ProxyState>,
+    headers: HeaderMap,
+    Path(goose_session): Path<String>,
+) -> Response {
+    let state = match tenant::resolve(&state, &headers).await {
+        Ok((state, _)) => state,
+        Err(e) => return profile_error(&state, e, "system").await,
+    };
+    let session_id = session_for_goose_session(state.tenant_id(), &goose_session);
+    state.end_masking_session(&session_id).await;
+
+    match flush_session(&state.privacy_guard_url, &session_id, &state.http_client).await {
+        Ok(flushed) => {
+            state.log_activity(
+                "session_ended",
+                "system",
+                format!("Masking session {} ended (flushed: {})", session_id, flushed),
+            ).await;
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "session_id": session_id,
+                    "flushed": flushed
+                })),
+            ).into_response()
+        }
+        Err(e) => {
+            state.log_activity(
+                "session_flush_error",
+                "system",
+                format!("Failed to flush masking session {}: {}", session_id, e),
+            ).await;
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({
+                    "error": {
+                        "message": format!("Failed to flush masking session: {}", e),
+                        "type": "flush_error"
+                    }
+                })),
+            ).into_response()
+        }
+    }
+}
+
+/// Flush masking sessions that have been idle for longer than `idle`
+///
+/// Covers conversations identified by their message prefix, which have no
+/// explicit end.
+pub fn spawn_idle_sweeper(state: ProxyState, idle: Duration) {
+    tokio::spawn(async move {
+        let client = state.http_client.clone();
+        loop {
+            tokio::time::sleep(SWEEP_INTERVAL).await;
+
+            for session_id in state.take_idle_masking_sessions(idle).await {
+                let details = match flush_session(&state.privacy_guard_url, &session_id, &client).await {
+                    Ok(flushed) => format!("Idle masking session {} flushed: {}", session_id, flushed),
+                    Err(e) => format!("Failed to flush idle masking session {}: {}", session_id, e),
+                };
+                state.log_activity("session_expired", "system", details).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{ProfileSource, DEFAULT_CACHE_TTL};
+    use crate::registry::ProviderRegistry;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    fn headers_with_session(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(SESSION_HEADER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_header_session_is_stable() {
+        let first = json!({"messages": [{"role": "user", "content": "Hi"}]});
+        let second = json!({"messages": [{"role": "user", "content": "Something else"}]});
+        let headers = headers_with_session("20261018_1");
+
+        let id = masking_session_id("proxy", &headers, &first);
+        assert!(id.starts_with("conv_"));
+        assert_eq!(id, masking_session_id("proxy", &headers, &second));
+        assert_eq!(id, session_for_goose_session("proxy", "20261018_1"));
+        assert_ne!(id, masking_session_id("proxy", &headers_with_session("20261018_2"), &first));
+    }
+
+    #[test]
+    fn test_prefix_session_is_stable_across_turns() {
+        let turn1 = json!({"messages": [
+            {"role": "system", "content": "You are goose"},
+            {"role": "user", "content": "Email alice@example.com"}
+        ]});
+        let turn2 = json!({"messages": [
+            {"role": "system", "content": "You are goose"},
+            {"role": "user", "content": "Email alice@example.com"},
+            {"role": "assistant", "content": "Done"},
+            {"role": "user", "content": "Now call 555-123-4567"}
+        ]});
+        let other = json!({"messages": [
+            {"role": "system", "content": "You are goose"},
+            {"role": "user", "content": "Email bob@example.com"}
+        ]});
+
+        let headers = HeaderMap::new();
+        assert_eq!(masking_session_id("proxy", &headers, &turn1), masking_session_id("proxy", &headers, &turn2));
+        assert_ne!(masking_session_id("proxy", &headers, &turn1), masking_session_id("proxy", &headers, &other));
+    }
+
+    #[test]
+    fn test_sessions_are_per_tenant() {
+        let body = json!({"messages": [{"role": "user", "content": "Hi"}]});
+        let headers = HeaderMap::new();
+        assert_ne!(
+            masking_session_id("finance", &headers, &body),
+            masking_session_id("legal", &headers, &body)
+        );
+        assert_ne!(
+            session_for_goose_session("finance", "20261018_1"),
+            session_for_goose_session("legal", "20261018_1")
+        );
+    }
+
+    #[test]
+    fn test_prefix_and_header_sessions_differ() {
+        // A header value equal to a prefix must not collide with it
+        let body = json!({"messages": []});
+        let prefix_id = masking_session_id("proxy", &HeaderMap::new(), &body);
+        assert_ne!(prefix_id, masking_session_id("proxy", &headers_with_session("[]"), &body));
+    }
+
+    #[test]
+    fn test_messages_api_system_prompt_is_part_of_prefix() {
+        let turn1 = json!({"system": "You are goose", "messages": [{"role": "user", "content": "Hi"}]});
+        let turn2 = json!({"system": "You are goose", "messages": [
+            {"role": "user", "content": "Hi"},
+            {"role": "assistant", "content": "Hello"},
+            {"role": "user", "content": "Bye"}
+        ]});
+        let other = json!({"system": "You are a bot", "messages": [{"role": "user", "content": "Hi"}]});
+
+        let headers = HeaderMap::new();
+        assert_eq!(masking_session_id("proxy", &headers, &turn1), masking_session_id("proxy", &headers, &turn2));
+        assert_ne!(masking_session_id("proxy", &headers, &turn1), masking_session_id("proxy", &headers, &other));
+    }
+
+    #[test]
+    fn test_completions_and_embeddings_sessions() {
+        let headers = HeaderMap::new();
+        let prompt = json!({"model": "gpt-3.5-turbo-instruct", "prompt": "Email alice@example.com"});
+        let other = json!({"model": "gpt-3.5-turbo-instruct", "prompt": "Email bob@example.com"});
+        let input = json!({"model": "text-embedding-3-small", "input": ["Email alice@example.com"]});
+
+        assert_ne!(masking_session_id("proxy", &headers, &prompt), masking_session_id("proxy", &headers, &other));
+        assert_ne!(masking_session_id("proxy", &headers, &prompt), masking_session_id("proxy", &headers, &input));
+        assert_ne!(
+            masking_session_id("proxy", &headers, &input),
+            masking_session_id("proxy", &headers, &json!({"input": []}))
+        );
+    }
+
+    #[test]
+    fn test_blank_header_falls_back_to_prefix() {
+        let body = json!({"messages": [{"role": "user", "content": "Hi"}]});
+        assert_eq!(
+            masking_session_id("proxy", &headers_with_session("  "), &body),
+            masking_session_id("proxy", &HeaderMap::new(), &body)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_are_taken_once() {
+        let state = ProxyState::new(
+            "http://localhost:8089".to_string(),
+            None,
+            ProviderRegistry::builtin(),
+            ProfileSource::new("http://localhost:8088".to_string(), None, None, false, DEFAULT_CACHE_TTL),
+        );
+        state.touch_masking_session("conv_a").await;
+
+        assert!(state.take_idle_masking_sessions(Duration::from_secs(60)).await.is_empty());
+        assert_eq!(state.take_idle_masking_sessions(Duration::ZERO).await, vec!["conv_a"]);
+        assert!(state.take_idle_masking_sessions(Duration::ZERO).await.is_empty());
+        assert!(!state.end_masking_session("conv_a").await);
+    }
+}
@@ -1,8 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+use crate::profile::{PrivacyPolicy, ProfileSource};
+use crate::registry::ProviderRegistry;
+use crate::tenant::DEFAULT_TENANT;
+
 /// Routing modes for the proxy (Level 1 control)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -106,37 +112,186 @@ impl ActivityLogEntry {
     }
 }
 
+/// Tenant settings prescribed by a role profile's privacy block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileDefaults {
+    pub mode: PrivacyMode,
+    pub detection_method: DetectionMethod,
+    pub allow_override: bool,
+}
+
+impl ProfileDefaults {
+    /// Profile `mode` ("rules", "ner", "hybrid") sets the detection method and
+    /// `strictness` "strict" sets strict privacy mode; anything else keeps the
+    /// defaults.
+    pub fn from_privacy(privacy: &PrivacyPolicy) -> Self {
+        let detection_method = match privacy.mode.as_deref() {
+            Some("rules") => DetectionMethod::Rules,
+            Some("ner") | Some("ai") => DetectionMethod::Ai,
+            Some("hybrid") => DetectionMethod::Hybrid,
+            _ => DetectionMethod::default(),
+        };
+        let mode = match privacy.strictness.as_deref() {
+            Some("strict") => PrivacyMode::Strict,
+            _ => PrivacyMode::default(),
+        };
+        Self {
+            mode,
+            detection_method,
+            allow_override: privacy.allow_override.unwrap_or(true),
+        }
+    }
+}
+
+/// Settings and activity log of one tenant
+pub struct TenantState {
+    /// Tenant id, also used as the Privacy Guard tenant id
+    pub id: String,
+    pub routing_mode: RwLock<RoutingMode>,
+    pub current_mode: RwLock<PrivacyMode>,
+    pub detection_method: RwLock<DetectionMethod>,
+    /// Whether users may change routing, privacy mode and detection method
+    pub allow_override: RwLock<bool>,
+    /// Profile defaults the settings were last reset to
+    pub profile_defaults: RwLock<Option<ProfileDefaults>>,
+    pub activity_log: RwLock<Vec<ActivityLogEntry>>,
+}
+
+impl TenantState {
+    /// A tenant with the default settings
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            routing_mode: RwLock::new(RoutingMode::default()),
+            current_mode: RwLock::new(PrivacyMode::default()),
+            detection_method: RwLock::new(DetectionMethod::default()),
+            allow_override: RwLock::new(true), // Default: allow user control
+            profile_defaults: RwLock::new(None),
+            activity_log: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// A tenant whose settings start from a role profile's privacy block
+    /// (see `ProfileDefaults::from_privacy`)
+    pub fn from_privacy(id: impl Into<String>, privacy: &PrivacyPolicy) -> Self {
+        let tenant = Self::new(id);
+        let defaults = ProfileDefaults::from_privacy(privacy);
+
+        Self {
+            current_mode: RwLock::new(defaults.mode),
+            detection_method: RwLock::new(defaults.detection_method),
+            allow_override: RwLock::new(defaults.allow_override),
+            profile_defaults: RwLock::new(Some(defaults)),
+            ..tenant
+        }
+    }
+}
+
 /// Shared state for the proxy service
+///
+/// Settings and activity are those of one tenant; `for_tenant` scopes the
+/// state to another tenant. The state built by `new` is scoped to the
+/// default tenant.
 #[derive(Clone)]
 pub struct ProxyState {
-    pub routing_mode: Arc<RwLock<RoutingMode>>,
-    pub current_mode: Arc<RwLock<PrivacyMode>>,
-    pub detection_method: Arc<RwLock<DetectionMethod>>,
-    pub allow_override: Arc<RwLock<bool>>,
-    pub activity_log: Arc<RwLock<Vec<ActivityLogEntry>>>,
+    /// Tenant this state is scoped to
+    pub tenant: Arc<TenantState>,
+    /// Every tenant seen so far, by id
+    pub tenants: Arc<RwLock<HashMap<String, Arc<TenantState>>>>,
+    /// Masking sessions open in Privacy Guard, with their last use
+    pub masking_sessions: Arc<RwLock<HashMap<String, Instant>>>,
+    /// LLM providers requests can be routed to
+    pub providers: Arc<ProviderRegistry>,
+    /// Role profiles limiting providers and models
+    pub profiles: Arc<ProfileSource>,
     pub privacy_guard_url: String,
+    /// Bearer token Privacy Guard requires to reidentify text
+    pub privacy_guard_token: Option<String>,
+    /// Token of control panel requests on other tenants (None disables them)
+    pub admin_token: Option<String>,
+    /// Client for Privacy Guard calls, shared so connections are reused
+    pub http_client: reqwest::Client,
 }
 
 impl ProxyState {
-    pub fn new(privacy_guard_url: String) -> Self {
+    pub fn new(
+        privacy_guard_url: String,
+        privacy_guard_token: Option<String>,
+        providers: ProviderRegistry,
+        profiles: ProfileSource,
+    ) -> Self {
+        let tenant = Arc::new(TenantState::new(DEFAULT_TENANT));
+        let tenants = HashMap::from([(DEFAULT_TENANT.to_string(), tenant.clone())]);
         Self {
-            routing_mode: Arc::new(RwLock::new(RoutingMode::default())),
-            current_mode: Arc::new(RwLock::new(PrivacyMode::default())),
-            detection_method: Arc::new(RwLock::new(DetectionMethod::default())),
-            allow_override: Arc::new(RwLock::new(true)), // Default: allow user control
-            activity_log: Arc::new(RwLock::new(Vec::new())),
+            tenant,
+            tenants: Arc::new(RwLock::new(tenants)),
+            masking_sessions: Arc::new(RwLock::new(HashMap::new())),
+            providers: Arc::new(providers),
+            profiles: Arc::new(profiles),
             privacy_guard_url,
+            privacy_guard_token,
+            admin_token: None,
+            http_client: reqwest::Client::new(),
         }
     }
+
+    /// Allow control panel requests on any tenant with this token
+    pub fn with_admin_token(mut self, admin_token: Option<String>) -> Self {
+        self.admin_token = admin_token;
+        self
+    }
+
+    /// Id of the tenant this state is scoped to
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant.id
+    }
+
+    /// This state scoped to a tenant, creating the tenant with `create` on first use
+    pub async fn for_tenant(&self, id: &str, create: impl FnOnce() -> TenantState) -> ProxyState {
+        let existing = self.tenants.read().await.get(id).cloned();
+        let tenant = match existing {
+            Some(tenant) => tenant,
+            None => self
+                .tenants
+                .write()
+                .await
+                .entry(id.to_string())
+                .or_insert_with(|| Arc::new(create()))
+                .clone(),
+        };
+        ProxyState {
+            tenant,
+            ..self.clone()
+        }
+    }
+
+    /// This state scoped to an existing tenant
+    pub async fn existing_tenant(&self, id: &str) -> Option<ProxyState> {
+        let tenant = self.tenants.read().await.get(id).cloned()?;
+        Some(ProxyState {
+            tenant,
+            ..self.clone()
+        })
+    }
+
+    /// Ids of every tenant seen so far, sorted
+    pub async fn tenant_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.tenants.read().await.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
     
     /// Get the current routing mode
     pub async fn get_routing_mode(&self) -> RoutingMode {
-        *self.routing_mode.read().await
+        *self.tenant.routing_mode.read().await
     }
     
-    /// Set the routing mode
-    pub async fn set_routing_mode(&self, mode: RoutingMode) {
-        let mut current = self.routing_mode.write().await;
+    /// Set the routing mode (only if override allowed)
+    pub async fn set_routing_mode(&self, mode: RoutingMode) -> Result<(), String> {
+        let mut current = self.tenant.routing_mode.write().await;
+        if *current != mode && !self.get_allow_override().await {
+            return Err("Routing mode is locked by profile configuration".to_string());
+        }
         *current = mode;
         
         self.log_activity(
@@ -144,16 +299,21 @@ impl ProxyState {
             "system",
             format!("Routing mode changed to: {}", mode),
         ).await;
+
+        Ok(())
     }
 
     /// Get the current privacy mode
     pub async fn get_mode(&self) -> PrivacyMode {
-        *self.current_mode.read().await
+        *self.tenant.current_mode.read().await
     }
 
-    /// Set the privacy mode
-    pub async fn set_mode(&self, mode: PrivacyMode) {
-        let mut current = self.current_mode.write().await;
+    /// Set the privacy mode (only if override allowed)
+    pub async fn set_mode(&self, mode: PrivacyMode) -> Result<(), String> {
+        let mut current = self.tenant.current_mode.write().await;
+        if *current != mode && !self.get_allow_override().await {
+            return Err("Privacy mode is locked by profile configuration".to_string());
+        }
         *current = mode;
         
         // Log the mode change
@@ -162,6 +322,8 @@ impl ProxyState {
             "system",
             format!("Privacy mode changed to: {}", mode),
         ).await;
+
+        Ok(())
     }
 
     /// Log an activity entry
@@ -171,7 +333,7 @@ impl ProxyState {
         content_type: impl Into<String>,
         details: impl Into<String>,
     ) {
-        let mut log = self.activity_log.write().await;
+        let mut log = self.tenant.activity_log.write().await;
         log.push(ActivityLogEntry::new(action, content_type, details));
         
         // Keep only last 100 entries to prevent unbounded growth
@@ -183,7 +345,7 @@ impl ProxyState {
 
     /// Get recent activity entries (last N entries)
     pub async fn get_recent_activity(&self, limit: usize) -> Vec<ActivityLogEntry> {
-        let log = self.activity_log.read().await;
+        let log = self.tenant.activity_log.read().await;
         let start = if log.len() > limit {
             log.len() - limit
         } else {
@@ -194,23 +356,20 @@ impl ProxyState {
 
     /// Get total activity count
     pub async fn get_activity_count(&self) -> usize {
-        self.activity_log.read().await.len()
+        self.tenant.activity_log.read().await.len()
     }
 
     /// Get the current detection method
     pub async fn get_detection_method(&self) -> DetectionMethod {
-        *self.detection_method.read().await
+        *self.tenant.detection_method.read().await
     }
 
     /// Set the detection method (only if override allowed)
     pub async fn set_detection_method(&self, method: DetectionMethod) -> Result<(), String> {
-        let allow_override = *self.allow_override.read().await;
-        
-        if !allow_override {
+        let mut current = self.tenant.detection_method.write().await;
+        if *current != method && !self.get_allow_override().await {
             return Err("Detection method is locked by profile configuration".to_string());
         }
-        
-        let mut current = self.detection_method.write().await;
         *current = method;
         
         // Log the change
@@ -225,12 +384,63 @@ impl ProxyState {
 
     /// Get whether override is allowed
     pub async fn get_allow_override(&self) -> bool {
-        *self.allow_override.read().await
+        *self.tenant.allow_override.read().await
     }
 
-    /// Set whether override is allowed (admin-only, set from profile)
-    pub async fn set_allow_override(&self, allowed: bool) {
-        let mut current = self.allow_override.write().await;
-        *current = allowed;
+    /// Reset the tenant's settings to its profile's defaults when they differ
+    /// from the defaults last applied, so profile edits reach running tenants
+    ///
+    /// Returns whether the settings were reset.
+    pub async fn apply_profile_defaults(&self, defaults: ProfileDefaults) -> bool {
+        {
+            let mut applied = self.tenant.profile_defaults.write().await;
+            if *applied == Some(defaults) {
+                return false;
+            }
+            *applied = Some(defaults);
+            *self.tenant.current_mode.write().await = defaults.mode;
+            *self.tenant.detection_method.write().await = defaults.detection_method;
+            *self.tenant.allow_override.write().await = defaults.allow_override;
+        }
+
+        self.log_activity(
+            "profile_defaults_applied",
+            "system",
+            format!(
+                "Profile changed: privacy mode {}, detection method {}, override {}",
+                defaults.mode,
+                defaults.detection_method,
+                if defaults.allow_override { "allowed" } else { "locked" }
+            ),
+        ).await;
+
+        true
+    }
+
+    /// Record that a masking session was used
+    pub async fn touch_masking_session(&self, session_id: &str) {
+        self.masking_sessions
+            .write()
+            .await
+            .insert(session_id.to_string(), Instant::now());
+    }
+
+    /// Stop tracking a masking session; returns false if it wasn't tracked
+    pub async fn end_masking_session(&self, session_id: &str) -> bool {
+        self.masking_sessions.write().await.remove(session_id).is_some()
+    }
+
+    /// Stop tracking and return sessions unused for longer than `idle`
+    pub async fn take_idle_masking_sessions(&self, idle: Duration) -> Vec<String> {
+        let mut sessions = self.masking_sessions.write().await;
+        let expired: Vec<String> = sessions
+            .iter()
+            .filter(|(_, last_used)| last_used.elapsed() >= idle)
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in &expired {
+            sessions.remove(session_id);
+        }
+        expired
     }
 }
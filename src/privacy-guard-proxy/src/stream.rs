@@ -0,0 +1,683 @@
+// stream.rs - Streaming (SSE) chat completions
+//
+// Requests with `stream: true` are masked like any other request, then the
+// provider's event stream is relayed to the client as it arrives. When the
+// request was masked, every `chat.completion.chunk` is unmasked before it is
+// forwarded. A pseudonym can be split across two chunks ("PERSON_a3" +
+// "f7b2..."), so the tail of each delta that could be the start of a token is
+// held back and prepended to the next delta of the same choice / tool call.
+// Held-back text is released on `finish_reason`, on `[DONE]`, or when the
+// upstream stream ends. Legacy completions streams (`choices[].text`) are
+// held back per choice, and Anthropic Messages streams per content block
+// (see anthropic::BlockBuffer).
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::anthropic::{self, BlockBuffer};
+use crate::chat;
+use crate::completions;
+use crate::contract::pseudonym_prefix_len;
+use crate::provider::ApiFlavor;
+use crate::proxy::unmask_fields;
+use crate::state::ProxyState;
+
+/// Longest run of trailing digits/separators held back for FPE tokens
+const MAX_FPE_HOLDBACK: usize = 20;
+
+/// Masking session used to unmask a stream
+pub struct StreamUnmask {
+    pub privacy_guard_url: String,
+    pub privacy_guard_token: Option<String>,
+    pub tenant_id: String,
+    pub session_id: String,
+    /// API of the stream's events
+    pub flavor: ApiFlavor,
+}
+
+/// Check whether a chat completion request asks for a streamed response
+pub fn is_stream_request(body: &Value) -> bool {
+    body.get("stream").and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Relay a provider event stream to the client, unmasking it if `unmask` is set
+pub async fn relay(
+    state: ProxyState,
+    upstream: Result<reqwest::Response, String>,
+    unmask: Option<StreamUnmask>,
+    content_type: String,
+) -> Response {
+    let upstream = match upstream {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            state.log_activity(
+                "chat_completion_error",
+                content_type,
+                format!("Error: {}", e),
+            ).await;
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({
+                    "error": {
+                        "message": format!("Failed to forward request: {}", e),
+                        "type": "proxy_error"
+                    }
+                })),
+            ).into_response();
+        }
+    };
+
+    state.log_activity(
+        "chat_completion_stream",
+        content_type.as_str(),
+        format!("Streaming response (unmasking: {})", unmask.is_some()),
+    ).await;
+
+    let (mut tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(16);
+
+    tokio::spawn(async move {
+        let mut events = upstream.bytes_stream();
+        let mut parser = SseParser::default();
+        let mut buffer = Holdback::new(unmask.as_ref().map_or(ApiFlavor::OpenAI, |u| u.flavor));
+        let client = state.http_client.clone();
+
+        loop {
+            let (bytes, done) = match events.next().await {
+                Some(Ok(bytes)) => (bytes, false),
+                Some(Err(e)) => {
+                    state.log_activity(
+                        "stream_error",
+                        content_type.as_str(),
+                        format!("Upstream stream failed: {}", e),
+                    ).await;
+                    (Bytes::new(), true)
+                }
+                None => (Bytes::new(), true),
+            };
+
+            let Some(unmask) = unmask.as_ref() else {
+                // Nothing was masked: relay bytes untouched
+                if done || tx.send(Ok(bytes)).await.is_err() {
+                    return;
+                }
+                continue;
+            };
+
+            let mut out = Vec::new();
+            for event in parser.push(&bytes) {
+                out.push(process_event(&event, &mut buffer, unmask, &client, &state, &content_type).await);
+            }
+            if done {
+                if let Some(event) = parser.finish() {
+                    out.push(process_event(&event, &mut buffer, unmask, &client, &state, &content_type).await);
+                }
+                // Stream ended without [DONE]: release anything still held back
+                for mut chunk in buffer.flush() {
+                    out.push(release_event(&mut chunk, unmask, &client, &state, &content_type).await);
+                }
+            }
+
+            for event in out {
+                if tx.send(Ok(Bytes::from(event))).await.is_err() {
+                    return;
+                }
+            }
+            if done {
+                return;
+            }
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from_stream(rx))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// Unmask one SSE event, returning the text to send to the client
+async fn process_event(
+    event: &str,
+    buffer: &mut Holdback,
+    unmask: &StreamUnmask,
+    client: &reqwest::Client,
+    state: &ProxyState,
+    content_type: &str,
+) -> String {
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+    let data = data.join("\n");
+
+    if data == "[DONE]" {
+        let mut out = String::new();
+        for mut chunk in buffer.flush() {
+            out.push_str(&release_event(&mut chunk, unmask, client, state, content_type).await);
+        }
+        out.push_str(event);
+        out.push_str("\n\n");
+        return out;
+    }
+
+    let Ok(mut chunk) = serde_json::from_str::<Value>(&data) else {
+        // Comments, keep-alives and non-JSON events pass through
+        return format!("{}\n\n", event);
+    };
+
+    let mut out = String::new();
+    for mut released in buffer.hold(&mut chunk) {
+        out.push_str(&release_event(&mut released, unmask, client, state, content_type).await);
+    }
+    let data_line = unmask_chunk(&mut chunk, unmask, client, state, content_type).await;
+
+    // Keep any non-data fields (event:, id:) of the original event
+    out.extend(event
+        .lines()
+        .filter(|line| !line.starts_with("data:"))
+        .map(|line| format!("{}\n", line)));
+    out.push_str(&data_line);
+    out
+}
+
+/// Unmask an event created by the proxy to release held-back text
+async fn release_event(
+    chunk: &mut Value,
+    unmask: &StreamUnmask,
+    client: &reqwest::Client,
+    state: &ProxyState,
+    content_type: &str,
+) -> String {
+    // Messages API events are named by their type
+    let event_line = match (unmask.flavor, chunk.get("type").and_then(Value::as_str)) {
+        (ApiFlavor::Anthropic, Some(event_type)) => format!("event: {}\n", event_type),
+        _ => String::new(),
+    };
+    event_line + &unmask_chunk(chunk, unmask, client, state, content_type).await
+}
+
+/// Unmask a chunk and format it as an SSE data event
+async fn unmask_chunk(
+    chunk: &mut Value,
+    unmask: &StreamUnmask,
+    client: &reqwest::Client,
+    state: &ProxyState,
+    content_type: &str,
+) -> String {
+    let fields = match unmask.flavor {
+        ApiFlavor::OpenAI if completions::is_completion(chunk) => completions::response_text_fields(chunk),
+        ApiFlavor::OpenAI => chat::response_text_fields(chunk),
+        ApiFlavor::Anthropic => anthropic::stream_event_text_fields(chunk),
+    };
+    if let Err(e) = unmask_fields(
+        &unmask.privacy_guard_url,
+        unmask.privacy_guard_token.as_deref(),
+        fields,
+        &unmask.tenant_id,
+        &unmask.session_id,
+        client,
+    ).await {
+        state.log_activity(
+            "unmasking_error",
+            content_type,
+            format!("Unmasking stream chunk failed (forwarding masked chunk): {}", e),
+        ).await;
+    }
+    format!("data: {}\n\n", chunk)
+}
+
+/// Splits a byte stream into SSE events
+#[derive(Default)]
+pub struct SseParser {
+    buf: Vec<u8>,
+}
+
+impl SseParser {
+    /// Add bytes and return every event completed by them
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        // Normalise CRLF line endings; JSON payloads never contain a raw CR
+        self.buf.extend(bytes.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buf.drain(..pos + 2).take(pos).collect();
+            if !event.is_empty() {
+                events.push(String::from_utf8_lossy(&event).into_owned());
+            }
+        }
+        events
+    }
+
+    /// Return a trailing event that was not terminated by a blank line
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = String::from_utf8_lossy(&self.buf).trim_end().to_string();
+        self.buf.clear();
+        (!rest.is_empty()).then_some(rest)
+    }
+}
+
+/// Held-back stream text, per API
+enum Holdback {
+    Chat(DeltaBuffer),
+    Messages(BlockBuffer),
+}
+
+impl Holdback {
+    fn new(flavor: ApiFlavor) -> Self {
+        match flavor {
+            ApiFlavor::OpenAI => Holdback::Chat(DeltaBuffer::default()),
+            ApiFlavor::Anthropic => Holdback::Messages(BlockBuffer::default()),
+        }
+    }
+
+    /// Rewrite an event, returning events to send before it
+    fn hold(&mut self, event: &mut Value) -> Vec<Value> {
+        match self {
+            Holdback::Chat(buffer) => {
+                buffer.hold(event);
+                Vec::new()
+            }
+            Holdback::Messages(buffer) => buffer.hold(event),
+        }
+    }
+
+    /// Events releasing everything still held back
+    fn flush(&mut self) -> Vec<Value> {
+        match self {
+            Holdback::Chat(buffer) => buffer.flush().into_iter().collect(),
+            Holdback::Messages(buffer) => buffer.flush(),
+        }
+    }
+}
+
+/// Key of a held-back delta: choice index and tool call index (None = content)
+type DeltaKey = (u64, Option<u64>);
+
+/// Holds back delta text that may end in a partial token
+#[derive(Default)]
+pub struct DeltaBuffer {
+    pending: BTreeMap<DeltaKey, String>,
+    /// id/object/created/model of the last chunk, for the flush chunk
+    template: Map<String, Value>,
+    /// The stream is a legacy completions stream (text instead of deltas)
+    text_completion: bool,
+}
+
+impl DeltaBuffer {
+    /// Rewrite a chunk's deltas so they end on a safe boundary
+    ///
+    /// Text held back from earlier chunks is prepended. When a choice
+    /// finishes, everything held back for it is released in this chunk.
+    pub fn hold(&mut self, chunk: &mut Value) {
+        if let Some(obj) = chunk.as_object() {
+            self.template = obj
+                .iter()
+                .filter(|(key, _)| matches!(key.as_str(), "id" | "object" | "created" | "model"))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+        }
+
+        let Some(choices) = chunk.get_mut("choices").and_then(Value::as_array_mut) else {
+            return;
+        };
+
+        for choice in choices.iter_mut() {
+            let Some(choice) = choice.as_object_mut() else {
+                continue;
+            };
+            let index = choice.get("index").and_then(Value::as_u64).unwrap_or(0);
+            let finished = choice.get("finish_reason").is_some_and(|reason| !reason.is_null());
+
+            if let Some(Value::String(text)) = choice.get_mut("text") {
+                self.text_completion = true;
+                let released = self.release((index, None), text, finished);
+                *text = released;
+                continue;
+            }
+
+            if finished && !choice.get("delta").is_some_and(Value::is_object) {
+                choice.insert("delta".to_string(), json!({}));
+            }
+            let Some(delta) = choice.get_mut("delta").and_then(Value::as_object_mut) else {
+                continue;
+            };
+
+            match delta.get_mut("content") {
+                Some(Value::String(text)) => {
+                    let released = self.release((index, None), text, finished);
+                    *text = released;
+                }
+                _ if finished => {
+                    if let Some(rest) = self.pending.remove(&(index, None)) {
+                        delta.insert("content".to_string(), Value::String(rest));
+                    }
+                }
+                _ => {}
+            }
+
+            if let Some(calls) = delta.get_mut("tool_calls").and_then(Value::as_array_mut) {
+                for call in calls.iter_mut() {
+                    let call_index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+                    if let Some(Value::String(arguments)) =
+                        call.get_mut("function").and_then(|f| f.get_mut("arguments"))
+                    {
+                        let released = self.release((index, Some(call_index)), arguments, finished);
+                        *arguments = released;
+                    }
+                }
+            }
+
+            if finished {
+                let rest = self.take_tool_calls(index);
+                if !rest.is_empty() {
+                    let calls = delta
+                        .entry("tool_calls")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Some(calls) = calls.as_array_mut() {
+                        calls.extend(rest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Chunk releasing everything still held back, if anything is
+    pub fn flush(&mut self) -> Option<Value> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        if self.text_completion {
+            let mut chunk = self.template.clone();
+            chunk
+                .entry("object")
+                .or_insert_with(|| Value::String("text_completion".to_string()));
+            chunk.insert(
+                "choices".to_string(),
+                std::mem::take(&mut self.pending)
+                    .into_iter()
+                    .map(|((index, _), text)| json!({"index": index, "text": text, "finish_reason": null}))
+                    .collect(),
+            );
+            return Some(Value::Object(chunk));
+        }
+
+        let mut deltas: BTreeMap<u64, Map<String, Value>> = BTreeMap::new();
+        for ((index, call_index), text) in std::mem::take(&mut self.pending) {
+            let delta = deltas.entry(index).or_default();
+            match call_index {
+                None => {
+                    delta.insert("content".to_string(), Value::String(text));
+                }
+                Some(call_index) => {
+                    let calls = delta
+                        .entry("tool_calls")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Some(calls) = calls.as_array_mut() {
+                        calls.push(json!({"index": call_index, "function": {"arguments": text}}));
+                    }
+                }
+            }
+        }
+
+        let mut chunk = self.template.clone();
+        chunk
+            .entry("object")
+            .or_insert_with(|| Value::String("chat.completion.chunk".to_string()));
+        chunk.insert(
+            "choices".to_string(),
+            deltas
+                .into_iter()
+                .map(|(index, delta)| json!({"index": index, "delta": delta, "finish_reason": null}))
+                .collect(),
+        );
+        Some(Value::Object(chunk))
+    }
+
+    /// Prepend held-back text and hold back a new unsafe tail
+    fn release(&mut self, key: DeltaKey, text: &str, finished: bool) -> String {
+        let mut combined = self.pending.remove(&key).unwrap_or_default();
+        combined.push_str(text);
+        if finished {
+            return combined;
+        }
+
+        let tail = combined.split_off(combined.len() - holdback_len(&combined));
+        if !tail.is_empty() {
+            self.pending.insert(key, tail);
+        }
+        combined
+    }
+
+    /// Remove held-back tool call arguments of a choice as tool_calls deltas
+    fn take_tool_calls(&mut self, index: u64) -> Vec<Value> {
+        let keys: Vec<DeltaKey> = self
+            .pending
+            .keys()
+            .filter(|(choice, call)| *choice == index && call.is_some())
+            .copied()
+            .collect();
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let text = self.pending.remove(&key)?;
+                Some(json!({"index": key.1, "function": {"arguments": text}}))
+            })
+            .collect()
+    }
+}
+
+/// Length of the tail of `text` that may be the start of a token
+///
+/// Covers pseudonyms ("PERSON_a3f7...") and format-preserving tokens, which
+/// keep the digits and separators of phone numbers and SSNs.
+pub(crate) fn holdback_len(text: &str) -> usize {
+    let fpe_tail: usize = text
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '(' | ')' | '.' | ' ' | '+'))
+        .take(MAX_FPE_HOLDBACK)
+        .map(char::len_utf8)
+        .sum();
+    let fpe_tail = if text[text.len() - fpe_tail..].chars().any(|c| c.is_ascii_digit()) {
+        fpe_tail
+    } else {
+        0
+    };
+
+    pseudonym_prefix_len(text).max(fpe_tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content_chunk(text: &str) -> Value {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": {"content": text}, "finish_reason": null}]
+        })
+    }
+
+    fn content(chunk: &Value) -> &str {
+        chunk["choices"][0]["delta"]["content"].as_str().unwrap()
+    }
+
+    #[test]
+    fn test_is_stream_request() {
+        assert!(is_stream_request(&json!({"stream": true})));
+        assert!(!is_stream_request(&json!({"stream": false})));
+        assert!(!is_stream_request(&json!({})));
+    }
+
+    #[test]
+    fn test_pseudonym_prefix_len() {
+        assert_eq!(pseudonym_prefix_len("Hello PERSON_a3"), "PERSON_a3".len());
+        assert_eq!(pseudonym_prefix_len("Hello PERS"), "PERS".len());
+        assert_eq!(pseudonym_prefix_len("Hello CREDIT_CARD_"), "CREDIT_CARD_".len());
+        assert_eq!(pseudonym_prefix_len("Hello world"), 0);
+        assert_eq!(pseudonym_prefix_len(""), 0);
+        // A complete pseudonym is safe to emit
+        assert_eq!(pseudonym_prefix_len("Hi PERSON_a3f7b2c8e1d4f9a2"), 0);
+        // Not hash characters
+        assert_eq!(pseudonym_prefix_len("PERSON_xyz"), 0);
+    }
+
+    #[test]
+    fn test_split_pseudonym_is_held_back() {
+        let mut buffer = DeltaBuffer::default();
+
+        let mut first = content_chunk("Hello PERSON_a3f7");
+        buffer.hold(&mut first);
+        assert_eq!(content(&first), "Hello ");
+
+        let mut second = content_chunk("b2c8e1d4f9a2, welcome");
+        buffer.hold(&mut second);
+        assert_eq!(content(&second), "PERSON_a3f7b2c8e1d4f9a2, welcome");
+
+        assert!(buffer.flush().is_none());
+    }
+
+    #[test]
+    fn test_split_custom_type_pseudonym_is_held_back() {
+        // Profile rules add entity types such as EMPLOYEE_ID
+        let mut buffer = DeltaBuffer::default();
+
+        let mut first = content_chunk("Your id is EMPLOYEE_I");
+        buffer.hold(&mut first);
+        assert_eq!(content(&first), "Your id is ");
+
+        let mut second = content_chunk("D_a3f7b2c8e1d4f9a2, thanks");
+        buffer.hold(&mut second);
+        assert_eq!(content(&second), "EMPLOYEE_ID_a3f7b2c8e1d4f9a2, thanks");
+
+        assert!(buffer.flush().is_none());
+    }
+
+    #[test]
+    fn test_split_fpe_token_is_held_back() {
+        let mut buffer = DeltaBuffer::default();
+
+        let mut first = content_chunk("Call 555-98");
+        buffer.hold(&mut first);
+        assert_eq!(content(&first), "Call");
+
+        let mut second = content_chunk("7-6543 today");
+        buffer.hold(&mut second);
+        assert_eq!(content(&second), " 555-987-6543 today");
+    }
+
+    #[test]
+    fn test_finish_reason_releases_held_text() {
+        let mut buffer = DeltaBuffer::default();
+
+        let mut first = content_chunk("Bye PERSON_a3");
+        buffer.hold(&mut first);
+        assert_eq!(content(&first), "Bye ");
+
+        let mut last = json!({
+            "id": "chatcmpl-1",
+            "choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}]
+        });
+        buffer.hold(&mut last);
+        assert_eq!(content(&last), "PERSON_a3");
+        assert!(buffer.flush().is_none());
+    }
+
+    #[test]
+    fn test_tool_call_arguments_are_held_per_call() {
+        let mut buffer = DeltaBuffer::default();
+
+        let mut first = json!({"choices": [{"index": 0, "delta": {"tool_calls": [
+            {"index": 0, "id": "call_1", "function": {"name": "crm__lookup", "arguments": "{\"email\":\"EMAIL_0f1e"}}
+        ]}, "finish_reason": null}]});
+        buffer.hold(&mut first);
+        assert_eq!(
+            first["choices"][0]["delta"]["tool_calls"][0]["function"]["arguments"],
+            "{\"email\":\""
+        );
+
+        let mut last = json!({"choices": [{"index": 0, "delta": {}, "finish_reason": "tool_calls"}]});
+        buffer.hold(&mut last);
+        assert_eq!(
+            last["choices"][0]["delta"]["tool_calls"],
+            json!([{"index": 0, "function": {"arguments": "EMAIL_0f1e"}}])
+        );
+    }
+
+    #[test]
+    fn test_flush_builds_chunk_from_template() {
+        let mut buffer = DeltaBuffer::default();
+        let mut chunk = content_chunk("Dear PERSON_");
+        buffer.hold(&mut chunk);
+
+        let flushed = buffer.flush().unwrap();
+        assert_eq!(flushed["id"], "chatcmpl-1");
+        assert_eq!(flushed["model"], "gpt-4o");
+        assert_eq!(flushed["object"], "chat.completion.chunk");
+        assert_eq!(content(&flushed), "PERSON_");
+        assert!(buffer.flush().is_none());
+    }
+
+    #[test]
+    fn test_completion_text_is_held_back() {
+        let text_chunk = |text: &str, finish_reason: Value| {
+            json!({
+                "id": "cmpl-1",
+                "object": "text_completion",
+                "model": "gpt-3.5-turbo-instruct",
+                "choices": [{"index": 0, "text": text, "logprobs": null, "finish_reason": finish_reason}]
+            })
+        };
+        let mut buffer = DeltaBuffer::default();
+
+        let mut first = text_chunk("Dear PERSON_a3f7", Value::Null);
+        buffer.hold(&mut first);
+        assert_eq!(first["choices"][0]["text"], "Dear ");
+        assert!(first["choices"][0].get("delta").is_none());
+
+        let flushed = buffer.flush().unwrap();
+        assert_eq!(flushed["object"], "text_completion");
+        assert_eq!(flushed["choices"][0]["text"], "PERSON_a3f7");
+
+        buffer.hold(&mut text_chunk("Call 555-12", Value::Null));
+        let mut last = text_chunk("3-4567", json!("stop"));
+        buffer.hold(&mut last);
+        assert_eq!(last["choices"][0]["text"], " 555-123-4567");
+        assert!(buffer.flush().is_none());
+    }
+
+    #[test]
+    fn test_sse_parser_handles_split_events() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.push(b"data: {\"a\":").is_empty());
+        assert_eq!(parser.push(b"1}\r\n\r\ndata: [DO"), vec!["data: {\"a\":1}"]);
+        assert_eq!(parser.push(b"NE]\n\n"), vec!["data: [DONE]"]);
+        assert!(parser.finish().is_none());
+
+        parser.push(b": keep-alive");
+        assert_eq!(parser.finish(), Some(": keep-alive".to_string()));
+    }
+
+    #[test]
+    fn test_sse_parser_keeps_split_utf8() {
+        let mut parser = SseParser::default();
+        let event = "data: {\"content\":\"caf\u{e9}\"}\n\n".as_bytes();
+        let (head, tail) = event.split_at(event.len() - 5);
+
+        assert!(parser.push(head).is_empty());
+        assert_eq!(parser.push(tail), vec!["data: {\"content\":\"caf\u{e9}\"}"]);
+    }
+}
@@ -0,0 +1,183 @@
+// tenant.rs - Per-tenant proxy settings
+//
+// One proxy can serve several departments. Each caller is a tenant with its
+// own routing mode, privacy mode, detection method and activity log, and its
+// own Privacy Guard tenant id, so masking sessions and audit never mix. The
+// tenant is the caller's role (PROXY_ROLE or the role token), otherwise a
+// hash of its API key, otherwise the default tenant. A role tenant's settings
+// start from the `privacy` block of the role's profile, and are reset to it
+// whenever that block changes. The control panel shows the caller's own
+// tenant; with the admin token it selects any tenant with `?tenant=<id>`.
+
+use axum::http::HeaderMap;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::profile::{ProfileError, RolePolicy};
+use crate::proxy::client_api_key;
+use crate::state::{ProfileDefaults, ProxyState, TenantState};
+
+/// Tenant of callers with neither a role nor an API key
+pub const DEFAULT_TENANT: &str = "proxy";
+
+/// Prefix of tenants identified by API key
+const KEY_TENANT_PREFIX: &str = "key_";
+
+/// Tenant id of a caller
+///
+/// API keys are hashed so they never show up in the control panel or audit.
+pub fn tenant_id(policy: Option<&RolePolicy>, api_key: Option<&str>) -> String {
+    if let Some(policy) = policy {
+        return policy.role.clone();
+    }
+
+    match api_key.map(str::trim).filter(|key| !key.is_empty()) {
+        Some(key) => {
+            let digest = Sha256::digest(key.as_bytes());
+            let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{}{}", KEY_TENANT_PREFIX, hex)
+        }
+        None => DEFAULT_TENANT.to_string(),
+    }
+}
+
+/// Scope the state to the tenant of a request
+///
+/// The request's role policy is returned too, so the profile is looked up
+/// once per request. Profile edits picked up by the profile cache reset the
+/// tenant's settings to the new defaults.
+pub async fn resolve(
+    state: &ProxyState,
+    headers: &HeaderMap,
+) -> Result<(ProxyState, Option<Arc<RolePolicy>>), ProfileError> {
+    let policy = state.profiles.policy_for(headers).await?;
+    let id = tenant_id(policy.as_deref(), client_api_key(headers).as_deref());
+
+    let tenant = state
+        .for_tenant(&id, || match policy.as_deref() {
+            Some(policy) => TenantState::from_privacy(id.as_str(), &policy.privacy),
+            None => TenantState::new(id.as_str()),
+        })
+        .await;
+    if let Some(policy) = policy.as_deref() {
+        tenant.apply_profile_defaults(ProfileDefaults::from_privacy(&policy.privacy)).await;
+    }
+    Ok((tenant, policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{PrivacyPolicy, ProfileSource, DEFAULT_CACHE_TTL};
+    use crate::registry::ProviderRegistry;
+    use crate::state::{DetectionMethod, PrivacyMode, RoutingMode};
+    use axum::http::HeaderValue;
+
+    fn state() -> ProxyState {
+        ProxyState::new(
+            "http://localhost:8089".to_string(),
+            None,
+            ProviderRegistry::builtin(),
+            ProfileSource::new("http://localhost:8088".to_string(), None, None, false, DEFAULT_CACHE_TTL),
+        )
+    }
+
+    fn role_policy(role: &str, privacy: PrivacyPolicy) -> RolePolicy {
+        RolePolicy {
+            role: role.to_string(),
+            providers: serde_json::from_value(serde_json::json!({
+                "primary": {"provider": "openrouter", "model": "m"}
+            }))
+            .unwrap(),
+            privacy,
+        }
+    }
+
+    #[test]
+    fn test_tenant_id() {
+        let finance = role_policy("finance", PrivacyPolicy::default());
+        assert_eq!(tenant_id(Some(&finance), Some("sk-or-v1-abc")), "finance");
+
+        let by_key = tenant_id(None, Some("sk-or-v1-abc"));
+        assert!(by_key.starts_with("key_"));
+        assert_eq!(by_key.len(), "key_".len() + 16);
+        assert!(!by_key.contains("abc"));
+        assert_eq!(by_key, tenant_id(None, Some("sk-or-v1-abc")));
+        assert_ne!(by_key, tenant_id(None, Some("sk-or-v1-xyz")));
+
+        assert_eq!(tenant_id(None, None), DEFAULT_TENANT);
+        assert_eq!(tenant_id(None, Some(" ")), DEFAULT_TENANT);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_are_isolated() {
+        let state = state();
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer sk-or-v1-abc"));
+
+        let (tenant, policy) = resolve(&state, &headers).await.unwrap();
+        assert!(policy.is_none());
+        assert!(tenant.tenant_id().starts_with("key_"));
+
+        tenant.set_mode(PrivacyMode::Strict).await.unwrap();
+        tenant.log_activity("chat_completion", "application/json", "hello").await;
+
+        assert_eq!(state.get_mode().await, PrivacyMode::Auto);
+        assert_eq!(state.get_activity_count().await, 0);
+        assert_eq!(state.tenant_id(), DEFAULT_TENANT);
+
+        // The same caller gets the same tenant back
+        let (again, _) = resolve(&state, &headers).await.unwrap();
+        assert_eq!(again.get_mode().await, PrivacyMode::Strict);
+        assert_eq!(again.get_activity_count().await, 2);
+
+        let (default, _) = resolve(&state, &HeaderMap::new()).await.unwrap();
+        assert_eq!(default.tenant_id(), DEFAULT_TENANT);
+        assert_eq!(state.tenant_ids().await, vec![tenant.tenant_id().to_string(), DEFAULT_TENANT.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_profile_privacy_defaults() {
+        let privacy: PrivacyPolicy = serde_json::from_value(serde_json::json!({
+            "mode": "hybrid",
+            "strictness": "strict",
+            "allow_override": false
+        }))
+        .unwrap();
+        let state = state();
+        let legal = state
+            .for_tenant("legal", || TenantState::from_privacy("legal", &privacy))
+            .await;
+
+        assert_eq!(legal.get_mode().await, PrivacyMode::Strict);
+        assert_eq!(legal.get_detection_method().await, DetectionMethod::Hybrid);
+        assert!(legal.set_detection_method(DetectionMethod::Rules).await.is_err());
+
+        // Without override nothing can be loosened, routing included
+        assert!(legal.set_mode(PrivacyMode::ServiceBypass).await.is_err());
+        assert!(legal.set_routing_mode(RoutingMode::Bypass).await.is_err());
+        assert!(legal.set_mode(PrivacyMode::Strict).await.is_ok());
+        assert_eq!(legal.get_mode().await, PrivacyMode::Strict);
+        assert_eq!(legal.get_routing_mode().await, RoutingMode::Service);
+
+        // Creating again keeps the existing tenant
+        let again = state.for_tenant("legal", || TenantState::new("legal")).await;
+        assert_eq!(again.get_mode().await, PrivacyMode::Strict);
+        assert!(state.existing_tenant("legal").await.is_some());
+        assert!(state.existing_tenant("hr").await.is_none());
+
+        // Unchanged profile defaults leave user settings alone
+        assert!(!again.apply_profile_defaults(ProfileDefaults::from_privacy(&privacy)).await);
+
+        // An edited profile resets the running tenant
+        let edited: PrivacyPolicy = serde_json::from_value(serde_json::json!({
+            "mode": "rules",
+            "allow_override": true
+        }))
+        .unwrap();
+        assert!(again.apply_profile_defaults(ProfileDefaults::from_privacy(&edited)).await);
+        assert_eq!(again.get_mode().await, PrivacyMode::Auto);
+        assert_eq!(again.get_detection_method().await, DetectionMethod::Rules);
+        assert!(again.set_routing_mode(RoutingMode::Bypass).await.is_ok());
+    }
+}
@@ -1,24 +1,38 @@
 // PII detection engine
-// This module implements regex-based pattern matching for 8 entity types
+// This module implements regex-based pattern matching for 9 built-in entity
+// types plus any custom types a rules file defines. Patterns come from a
+// rules file (rules.yaml or rules.json) when one is configured, otherwise
+// from the built-in baseline rules.
 
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+use crate::contract::{is_entity_type_name, MAX_ENTITY_TYPE_LEN};
+use crate::locale::{self, Validator};
+use crate::redaction::MaskingStrategy;
+use crate::secrets;
+
+/// Entity type, serialized as its name (e.g. "CREDIT_CARD")
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EntityType {
     SSN,
     EMAIL,
     PHONE,
-    #[serde(rename = "CREDIT_CARD")]
     CreditCard,
     PERSON,
-    #[serde(rename = "IP_ADDRESS")]
     IpAddress,
-    #[serde(rename = "DATE_OF_BIRTH")]
     DateOfBirth,
-    #[serde(rename = "ACCOUNT_NUMBER")]
     AccountNumber,
+    /// API keys, private keys, tokens and passwords (see secrets.rs)
+    Secret,
+    /// User-defined type (e.g. "EMPLOYEE_ID"), from a rules file or a profile
+    ///
+    /// Names are uppercase letters and underscores, like the built-in ones,
+    /// since they become the prefix of pseudonyms.
+    Custom(String),
 }
 
 impl std::fmt::Display for EntityType {
@@ -32,10 +46,48 @@ impl std::fmt::Display for EntityType {
             EntityType::IpAddress => write!(f, "IP_ADDRESS"),
             EntityType::DateOfBirth => write!(f, "DATE_OF_BIRTH"),
             EntityType::AccountNumber => write!(f, "ACCOUNT_NUMBER"),
+            EntityType::Secret => write!(f, "SECRET"),
+            EntityType::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl std::str::FromStr for EntityType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SSN" => Ok(EntityType::SSN),
+            "EMAIL" => Ok(EntityType::EMAIL),
+            "PHONE" => Ok(EntityType::PHONE),
+            "CREDIT_CARD" => Ok(EntityType::CreditCard),
+            "PERSON" => Ok(EntityType::PERSON),
+            "IP_ADDRESS" => Ok(EntityType::IpAddress),
+            "DATE_OF_BIRTH" => Ok(EntityType::DateOfBirth),
+            "ACCOUNT_NUMBER" => Ok(EntityType::AccountNumber),
+            "SECRET" => Ok(EntityType::Secret),
+            _ if is_entity_type_name(s) => Ok(EntityType::Custom(s.to_string())),
+            _ => Err(format!(
+                "Invalid entity type name: '{}' (use A-Z and _, at most {} characters)",
+                s, MAX_ENTITY_TYPE_LEN
+            )),
         }
     }
 }
 
+impl Serialize for EntityType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Confidence {
     HIGH,
@@ -65,6 +117,24 @@ impl Ord for Confidence {
     }
 }
 
+impl Confidence {
+    /// One level up (HIGH stays HIGH)
+    pub fn promoted(&self) -> Confidence {
+        match self {
+            Confidence::LOW => Confidence::MEDIUM,
+            Confidence::MEDIUM | Confidence::HIGH => Confidence::HIGH,
+        }
+    }
+
+    /// One level down (LOW stays LOW)
+    pub fn demoted(&self) -> Confidence {
+        match self {
+            Confidence::HIGH => Confidence::MEDIUM,
+            Confidence::MEDIUM | Confidence::LOW => Confidence::LOW,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Detection {
     pub start: usize,
@@ -73,6 +143,9 @@ pub struct Detection {
     pub entity_type: EntityType,
     pub confidence: Confidence,
     pub matched_text: String,
+    /// Context keyword found near the match, which promoted its confidence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_keyword: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,13 +155,191 @@ pub struct Pattern {
     pub context_keywords: Option<Vec<String>>,
     pub description: String,
     pub luhn_check: bool,
+    /// Check digit or range check a match must pass (see locale.rs)
+    pub validator: Option<Validator>,
 }
 
+#[derive(Clone)]
 pub struct Rules {
     patterns: HashMap<EntityType, Vec<Pattern>>,
+    /// Masking strategies set by the rules file, overriding the policy's
+    strategies: HashMap<EntityType, MaskingStrategy>,
+    /// Locale packs enabled by the rules file
+    locale_packs: Vec<String>,
+    /// Characters on each side of a match searched for context keywords
+    context_window: usize,
+    /// Version declared by the rules file ("builtin" for the baseline rules)
+    version: String,
+    /// Digest of the rules file contents, to tell edits apart without a version bump
+    digest: Option<String>,
+    /// Rules file the rules were loaded from
+    source: Option<PathBuf>,
+}
+
+/// Version reported for the built-in baseline rules
+pub const BUILTIN_RULES_VERSION: &str = "builtin";
+
+/// Context window (characters on each side of a match) when the rules file
+/// does not set `context_window`
+pub const DEFAULT_CONTEXT_WINDOW: usize = 50;
+
+/// Rules loading errors
+#[derive(Debug)]
+pub enum RulesError {
+    /// Rules file could not be read
+    Io(String),
+    /// Rules file is not valid YAML/JSON
+    Parse(String),
+    /// Rules file parsed but a rule is invalid
+    Invalid(String),
+}
+
+impl std::fmt::Display for RulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesError::Io(msg) => write!(f, "Failed to read rules file: {}", msg),
+            RulesError::Parse(msg) => write!(f, "Failed to parse rules file: {}", msg),
+            RulesError::Invalid(msg) => write!(f, "Invalid rule: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// Rules file format (see deploy/compose/guard-config/rules.yaml)
+///
+/// Entity types other than the built-in ones are custom types. `metadata`,
+/// `display_name` and `category` are documentation only and are not read.
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    version: String,
+    /// Opt-in international patterns (eu, uk, ca, mx, intl)
+    #[serde(default)]
+    locale_packs: Vec<String>,
+    /// Characters on each side of a match searched for context keywords
+    #[serde(default)]
+    context_window: Option<usize>,
+    entity_types: HashMap<String, EntityRules>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntityRules {
+    #[serde(default)]
+    patterns: Vec<PatternRule>,
+    /// Masking strategy (PSEUDONYM, FPE or REDACT); the policy's when not set
+    #[serde(default)]
+    strategy: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatternRule {
+    regex: String,
+    confidence: Confidence,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    context_keywords: Option<Vec<String>>,
+    #[serde(default)]
+    luhn_check: bool,
+    /// Check a match must pass (IBAN, UK_NINO, CA_SIN, MX_CURP, MX_RFC, E164, HIGH_ENTROPY)
+    #[serde(default)]
+    validator: Option<String>,
+    /// Sample values the regex must match
+    #[serde(default)]
+    examples: Vec<String>,
 }
 
 impl Rules {
+    /// Load rules from a rules file
+    ///
+    /// `.json` files are read as JSON, anything else as YAML.
+    pub fn load(path: &Path) -> Result<Self, RulesError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| RulesError::Io(format!("{}: {}", path.display(), e)))?;
+        let mut rules = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&contents)?
+        } else {
+            Self::from_yaml(&contents)?
+        };
+        rules.source = Some(path.to_path_buf());
+        Ok(rules)
+    }
+
+    /// Parse and validate rules from YAML
+    pub fn from_yaml(contents: &str) -> Result<Self, RulesError> {
+        let file: RulesFile =
+            serde_yaml::from_str(contents).map_err(|e| RulesError::Parse(e.to_string()))?;
+        Self::from_file(file, contents)
+    }
+
+    /// Parse and validate rules from JSON
+    pub fn from_json(contents: &str) -> Result<Self, RulesError> {
+        let file: RulesFile =
+            serde_json::from_str(contents).map_err(|e| RulesError::Parse(e.to_string()))?;
+        Self::from_file(file, contents)
+    }
+
+    /// Validate a parsed rules file
+    ///
+    /// Every regex must compile and match its examples, and LOW confidence
+    /// patterns must have context keywords, since they are too broad to be
+    /// used on their own.
+    fn from_file(file: RulesFile, contents: &str) -> Result<Self, RulesError> {
+        if file.version.trim().is_empty() {
+            return Err(RulesError::Invalid("version must not be empty".to_string()));
+        }
+        if file.entity_types.is_empty() {
+            return Err(RulesError::Invalid("no entity types defined".to_string()));
+        }
+
+        let mut patterns: HashMap<EntityType, Vec<Pattern>> = HashMap::new();
+        let mut strategies = HashMap::new();
+        for (name, entity) in file.entity_types {
+            let entity_type: EntityType = name.parse().map_err(RulesError::Invalid)?;
+            if entity.patterns.is_empty() {
+                return Err(RulesError::Invalid(format!("{} has no patterns", name)));
+            }
+            if let Some(strategy) = entity.strategy {
+                let strategy = strategy
+                    .parse()
+                    .map_err(|e| RulesError::Invalid(format!("{}: {}", name, e)))?;
+                strategies.insert(entity_type.clone(), strategy);
+            }
+
+            let mut compiled = Vec::with_capacity(entity.patterns.len());
+            for (i, rule) in entity.patterns.into_iter().enumerate() {
+                compiled.push(compile_rule(rule).map_err(|e| {
+                    RulesError::Invalid(format!("{} pattern {}: {}", name, i + 1, e))
+                })?);
+            }
+            patterns.insert(entity_type, compiled);
+        }
+
+        // Pack patterns come after the file's own, which take precedence
+        for name in &file.locale_packs {
+            let pack = locale::pack(name).ok_or_else(|| {
+                RulesError::Invalid(format!(
+                    "unknown locale pack '{}' (available: {})",
+                    name,
+                    locale::PACKS.join(", ")
+                ))
+            })?;
+            for (entity_type, pattern) in pack {
+                patterns.entry(entity_type).or_default().push(pattern);
+            }
+        }
+
+        Ok(Rules {
+            patterns,
+            strategies,
+            locale_packs: file.locale_packs,
+            context_window: file.context_window.unwrap_or(DEFAULT_CONTEXT_WINDOW),
+            version: file.version,
+            digest: Some(content_digest(contents)),
+            source: None,
+        })
+    }
+
     /// Create rules from default hardcoded patterns
     pub fn default_rules() -> Self {
         let mut patterns: HashMap<EntityType, Vec<Pattern>> = HashMap::new();
@@ -103,6 +354,7 @@ impl Rules {
                     context_keywords: None,
                     description: "US SSN with hyphens (xxx-xx-xxxx)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b\d{3}\s\d{2}\s\d{4}\b").unwrap(),
@@ -110,6 +362,7 @@ impl Rules {
                     context_keywords: None,
                     description: "US SSN with spaces".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b\d{9}\b").unwrap(),
@@ -117,6 +370,7 @@ impl Rules {
                     context_keywords: Some(vec!["SSN".to_string(), "social security".to_string(), "SS#".to_string()]),
                     description: "US SSN no separators (context-dependent)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
             ],
         );
@@ -130,6 +384,7 @@ impl Rules {
                 context_keywords: None,
                 description: "RFC-compliant email".to_string(),
                 luhn_check: false,
+                validator: None,
             }],
         );
 
@@ -143,6 +398,7 @@ impl Rules {
                     context_keywords: None,
                     description: "US phone (xxx-xxx-xxxx)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\(\d{3}\)\s*\d{3}-\d{4}").unwrap(),
@@ -150,6 +406,7 @@ impl Rules {
                     context_keywords: None,
                     description: "US phone with parens ((xxx) xxx-xxxx)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b\d{3}\.\d{3}\.\d{4}\b").unwrap(),
@@ -157,6 +414,7 @@ impl Rules {
                     context_keywords: None,
                     description: "US phone with dots (xxx.xxx.xxxx)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\+1\s?\d{3}\s?\d{3}\s?\d{4}").unwrap(),
@@ -164,6 +422,7 @@ impl Rules {
                     context_keywords: None,
                     description: "US phone with country code (+1 xxx xxx xxxx)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\+\d{1,3}\s?\d{4,14}").unwrap(),
@@ -171,6 +430,7 @@ impl Rules {
                     context_keywords: None,
                     description: "International E.164 format".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
             ],
         );
@@ -186,6 +446,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Visa (16 digits starting with 4)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b5[1-5]\d{14}\b").unwrap(),
@@ -193,6 +454,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Mastercard (16 digits starting with 51-55)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b3[47]\d{13}\b").unwrap(),
@@ -200,6 +462,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Amex (15 digits starting with 34 or 37)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b6(?:011|5\d{2})\d{12}\b").unwrap(),
@@ -207,6 +470,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Discover (16 digits starting with 6011 or 65)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 // NEW: Patterns with separators (hyphens or spaces)
                 Pattern {
@@ -215,6 +479,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Visa with separators (4xxx-xxxx-xxxx-xxxx or spaces)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b5[1-5]\d{2}[- ]?\d{4}[- ]?\d{4}[- ]?\d{4}\b").unwrap(),
@@ -222,6 +487,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Mastercard with separators (51-55xx-xxxx-xxxx-xxxx)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b3[47]\d{2}[- ]?\d{6}[- ]?\d{5}\b").unwrap(),
@@ -229,6 +495,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Amex with separators (34/37xx-xxxxxx-xxxxx)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b6(?:011|5\d{2})[- ]?\d{4}[- ]?\d{4}[- ]?\d{4}\b").unwrap(),
@@ -236,6 +503,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Discover with separators (6011-xxxx-xxxx-xxxx or 65xx)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
                 // Generic catch-all (unchanged)
                 Pattern {
@@ -244,6 +512,7 @@ impl Rules {
                     context_keywords: Some(vec!["card".to_string(), "credit".to_string(), "payment".to_string()]),
                     description: "Generic 13-19 digit card number (context-dependent)".to_string(),
                     luhn_check: true,
+                    validator: None,
                 },
             ],
         );
@@ -260,6 +529,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Name with title (Mr./Mrs./Ms./Dr./Prof.) + first + last".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b[A-Z][a-z]+\s+[A-Z][a-z]+\b").unwrap(),
@@ -275,6 +545,7 @@ impl Rules {
                     ]),
                     description: "Two capitalized words (prone to false positives)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
             ],
         );
@@ -289,6 +560,7 @@ impl Rules {
                     context_keywords: None,
                     description: "IPv4 address".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b(?:[0-9a-fA-F]{1,4}:){7}[0-9a-fA-F]{1,4}\b").unwrap(),
@@ -296,6 +568,7 @@ impl Rules {
                     context_keywords: None,
                     description: "IPv6 address (full)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
             ],
         );
@@ -310,6 +583,7 @@ impl Rules {
                     context_keywords: None,
                     description: "DOB with label (MM/DD/YYYY or variants)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b\d{1,2}/\d{1,2}/\d{2,4}\b").unwrap(),
@@ -317,6 +591,7 @@ impl Rules {
                     context_keywords: Some(vec!["birth".to_string(), "DOB".to_string(), "born".to_string(), "age".to_string()]),
                     description: "Generic date (many false positives)".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
             ],
         );
@@ -331,6 +606,7 @@ impl Rules {
                     context_keywords: None,
                     description: "Account number with label".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
                 Pattern {
                     regex: Regex::new(r"\b\d{8,16}\b").unwrap(),
@@ -338,16 +614,125 @@ impl Rules {
                     context_keywords: Some(vec!["account".to_string(), "acct".to_string(), "number".to_string(), "ID".to_string()]),
                     description: "Generic 8-16 digit number".to_string(),
                     luhn_check: false,
+                    validator: None,
                 },
             ],
         );
 
-        Rules { patterns }
+        // SECRET patterns
+        patterns.insert(EntityType::Secret, secrets::patterns());
+
+        Rules {
+            patterns,
+            strategies: HashMap::new(),
+            locale_packs: Vec::new(),
+            context_window: DEFAULT_CONTEXT_WINDOW,
+            version: BUILTIN_RULES_VERSION.to_string(),
+            digest: None,
+            source: None,
+        }
     }
 
     pub fn count(&self) -> usize {
         self.patterns.values().map(|v| v.len()).sum()
     }
+
+    /// These rules plus extra patterns (e.g. a role profile's rules)
+    pub fn extend(&self, extra: impl IntoIterator<Item = (EntityType, Pattern)>) -> Rules {
+        let mut rules = self.clone();
+        for (entity_type, pattern) in extra {
+            rules.patterns.entry(entity_type).or_default().push(pattern);
+        }
+        rules
+    }
+
+    /// Masking strategies set by the rules file
+    pub fn strategies(&self) -> &HashMap<EntityType, MaskingStrategy> {
+        &self.strategies
+    }
+
+    /// Locale packs enabled by the rules file
+    pub fn locale_packs(&self) -> &[String] {
+        &self.locale_packs
+    }
+
+    /// Characters on each side of a match searched for context keywords
+    pub fn context_window(&self) -> usize {
+        self.context_window
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+}
+
+/// HIGH confidence pattern of an extra rule sent with a request
+pub fn request_pattern(entity_type: &str, regex: &str) -> Result<(EntityType, Pattern), String> {
+    let entity_type: EntityType = entity_type.parse()?;
+    let regex = Regex::new(regex).map_err(|e| format!("invalid regex: {}", e))?;
+    let pattern = Pattern {
+        regex,
+        confidence: Confidence::HIGH,
+        context_keywords: None,
+        description: format!("Request rule for {}", entity_type),
+        luhn_check: false,
+        validator: None,
+    };
+    Ok((entity_type, pattern))
+}
+
+/// Short digest of a rules file's contents
+pub fn content_digest(contents: &str) -> String {
+    let digest = Sha256::digest(contents.as_bytes());
+    digest[..6].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn compile_rule(rule: PatternRule) -> Result<Pattern, String> {
+    let regex = Regex::new(&rule.regex).map_err(|e| format!("invalid regex: {}", e))?;
+
+    let context_keywords = match rule.context_keywords {
+        Some(keywords) if keywords.iter().any(|kw| kw.trim().is_empty()) => {
+            return Err("context keywords must not be blank".to_string());
+        }
+        Some(keywords) if keywords.is_empty() => None,
+        keywords => keywords,
+    };
+    if rule.confidence == Confidence::LOW && context_keywords.is_none() {
+        return Err("LOW confidence patterns require context_keywords".to_string());
+    }
+
+    let validator = rule
+        .validator
+        .map(|name| name.parse::<Validator>())
+        .transpose()?;
+
+    if let Some(example) = rule.examples.iter().find(|ex| !regex.is_match(ex)) {
+        return Err(format!("regex does not match example '{}'", example));
+    }
+    if let Some(example) = rule
+        .examples
+        .iter()
+        .find(|ex| validator.is_some_and(|v| !v.is_valid(ex)))
+    {
+        return Err(format!("example '{}' fails the validator", example));
+    }
+
+    Ok(Pattern {
+        regex,
+        confidence: rule.confidence,
+        context_keywords,
+        description: rule.description,
+        luhn_check: rule.luhn_check,
+        validator,
+    })
 }
 
 /// Luhn algorithm for credit card validation
@@ -388,23 +773,25 @@ pub fn detect(text: &str, rules: &Rules) -> Vec<Detection> {
                     continue;
                 }
 
-                // Check context keywords if required
+                // Skip if the match fails its check digit or range check
+                if pattern.validator.is_some_and(|v| !v.is_valid(&matched_text)) {
+                    continue;
+                }
+
+                // Score by context: a keyword nearby promotes the match, none demotes it
+                let mut confidence = pattern.confidence.clone();
+                let mut context_keyword = None;
                 if let Some(keywords) = &pattern.context_keywords {
-                    // Look for keywords in surrounding text (±50 chars)
-                    let start_ctx = mat.start().saturating_sub(50);
-                    let end_ctx = (mat.end() + 50).min(text.len());
-                    let context = &text[start_ctx..end_ctx];
-                    let context_lower = context.to_lowercase();
-
-                    let has_keyword = keywords
-                        .iter()
-                        .any(|kw| context_lower.contains(&kw.to_lowercase()));
-
-                    // For LOW confidence, require context keyword
-                    if pattern.confidence == Confidence::LOW && !has_keyword {
-                        continue;
+                    match nearest_keyword(text, mat.start(), mat.end(), keywords, rules.context_window) {
+                        Some(keyword) => {
+                            confidence = confidence.promoted();
+                            context_keyword = Some(keyword.to_string());
+                        }
+                        // LOW confidence patterns are too broad to report without one
+                        None if pattern.confidence == Confidence::LOW => continue,
+                        None => confidence = confidence.demoted(),
                     }
-                    
+
                     // For LOW/MEDIUM confidence with keywords (generic patterns), 
                     // skip if already detected by higher confidence pattern (check overlap)
                     if pattern.confidence == Confidence::LOW || pattern.confidence == Confidence::MEDIUM {
@@ -425,8 +812,9 @@ pub fn detect(text: &str, rules: &Rules) -> Vec<Detection> {
                     start: mat.start(),
                     end: mat.end(),
                     entity_type: entity_type.clone(),
-                    confidence: pattern.confidence.clone(),
+                    confidence,
                     matched_text,
+                    context_keyword,
                 });
             }
         }
@@ -437,6 +825,69 @@ pub fn detect(text: &str, rules: &Rules) -> Vec<Detection> {
     detections
 }
 
+/// Context keyword closest to a match, within `window` characters of it
+///
+/// Keywords match case-insensitively as whole words (a trailing "s" is
+/// allowed, so "accounts" counts for "account" but "monkey" not for "key").
+/// A keyword inside the match itself (e.g. "Contact John") is at distance 0.
+fn nearest_keyword<'a>(
+    text: &str,
+    start: usize,
+    end: usize,
+    keywords: &'a [String],
+    window: usize,
+) -> Option<&'a str> {
+    let context_start = if window == 0 {
+        start
+    } else {
+        text[..start]
+            .char_indices()
+            .rev()
+            .nth(window - 1)
+            .map_or(0, |(i, _)| i)
+    };
+    let context_end = text[end..]
+        .char_indices()
+        .nth(window)
+        .map_or(text.len(), |(i, _)| end + i);
+    let context = text[context_start..context_end].to_lowercase();
+    let (match_start, match_end) = (start - context_start, end - context_start);
+
+    let mut nearest: Option<(usize, &str)> = None;
+    for keyword in keywords {
+        let needle = keyword.to_lowercase();
+        let distance = keyword_positions(&context, &needle)
+            .map(|pos| {
+                if pos + needle.len() <= match_start {
+                    match_start - (pos + needle.len())
+                } else {
+                    pos.saturating_sub(match_end)
+                }
+            })
+            .min();
+        if let Some(distance) = distance {
+            if nearest.is_none_or(|(best, _)| distance < best) {
+                nearest = Some((distance, keyword.as_str()));
+            }
+        }
+    }
+    nearest.map(|(_, keyword)| keyword)
+}
+
+/// Positions of `keyword` as a whole word in `haystack`
+fn keyword_positions<'a>(haystack: &'a str, keyword: &'a str) -> impl Iterator<Item = usize> + 'a {
+    haystack.match_indices(keyword).filter_map(move |(pos, _)| {
+        let starts_word = !haystack[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let rest = &haystack[pos + keyword.len()..];
+        let rest = rest.strip_prefix('s').unwrap_or(rest);
+        let ends_word = !rest.chars().next().is_some_and(char::is_alphanumeric);
+        (starts_word && ends_word).then_some(pos)
+    })
+}
+
 /// Hybrid detection: combine regex-based and NER model results
 /// This is the async version that integrates with OllamaClient
 pub async fn detect_hybrid(
@@ -497,6 +948,7 @@ fn merge_detections(
                         entity_type,
                         confidence: Confidence::HIGH,
                         matched_text: model_entity.text.clone(),
+                        context_keyword: None,
                     });
                 }
             }
@@ -774,6 +1226,7 @@ mod tests {
             entity_type: EntityType::EMAIL,
             confidence: Confidence::HIGH,
             matched_text: "john@example.com".to_string(),
+            context_keyword: None,
         }];
 
         let model_entities = vec![NerEntity {
@@ -821,6 +1274,7 @@ mod tests {
             entity_type: EntityType::SSN,
             confidence: Confidence::HIGH,
             matched_text: "123-45-6789".to_string(),
+            context_keyword: None,
         }];
 
         let model_entities = vec![]; // Model missed this
@@ -847,6 +1301,7 @@ mod tests {
                 entity_type: EntityType::EMAIL,
                 confidence: Confidence::HIGH,
                 matched_text: "alice@test.com".to_string(),
+                context_keyword: None,
             },
             Detection {
                 start: 39,
@@ -854,6 +1309,7 @@ mod tests {
                 entity_type: EntityType::SSN,
                 confidence: Confidence::HIGH,
                 matched_text: "123-45-6789".to_string(),
+                context_keyword: None,
             },
         ];
 
@@ -928,4 +1384,230 @@ mod tests {
             .find(|d| matches!(d.entity_type, EntityType::EMAIL));
         assert!(email_det.is_some());
     }
+
+    const SAMPLE_RULES: &str = r#"
+version: "2.1"
+metadata:
+  description: "Test rules"
+entity_types:
+  SSN:
+    display_name: "Social Security Number"
+    patterns:
+      - regex: '\b\d{3}-\d{2}-\d{4}\b'
+        confidence: HIGH
+        description: "US SSN with hyphens"
+        examples: ["123-45-6789"]
+      - regex: '\b\d{9}\b'
+        confidence: LOW
+        description: "US SSN no separators"
+        context_keywords: ["SSN"]
+  CREDIT_CARD:
+    patterns:
+      - regex: '\b4\d{15}\b'
+        confidence: HIGH
+        luhn_check: true
+"#;
+
+    #[test]
+    fn test_rules_from_yaml() {
+        let rules = Rules::from_yaml(SAMPLE_RULES).unwrap();
+        assert_eq!(rules.count(), 3);
+        assert_eq!(rules.version(), "2.1");
+        assert_eq!(rules.digest().unwrap().len(), 12);
+        assert!(rules.source().is_none());
+
+        let detections = detect("SSN 123456789, card 4532015112830366 or 4532015112830367", &rules);
+        let types: Vec<_> = detections.iter().map(|d| d.entity_type.to_string()).collect();
+        assert_eq!(types, vec!["SSN", "CREDIT_CARD"]);
+        // LOW pattern promoted by its keyword
+        assert_eq!(detections[0].confidence, Confidence::MEDIUM);
+        assert_eq!(detections[0].context_keyword.as_deref(), Some("SSN"));
+        assert_eq!(rules.context_window(), DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn test_context_keywords_score_confidence() {
+        let rules = Rules::from_yaml(
+            "version: '1'\nentity_types:\n  EMPLOYEE_ID:\n    patterns:\n      - regex: '\\bE\\d{6}\\b'\n        confidence: MEDIUM\n        context_keywords: [employee, badge]\n",
+        )
+        .unwrap();
+        let scored = |text: &str| {
+            let detections = detect(text, &rules);
+            assert_eq!(detections.len(), 1, "{}", text);
+            (detections[0].confidence.clone(), detections[0].context_keyword.clone())
+        };
+
+        // Promoted by the nearest keyword, demoted without one
+        assert_eq!(scored("Employee badge E123456"), (Confidence::HIGH, Some("badge".to_string())));
+        assert_eq!(scored("employees: E123456"), (Confidence::HIGH, Some("employee".to_string())));
+        assert_eq!(scored("Ticket E123456"), (Confidence::LOW, None));
+        // Whole words only
+        assert_eq!(scored("Badgeless E123456"), (Confidence::LOW, None));
+    }
+
+    #[test]
+    fn test_context_window_is_configurable() {
+        let yaml = |window: usize| {
+            format!(
+                "version: '1'\ncontext_window: {}\nentity_types:\n  SSN:\n    patterns:\n      - regex: '\\b\\d{{9}}\\b'\n        confidence: LOW\n        context_keywords: [SSN]\n",
+                window
+            )
+        };
+        let text = "SSN (see the attached form): 123456789";
+
+        let narrow = Rules::from_yaml(&yaml(10)).unwrap();
+        assert_eq!(narrow.context_window(), 10);
+        assert!(detect(text, &narrow).is_empty());
+
+        let wide = Rules::from_yaml(&yaml(40)).unwrap();
+        assert_eq!(detect(text, &wide)[0].confidence, Confidence::MEDIUM);
+
+        // Multi-byte text around the match
+        assert_eq!(detect("SSN éééé 123456789 ü", &narrow).len(), 1);
+    }
+
+    #[test]
+    fn test_confidence_promote_demote() {
+        assert_eq!(Confidence::LOW.promoted(), Confidence::MEDIUM);
+        assert_eq!(Confidence::HIGH.promoted(), Confidence::HIGH);
+        assert_eq!(Confidence::HIGH.demoted(), Confidence::MEDIUM);
+        assert_eq!(Confidence::LOW.demoted(), Confidence::LOW);
+    }
+
+    #[test]
+    fn test_rules_from_json() {
+        let json = r#"{
+            "version": "1",
+            "entity_types": {
+                "EMAIL": {"patterns": [{"regex": "[a-z]+@[a-z]+\\.com", "confidence": "HIGH"}]}
+            }
+        }"#;
+        let rules = Rules::from_json(json).unwrap();
+        assert_eq!(rules.count(), 1);
+        assert_eq!(detect("mail bob@example.com", &rules)[0].matched_text, "bob@example.com");
+    }
+
+    #[test]
+    fn test_rules_digest_tracks_contents() {
+        let edited = SAMPLE_RULES.replace(r"\b4\d{15}\b", r"\b4\d{12,15}\b");
+        let a = Rules::from_yaml(SAMPLE_RULES).unwrap();
+        let b = Rules::from_yaml(&edited).unwrap();
+        assert_eq!(a.version(), b.version());
+        assert_ne!(a.digest(), b.digest());
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let invalid = |yaml: &str| Rules::from_yaml(yaml).err().unwrap().to_string();
+
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    patterns:\n      - regex: '(\\d'\n        confidence: HIGH\n")
+            .contains("SSN pattern 1: invalid regex"));
+        assert!(invalid("version: '1'\nentity_types:\n  passport-no:\n    patterns:\n      - regex: 'x'\n        confidence: HIGH\n")
+            .contains("Invalid entity type name: 'passport-no'"));
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    strategy: HIDE\n    patterns:\n      - regex: 'x'\n        confidence: HIGH\n")
+            .contains("SSN: Invalid masking strategy: HIDE"));
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    patterns:\n      - regex: '\\d{9}'\n        confidence: LOW\n")
+            .contains("LOW confidence patterns require context_keywords"));
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    patterns:\n      - regex: '\\d{9}'\n        confidence: HIGH\n        examples: ['12-34']\n")
+            .contains("does not match example '12-34'"));
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    patterns: []\n").contains("SSN has no patterns"));
+        assert!(invalid("version: '1'\nlocale_packs: [fr]\nentity_types:\n  SSN:\n    patterns:\n      - regex: 'x'\n        confidence: HIGH\n")
+            .contains("unknown locale pack 'fr'"));
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    patterns:\n      - regex: 'x'\n        confidence: HIGH\n        validator: VIN\n")
+            .contains("Unknown validator: VIN"));
+        assert!(invalid("version: '1'\nentity_types:\n  IBAN:\n    patterns:\n      - regex: '[A-Z]{2}\\d{2}\\w+'\n        confidence: HIGH\n        validator: IBAN\n        examples: ['DE00123412341234']\n")
+            .contains("example 'DE00123412341234' fails the validator"));
+        assert!(invalid("version: ''\nentity_types:\n  SSN:\n    patterns: []\n").contains("version"));
+        assert!(invalid("version: '1'\nentity_types:\n  SSN:\n    patterns:\n      - regex: 'x'\n        confidence: SURE\n")
+            .starts_with("Failed to parse rules file"));
+    }
+
+    #[test]
+    fn test_load_rules_file() {
+        let path = std::env::temp_dir().join(format!("guard-rules-{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, SAMPLE_RULES).unwrap();
+        let rules = Rules::load(&path).unwrap();
+        assert_eq!(rules.source(), Some(path.as_path()));
+        assert_eq!(rules.digest(), Some(content_digest(SAMPLE_RULES).as_str()));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(Rules::load(&path), Err(RulesError::Io(_))));
+    }
+
+    #[test]
+    fn test_deployed_rules_file_is_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../deploy/compose/guard-config/rules.yaml");
+        let rules = Rules::load(&path).unwrap();
+        assert_eq!(rules.version(), "1.0");
+        assert_eq!(rules.patterns.len(), 9);
+
+        // Same SECRET patterns as the built-in rules
+        let builtin: Vec<_> = secrets::patterns().iter().map(|p| p.regex.as_str().to_string()).collect();
+        let deployed: Vec<_> = rules.patterns[&EntityType::Secret].iter().map(|p| p.regex.as_str().to_string()).collect();
+        assert_eq!(deployed, builtin);
+    }
+
+    #[test]
+    fn test_entity_type_names() {
+        assert_eq!("CREDIT_CARD".parse::<EntityType>().unwrap(), EntityType::CreditCard);
+        assert_eq!(
+            "EMPLOYEE_ID".parse::<EntityType>().unwrap(),
+            EntityType::Custom("EMPLOYEE_ID".to_string())
+        );
+        assert!("employee_id".parse::<EntityType>().is_err());
+        assert!("EMPLOYEE_ID2".parse::<EntityType>().is_err());
+        assert!("_ID".parse::<EntityType>().is_err());
+        assert!("A".repeat(MAX_ENTITY_TYPE_LEN + 1).parse::<EntityType>().is_err());
+
+        // Serialized as the name, built-in or custom
+        let types = vec![EntityType::IpAddress, EntityType::Custom("PROJECT_CODENAME".to_string())];
+        let json = serde_json::to_string(&types).unwrap();
+        assert_eq!(json, r#"["IP_ADDRESS","PROJECT_CODENAME"]"#);
+        assert_eq!(serde_json::from_str::<Vec<EntityType>>(&json).unwrap(), types);
+    }
+
+    #[test]
+    fn test_custom_entity_types_from_rules_file() {
+        let yaml = r#"
+version: "1"
+entity_types:
+  EMPLOYEE_ID:
+    strategy: REDACT
+    patterns:
+      - regex: '\bEMP-\d{6}\b'
+        confidence: HIGH
+        examples: ["EMP-004211"]
+  PROJECT_CODENAME:
+    patterns:
+      - regex: '\bProject [A-Z][a-z]+\b'
+        confidence: LOW
+        context_keywords: ["codename"]
+"#;
+        let rules = Rules::from_yaml(yaml).unwrap();
+        let detections = detect("codename Project Falcon, owner EMP-004211", &rules);
+        let types: Vec<_> = detections.iter().map(|d| d.entity_type.to_string()).collect();
+        assert_eq!(types, vec!["PROJECT_CODENAME", "EMPLOYEE_ID"]);
+        assert_eq!(
+            rules.strategies().get(&EntityType::Custom("EMPLOYEE_ID".to_string())),
+            Some(&MaskingStrategy::Redact)
+        );
+    }
+
+    #[test]
+    fn test_request_rules_extend_rules() {
+        let base = Rules::default_rules();
+        let (entity_type, pattern) = request_pattern("CUSTOMER_NUMBER", r"\bCUST-\d{5}\b").unwrap();
+        let rules = base.extend([(entity_type.clone(), pattern)]);
+        assert_eq!(rules.count(), base.count() + 1);
+
+        let detections = detect("Customer CUST-12345", &rules);
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].entity_type, entity_type);
+        assert_eq!(detections[0].confidence, Confidence::HIGH);
+        assert!(detect("Customer CUST-12345", &base).is_empty());
+
+        assert!(request_pattern("CUSTOMER_NUMBER", r"\d+(?=\s*USD)").unwrap_err().starts_with("invalid regex"));
+        assert!(request_pattern("customer", r"\d+").is_err());
+    }
 }
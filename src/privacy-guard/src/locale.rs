@@ -0,0 +1,417 @@
+// Locale packs: opt-in detection rules for non-US identifiers
+// A rules file enables packs with `locale_packs: [eu, uk, ca, mx, intl]`.
+// Where an identifier has a check digit or a reserved range, matches are
+// validated so random numbers of the right shape are not reported.
+
+use regex::Regex;
+
+use crate::detection::{Confidence, EntityType, Pattern};
+
+/// Names of the available packs
+pub const PACKS: [&str; 5] = ["eu", "uk", "ca", "mx", "intl"];
+
+/// Check applied to a match before it is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validator {
+    /// IBAN (ISO 13616 mod-97)
+    Iban,
+    /// UK National Insurance number (reserved prefixes)
+    UkNino,
+    /// Canadian Social Insurance Number (Luhn, issued ranges)
+    CaSin,
+    /// Mexican CURP (check digit)
+    MxCurp,
+    /// Mexican RFC (check digit)
+    MxRfc,
+    /// E.164 phone number (at most 15 digits)
+    E164,
+    /// Random-looking secret value (see secrets.rs)
+    HighEntropy,
+}
+
+impl std::str::FromStr for Validator {
+    type Err = String;
+
+    /// Parse a validator as written in rules files (e.g. IBAN, MX_CURP)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "IBAN" => Ok(Validator::Iban),
+            "UK_NINO" => Ok(Validator::UkNino),
+            "CA_SIN" => Ok(Validator::CaSin),
+            "MX_CURP" => Ok(Validator::MxCurp),
+            "MX_RFC" => Ok(Validator::MxRfc),
+            "E164" => Ok(Validator::E164),
+            "HIGH_ENTROPY" => Ok(Validator::HighEntropy),
+            _ => Err(format!("Unknown validator: {}", s)),
+        }
+    }
+}
+
+impl Validator {
+    /// Whether a match is a valid identifier
+    pub fn is_valid(&self, text: &str) -> bool {
+        match self {
+            Validator::Iban => is_iban_valid(text),
+            Validator::UkNino => is_nino_valid(text),
+            Validator::CaSin => is_sin_valid(text),
+            Validator::MxCurp => is_curp_valid(text),
+            Validator::MxRfc => is_rfc_valid(text),
+            Validator::E164 => is_e164_valid(text),
+            Validator::HighEntropy => crate::secrets::is_high_entropy(text),
+        }
+    }
+}
+
+/// Patterns of a pack, or None for an unknown pack
+pub fn pack(name: &str) -> Option<Vec<(EntityType, Pattern)>> {
+    let patterns = match name {
+        "eu" => vec![
+            (
+                custom("IBAN"),
+                pattern(
+                    r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,4})?\b",
+                    Confidence::HIGH,
+                    None,
+                    "IBAN (mod-97 checked)",
+                    Some(Validator::Iban),
+                ),
+            ),
+            (
+                custom("EU_VAT"),
+                pattern(
+                    r"\b(?:ATU\d{8}|BE[01]\d{9}|BG\d{9,10}|CY\d{8}[A-Z]|CZ\d{8,10}|DE\d{9}|DK\d{8}|EE\d{9}|EL\d{9}|ES[A-Z0-9]\d{7}[A-Z0-9]|FI\d{8}|FR[A-HJ-NP-Z0-9]{2}\d{9}|HR\d{11}|HU\d{8}|IE\d{7}[A-W][A-IW]?|IE\d[A-Z+*]\d{5}[A-W]|IT\d{11}|LT(?:\d{9}|\d{12})|LU\d{8}|LV\d{11}|MT\d{8}|NL\d{9}B\d{2}|PL\d{10}|PT\d{9}|RO\d{2,10}|SE\d{12}|SI\d{8}|SK\d{10})\b",
+                    Confidence::MEDIUM,
+                    Some(&["VAT", "TVA", "USt", "IVA", "BTW", "MwSt", "MOMS", "ALV", "tax"]),
+                    "EU VAT identification number (format only)",
+                    None,
+                ),
+            ),
+        ],
+        "uk" => vec![(
+            custom("UK_NINO"),
+            pattern(
+                r"\b[A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z] ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b",
+                Confidence::MEDIUM,
+                Some(&["NI", "NINO", "national insurance"]),
+                "UK National Insurance number",
+                Some(Validator::UkNino),
+            ),
+        )],
+        "ca" => vec![
+            (
+                custom("CA_SIN"),
+                pattern(
+                    r"\b\d{3}[- ]\d{3}[- ]\d{3}\b",
+                    Confidence::MEDIUM,
+                    Some(&["SIN", "social insurance", "NAS", "assurance sociale"]),
+                    "Canadian SIN with separators (Luhn checked)",
+                    Some(Validator::CaSin),
+                ),
+            ),
+            (
+                custom("CA_SIN"),
+                pattern(
+                    r"\b\d{9}\b",
+                    Confidence::LOW,
+                    Some(&["SIN", "social insurance", "NAS", "assurance sociale"]),
+                    "Canadian SIN no separators (Luhn checked, context-dependent)",
+                    Some(Validator::CaSin),
+                ),
+            ),
+        ],
+        "mx" => vec![
+            (
+                custom("MX_CURP"),
+                pattern(
+                    r"\b[A-Z][AEIOUX][A-Z]{2}\d{6}[HMX](?:AS|BC|BS|CC|CL|CM|CS|CH|DF|DG|GT|GR|HG|JC|MC|MN|MS|NT|NL|OC|PL|QT|QR|SP|SL|SR|TC|TS|TL|VZ|YN|ZS|NE)[B-DF-HJ-NP-TV-Z]{3}[A-Z\d]\d\b",
+                    Confidence::HIGH,
+                    None,
+                    "Mexican CURP (check digit checked)",
+                    Some(Validator::MxCurp),
+                ),
+            ),
+            (
+                custom("MX_RFC"),
+                pattern(
+                    r"\b[A-ZÑ&]{3,4}\d{6}[A-Z\d]{2}[A\d]\b",
+                    Confidence::HIGH,
+                    None,
+                    "Mexican RFC, persons and companies (check digit checked)",
+                    Some(Validator::MxRfc),
+                ),
+            ),
+        ],
+        "intl" => vec![
+            (
+                custom("PASSPORT"),
+                pattern(
+                    r"(?i:passport|passeport|pasaporte|reisepass)(?i:\s+(?:no\.?|number|num\.?|#))?:?\s*[A-Z0-9]{6,9}\b",
+                    Confidence::HIGH,
+                    None,
+                    "Passport number with label",
+                    None,
+                ),
+            ),
+            (
+                custom("PASSPORT"),
+                pattern(
+                    r"\b[A-Z]{1,2}\d{6,8}\b",
+                    Confidence::LOW,
+                    Some(&["passport", "travel document", "passeport", "pasaporte"]),
+                    "Passport number (context-dependent)",
+                    None,
+                ),
+            ),
+            (
+                EntityType::PHONE,
+                pattern(
+                    r"\+[1-9]\d{7,14}\b",
+                    Confidence::HIGH,
+                    None,
+                    "E.164 phone number",
+                    Some(Validator::E164),
+                ),
+            ),
+            (
+                EntityType::PHONE,
+                pattern(
+                    r"\+[1-9]\d{0,2}(?:[ -]\d{1,4}){2,5}\b",
+                    Confidence::MEDIUM,
+                    None,
+                    "International phone with separators (E.164 length checked)",
+                    Some(Validator::E164),
+                ),
+            ),
+        ],
+        _ => return None,
+    };
+    Some(patterns)
+}
+
+fn custom(name: &str) -> EntityType {
+    EntityType::Custom(name.to_string())
+}
+
+fn pattern(
+    regex: &str,
+    confidence: Confidence,
+    context_keywords: Option<&[&str]>,
+    description: &str,
+    validator: Option<Validator>,
+) -> Pattern {
+    Pattern {
+        regex: Regex::new(regex).unwrap(),
+        confidence,
+        context_keywords: context_keywords.map(|kws| kws.iter().map(|kw| kw.to_string()).collect()),
+        description: description.to_string(),
+        luhn_check: false,
+        validator,
+    }
+}
+
+/// IBAN: country code, check digits and account, mod 97 == 1
+fn is_iban_valid(text: &str) -> bool {
+    let iban: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&iban.len()) || !iban.is_ascii() {
+        return false;
+    }
+
+    // Move the country code and check digits to the end, letters count 10-35
+    let rearranged = iban[4..].chars().chain(iban[..4].chars());
+    let mut remainder = 0u32;
+    for c in rearranged {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+/// NINO: prefixes BG, GB, NK, KN, TN, NT and ZZ are never issued
+fn is_nino_valid(text: &str) -> bool {
+    let prefix: String = text.chars().take(2).collect();
+    !matches!(prefix.as_str(), "BG" | "GB" | "NK" | "KN" | "TN" | "NT" | "ZZ")
+}
+
+/// SIN: Luhn, and no SIN starts with 0 or 8
+fn is_sin_valid(text: &str) -> bool {
+    let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.len() == 9 && !digits.starts_with(['0', '8']) && luhn(&digits)
+}
+
+/// CURP: weighted sum of the first 17 characters gives the 18th
+fn is_curp_valid(text: &str) -> bool {
+    const ALPHABET: &str = "0123456789ABCDEFGHIJKLMNÑOPQRSTUVWXYZ";
+
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() != 18 {
+        return false;
+    }
+    let mut sum = 0;
+    for (i, c) in chars[..17].iter().enumerate() {
+        let Some(value) = ALPHABET.chars().position(|a| a == *c) else {
+            return false;
+        };
+        sum += value * (18 - i);
+    }
+    let check = (10 - sum % 10) % 10;
+    chars[17].to_digit(10) == Some(check as u32)
+}
+
+/// RFC: weighted mod-11 sum of the first 12 characters (companies padded
+/// with a leading space) gives the 13th
+fn is_rfc_valid(text: &str) -> bool {
+    const ALPHABET: &str = "0123456789ABCDEFGHIJKLMN&OPQRSTUVWXYZ Ñ";
+
+    let mut chars: Vec<char> = text.chars().collect();
+    if chars.len() == 12 {
+        chars.insert(0, ' ');
+    }
+    if chars.len() != 13 {
+        return false;
+    }
+    let mut sum = 0;
+    for (i, c) in chars[..12].iter().enumerate() {
+        let Some(value) = ALPHABET.chars().position(|a| a == *c) else {
+            return false;
+        };
+        sum += value * (13 - i);
+    }
+    let check = match 11 - sum % 11 {
+        11 => '0',
+        10 => 'A',
+        digit => char::from_digit(digit as u32, 10).unwrap_or('0'),
+    };
+    chars[12] == check
+}
+
+/// E.164: at most 15 digits including the country code
+fn is_e164_valid(text: &str) -> bool {
+    let digits = text.chars().filter(|c| c.is_ascii_digit()).count();
+    (8..=15).contains(&digits)
+}
+
+fn luhn(digits: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in digits.chars().rev().enumerate() {
+        let Some(mut d) = c.to_digit(10) else {
+            return false;
+        };
+        if i % 2 == 1 {
+            d *= 2;
+            if d > 9 {
+                d -= 9;
+            }
+        }
+        sum += d;
+    }
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::{detect, Rules};
+
+    fn pack_rules(name: &str) -> Rules {
+        let yaml = format!(
+            "version: '1'\nlocale_packs: [{}]\nentity_types:\n  EMAIL:\n    patterns:\n      - regex: '[a-z]+@[a-z]+\\.com'\n        confidence: HIGH\n",
+            name
+        );
+        Rules::from_yaml(&yaml).unwrap()
+    }
+
+    fn found(text: &str, rules: &Rules, entity_type: &str) -> Vec<String> {
+        detect(text, rules)
+            .into_iter()
+            .filter(|d| d.entity_type.to_string() == entity_type)
+            .map(|d| d.matched_text)
+            .collect()
+    }
+
+    #[test]
+    fn test_all_packs_exist() {
+        for name in PACKS {
+            assert!(!pack(name).unwrap().is_empty(), "pack {}", name);
+        }
+        assert!(pack("us").is_none());
+    }
+
+    #[test]
+    fn test_eu_pack() {
+        assert!(is_iban_valid("GB82 WEST 1234 5698 7654 32"));
+        assert!(is_iban_valid("DE89370400440532013000"));
+        assert!(!is_iban_valid("GB82 WEST 1234 5698 7654 33"));
+        assert!(!is_iban_valid("DE89"));
+
+        let rules = pack_rules("eu");
+        let text = "Pay DE89 3704 0044 0532 0130 00, not DE89 3704 0044 0532 0130 01. VAT no. DE123456789";
+        assert_eq!(found(text, &rules, "IBAN"), vec!["DE89 3704 0044 0532 0130 00"]);
+        assert_eq!(found(text, &rules, "EU_VAT"), vec!["DE123456789"]);
+    }
+
+    #[test]
+    fn test_uk_pack() {
+        assert!(is_nino_valid("AB123456C"));
+        assert!(!is_nino_valid("GB123456C"));
+
+        let rules = pack_rules("uk");
+        let text = "NI number AB 12 34 56 C, old GB 12 34 56 C, bad QQ123456C";
+        assert_eq!(found(text, &rules, "UK_NINO"), vec!["AB 12 34 56 C"]);
+    }
+
+    #[test]
+    fn test_ca_pack() {
+        assert!(is_sin_valid("130 692 544"));
+        assert!(!is_sin_valid("130 692 545"));
+        // Luhn valid, but 8 is for business numbers
+        assert!(!is_sin_valid("846 454 288"));
+
+        let rules = pack_rules("ca");
+        assert_eq!(found("SIN 130-692-544", &rules, "CA_SIN"), vec!["130-692-544"]);
+        assert!(found("Order 130-692-545", &rules, "CA_SIN").is_empty());
+        // Without separators only with context
+        assert_eq!(found("social insurance 130692544", &rules, "CA_SIN"), vec!["130692544"]);
+        assert!(found("ref 130692544", &rules, "CA_SIN").is_empty());
+    }
+
+    #[test]
+    fn test_mx_pack() {
+        assert!(is_curp_valid("HEGG560427MVZRRL04"));
+        assert!(!is_curp_valid("HEGG560427MVZRRL05"));
+        assert!(is_rfc_valid("GODE561231GR8"));
+        assert!(is_rfc_valid("ABC680524P73"));
+        assert!(!is_rfc_valid("GODE561231GR9"));
+
+        let rules = pack_rules("mx");
+        let text = "CURP HEGG560427MVZRRL04 RFC GODE561231GR8 empresa ABC680524P73 otro GODE561231GR9";
+        assert_eq!(found(text, &rules, "MX_CURP"), vec!["HEGG560427MVZRRL04"]);
+        assert_eq!(found(text, &rules, "MX_RFC"), vec!["GODE561231GR8", "ABC680524P73"]);
+    }
+
+    #[test]
+    fn test_intl_pack() {
+        assert!(is_e164_valid("+447911123456"));
+        assert!(!is_e164_valid("+1234567890123456"));
+
+        let rules = pack_rules("intl");
+        assert_eq!(found("Passport No: X1234567", &rules, "PASSPORT"), vec!["Passport No: X1234567"]);
+        assert_eq!(found("travel document AB123456", &rules, "PASSPORT"), vec!["AB123456"]);
+        assert!(found("Model AB123456", &rules, "PASSPORT").is_empty());
+
+        let phones = found("Call +447911123456 or +33 6 12 34 56 78 or +1234567890123456", &rules, "PHONE");
+        assert_eq!(phones, vec!["+447911123456", "+33 6 12 34 56 78"]);
+    }
+
+    #[test]
+    fn test_validator_names() {
+        assert_eq!("iban".parse::<Validator>().unwrap(), Validator::Iban);
+        assert_eq!("MX_CURP".parse::<Validator>().unwrap(), Validator::MxCurp);
+        assert!("ssn".parse::<Validator>().is_err());
+    }
+}
@@ -67,17 +67,11 @@ pub fn pseudonymize_with_salt(
 }
 
 /// Verify if a pseudonym is valid format
+///
+/// The format is part of the guard contract so clients can recognise
+/// pseudonyms too.
 pub fn is_valid_pseudonym(pseudonym: &str) -> bool {
-    // Format: {TYPE}_{16_hex_chars}
-    // Find the last underscore to separate type from hash
-    match pseudonym.rfind('_') {
-        Some(pos) => {
-            let hash_part = &pseudonym[pos + 1..];
-            // Hash should be exactly 16 hex characters
-            hash_part.len() == 16 && hash_part.chars().all(|c| c.is_ascii_hexdigit())
-        }
-        None => false,
-    }
+    crate::contract::is_valid_pseudonym(pseudonym)
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@
 // This module implements session-scoped state management
 
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Thread-safe in-memory state for pseudonym mappings
@@ -17,6 +18,8 @@ pub struct MappingState {
     forward: Arc<DashMap<String, String>>,
     /// Map: original text -> pseudonym
     reverse: Arc<DashMap<String, String>>,
+    /// Tenant that owns the session (None = not bound to a tenant)
+    tenant_id: Option<String>,
 }
 
 impl MappingState {
@@ -25,9 +28,23 @@ impl MappingState {
         Self {
             forward: Arc::new(DashMap::new()),
             reverse: Arc::new(DashMap::new()),
+            tenant_id: None,
         }
     }
 
+    /// Create a new empty mapping state owned by a tenant
+    pub fn for_tenant(tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: Some(tenant_id.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Check whether a tenant may read this session's mappings
+    pub fn belongs_to(&self, tenant_id: &str) -> bool {
+        self.tenant_id.as_deref().is_none_or(|owner| owner == tenant_id)
+    }
+
     /// Store a mapping: pseudonym -> original
     ///
     /// # Arguments
@@ -60,6 +77,56 @@ impl MappingState {
         self.reverse.get(original).map(|v| v.clone())
     }
 
+    /// Replace every pseudonym in `text` with its original
+    ///
+    /// Scans left to right and substitutes the longest pseudonym starting at
+    /// each position, so restored originals are never rescanned and a token
+    /// that prefixes another cannot split it.
+    ///
+    /// # Returns
+    /// The restored text and the number of substitutions made
+    pub fn restore(&self, text: &str) -> (String, usize) {
+        if self.forward.is_empty() {
+            return (text.to_string(), 0);
+        }
+
+        // Bucket pseudonyms by first byte, longest first within a bucket
+        let mut buckets: HashMap<u8, Vec<(String, String)>> = HashMap::new();
+        for entry in self.forward.iter() {
+            if let Some(&first) = entry.key().as_bytes().first() {
+                buckets
+                    .entry(first)
+                    .or_default()
+                    .push((entry.key().clone(), entry.value().clone()));
+            }
+        }
+        for tokens in buckets.values_mut() {
+            tokens.sort_by_key(|(token, _)| std::cmp::Reverse(token.len()));
+        }
+
+        let mut restored = String::with_capacity(text.len());
+        let mut count = 0;
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let matched = buckets
+                .get(&rest.as_bytes()[0])
+                .and_then(|tokens| tokens.iter().find(|(token, _)| rest.starts_with(token.as_str())));
+            match matched {
+                Some((token, original)) => {
+                    restored.push_str(original);
+                    rest = &rest[token.len()..];
+                    count += 1;
+                }
+                None => {
+                    restored.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        (restored, count)
+    }
+
     /// Check if a pseudonym exists
     pub fn contains_pseudonym(&self, pseudonym: &str) -> bool {
         self.forward.contains_key(pseudonym)
@@ -277,6 +344,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_restore_replaces_every_token() {
+        let state = MappingState::new();
+        state.insert("PERSON_a1b2c3d4e5f6a7b8".to_string(), "John Doe".to_string());
+        state.insert("EMAIL_0f1e2d3c4b5a6978".to_string(), "john@example.com".to_string());
+        state.insert("555-987-6543".to_string(), "555-123-4567".to_string());
+
+        let (text, count) = state.restore(
+            "Hi PERSON_a1b2c3d4e5f6a7b8, I'll mail EMAIL_0f1e2d3c4b5a6978 \
+             and call 555-987-6543. Thanks, PERSON_a1b2c3d4e5f6a7b8!",
+        );
+
+        assert_eq!(
+            text,
+            "Hi John Doe, I'll mail john@example.com \
+             and call 555-123-4567. Thanks, John Doe!"
+        );
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_restore_prefers_longest_token() {
+        let state = MappingState::new();
+        state.insert("555-0100".to_string(), "short".to_string());
+        state.insert("555-0100-22".to_string(), "long".to_string());
+
+        let (text, count) = state.restore("call 555-0100-22 or 555-0100");
+
+        assert_eq!(text, "call long or short");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_restore_does_not_rescan_originals() {
+        let state = MappingState::new();
+        // The original of one token is itself another token
+        state.insert("PERSON_1111".to_string(), "PERSON_2222".to_string());
+        state.insert("PERSON_2222".to_string(), "Bob".to_string());
+
+        let (text, count) = state.restore("→ PERSON_1111 ←");
+
+        assert_eq!(text, "→ PERSON_2222 ←");
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_restore_without_tokens() {
+        let state = MappingState::new();
+        assert_eq!(state.restore("nothing here"), ("nothing here".to_string(), 0));
+
+        state.insert("PERSON_abc".to_string(), "Alice".to_string());
+        assert_eq!(state.restore("no pseudonyms"), ("no pseudonyms".to_string(), 0));
+    }
+
+    #[test]
+    fn test_tenant_binding() {
+        let owned = MappingState::for_tenant("org1");
+        assert!(owned.belongs_to("org1"));
+        assert!(!owned.belongs_to("org2"));
+
+        // Clones share the owner
+        assert!(!owned.clone().belongs_to("org2"));
+
+        assert!(MappingState::new().belongs_to("any"));
+    }
+
     #[test]
     fn test_empty_state() {
         let state = MappingState::new();
@@ -49,6 +49,10 @@ pub struct Profile {
     /// RBAC/ABAC policy rules
     pub policies: Vec<Policy>,
     
+    /// Multi-level approval rules for tasks created by this role
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approval_rules: Vec<ApprovalRule>,
+    
     /// Privacy Guard configuration
     pub privacy: PrivacyConfig,
     
@@ -401,6 +405,45 @@ impl<'de> Deserialize<'de> for Policy {
     }
 }
 
+/// Approval rule (multi-level approval chain)
+///
+/// When a task created by this role matches the rule, every approver role
+/// must sign off, in order, before the task is approved. Approvers are
+/// resolved by walking the requester's management chain in the org chart.
+/// If several rules match, the one with the highest threshold wins.
+///
+/// ```yaml
+/// approval_rules:
+///   - name: "spend-over-10k"
+///     task_type: "budget_approval"
+///     attribute: "amount"
+///     greater_than: 10000
+///     approvers: ["manager"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRule {
+    /// Rule name (used in logs and audit)
+    pub name: String,
+    
+    /// Task type the rule applies to (None = all task types)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_type: Option<String>,
+    
+    /// Numeric attribute in task data to compare (e.g., "amount")
+    #[serde(default = "default_approval_attribute")]
+    pub attribute: String,
+    
+    /// Rule applies when the attribute is strictly greater than this value
+    pub greater_than: f64,
+    
+    /// Approver roles, in the order they must sign off
+    pub approvers: Vec<String>,
+}
+
+fn default_approval_attribute() -> String {
+    "amount".to_string()
+}
+
 /// Privacy Guard configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PrivacyConfig {
@@ -481,6 +524,7 @@ impl Default for Profile {
             recipes: Vec::new(),
             automated_tasks: Vec::new(),
             policies: Vec::new(),
+            approval_rules: Vec::new(),
             privacy: PrivacyConfig::default(),
             env_vars: HashMap::new(),
             signature: None,
@@ -558,6 +602,7 @@ mod tests {
             recipes: vec![],
             automated_tasks: vec![],
             policies: vec![],
+            approval_rules: vec![],
             privacy: PrivacyConfig::default(),
             env_vars: HashMap::new(),
             signature: None,
@@ -746,4 +791,38 @@ env_vars: {}
         assert_eq!(profile.policies[1].rule_type, "deny_tool");
         assert_eq!(profile.policies[1].pattern, "developer__shell");
     }
+
+    #[test]
+    fn test_approval_rules_yaml_deserialization() {
+        let yaml = r#"
+- name: "spend-over-10k"
+  task_type: "budget_approval"
+  greater_than: 10000
+  approvers: ["manager"]
+- name: "spend-over-50k"
+  attribute: "total"
+  greater_than: 50000
+  approvers: ["finance", "manager"]
+"#;
+
+        let rules: Vec<ApprovalRule> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rules.len(), 2);
+
+        // Attribute defaults to "amount"
+        assert_eq!(rules[0].attribute, "amount");
+        assert_eq!(rules[0].task_type, Some("budget_approval".to_string()));
+        assert_eq!(rules[0].greater_than, 10000.0);
+
+        assert_eq!(rules[1].attribute, "total");
+        assert!(rules[1].task_type.is_none());
+        assert_eq!(rules[1].approvers, vec!["finance".to_string(), "manager".to_string()]);
+    }
+
+    #[test]
+    fn test_empty_approval_rules_not_serialized() {
+        // Keeps signatures of profiles without approval rules stable
+        let profile = Profile::default();
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(!json.contains("approval_rules"));
+    }
 }
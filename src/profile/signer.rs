@@ -150,6 +150,7 @@ mod tests {
             recipes: vec![],
             automated_tasks: vec![],
             policies: vec![],
+            approval_rules: vec![],
             privacy: PrivacyConfig::default(),
             env_vars: HashMap::new(),
             signature: None,
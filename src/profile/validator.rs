@@ -21,6 +21,7 @@ impl ProfileValidator {
     /// 5. Strictness must be valid ("strict", "moderate", "permissive")
     /// 6. Policy rule types must be valid
     /// 7. Required fields must be non-empty
+    /// 8. Approval rules must name at least one approver and a finite threshold
     pub fn validate(profile: &Profile) -> Result<()> {
         // 1. Validate required fields
         Self::validate_required_fields(profile)?;
@@ -40,6 +41,9 @@ impl ProfileValidator {
         // 6. Validate policies
         Self::validate_policies(profile)?;
         
+        // 7. Validate approval rules
+        Self::validate_approval_rules(profile)?;
+        
         Ok(())
     }
     
@@ -217,6 +221,37 @@ impl ProfileValidator {
         
         Ok(())
     }
+    
+    /// Validate approval rules
+    fn validate_approval_rules(profile: &Profile) -> Result<()> {
+        for rule in &profile.approval_rules {
+            if rule.name.is_empty() {
+                bail!("Approval rule name cannot be empty");
+            }
+            
+            if rule.attribute.is_empty() {
+                bail!("Approval rule '{}' attribute cannot be empty", rule.name);
+            }
+            
+            if !rule.greater_than.is_finite() || rule.greater_than < 0.0 {
+                bail!(
+                    "Approval rule '{}' threshold must be a non-negative number, got: {}",
+                    rule.name,
+                    rule.greater_than
+                );
+            }
+            
+            if rule.approvers.is_empty() {
+                bail!("Approval rule '{}' must list at least one approver role", rule.name);
+            }
+            
+            if rule.approvers.iter().any(|r| r.is_empty()) {
+                bail!("Approval rule '{}' contains an empty approver role", rule.name);
+            }
+        }
+        
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -263,6 +298,7 @@ mod tests {
                 conditions: None,
                 reason: Some("No code execution for Finance role".to_string()),
             }],
+            approval_rules: Vec::new(),
             privacy: PrivacyConfig {
                 mode: "strict".to_string(),
                 strictness: "strict".to_string(),
@@ -451,4 +487,36 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("replacement cannot be empty"));
     }
+
+    #[test]
+    fn test_approval_rule_without_approvers() {
+        let mut profile = create_valid_finance_profile();
+        profile.approval_rules.push(ApprovalRule {
+            name: "spend-over-10k".to_string(),
+            task_type: None,
+            attribute: "amount".to_string(),
+            greater_than: 10000.0,
+            approvers: Vec::new(),
+        });
+        
+        let result = ProfileValidator::validate_approval_rules(&profile);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("at least one approver"));
+    }
+
+    #[test]
+    fn test_approval_rule_negative_threshold() {
+        let mut profile = create_valid_finance_profile();
+        profile.approval_rules.push(ApprovalRule {
+            name: "spend-over-10k".to_string(),
+            task_type: None,
+            attribute: "amount".to_string(),
+            greater_than: -1.0,
+            approvers: vec!["manager".to_string()],
+        });
+        
+        let result = ProfileValidator::validate_approval_rules(&profile);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("non-negative"));
+    }
 }
@@ -165,6 +165,7 @@ mod tests {
             recipes: vec![],
             automated_tasks: vec![],
             policies: vec![],
+            approval_rules: vec![],
             privacy: PrivacyConfig::default(),
             env_vars: std::collections::HashMap::new(),
             signature: None,  // No signature
@@ -188,6 +189,7 @@ mod tests {
             recipes: vec![],
             automated_tasks: vec![],
             policies: vec![],
+            approval_rules: vec![],
             privacy: PrivacyConfig::default(),
             env_vars: std::collections::HashMap::new(),
             signature: Some(Signature {
@@ -232,6 +234,7 @@ mod tests {
             recipes: vec![],
             automated_tasks: vec![],
             policies: vec![],
+            approval_rules: vec![],
             privacy: PrivacyConfig::default(),
             env_vars: std::collections::HashMap::new(),
             signature: None,
@@ -291,6 +294,7 @@ mod tests {
             recipes: vec![],
             automated_tasks: vec![],
             policies: vec![],
+            approval_rules: vec![],
             privacy: PrivacyConfig::default(),
             env_vars: std::collections::HashMap::new(),
             signature: None,